@@ -0,0 +1,373 @@
+use bevy::{prelude::*, ecs::system::SystemParam};
+use serde::{Deserialize, Serialize};
+use std::{collections::{HashMap, HashSet}, path::PathBuf};
+use crate::{
+    game::{AppState, GameState, ItemCollectedEvent, UpgradeChosenEvent},
+    horror::{HorrorKilledEvent, HorrorType},
+    items::{AutomaticWeaponId, AutomaticWeaponLibrary, ItemId, ItemLibrary},
+    run_statistics::RunStatistics,
+    audio::{PlaySoundEvent, SoundEffect},
+};
+
+const PROFILE_FILE_NAME: &str = "eldritch_profile.json";
+const TOAST_DURATION_SECS: f32 = 4.0;
+const TOAST_FADE_SECS: f32 = 0.6;
+const TOAST_WIDTH: f32 = 340.0;
+const TOAST_GAP: f32 = 8.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AchievementId(pub u32);
+
+/// What has to happen within a single run for an achievement to unlock.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AchievementCondition {
+    KillHorrors(u32),
+    KillHorrorsOfType { horror_type: HorrorType, count: u32 },
+    KillElites(u32),
+    ReachCycle(u32),
+    ReachCycleWithoutDamage(u32),
+    ChainLightningKills(u32),
+    CollectRelics(u32),
+    ChooseUpgrades(u32),
+}
+
+impl AchievementCondition {
+    fn is_met(&self, progress: &AchievementRunProgress, cycle_number: u32, took_damage: bool) -> bool {
+        match *self {
+            AchievementCondition::KillHorrors(count) => progress.kills >= count,
+            AchievementCondition::KillHorrorsOfType { horror_type, count } => progress.kills_by_type.get(&horror_type).copied().unwrap_or(0) >= count,
+            AchievementCondition::KillElites(count) => progress.elite_kills >= count,
+            AchievementCondition::ReachCycle(cycle) => cycle_number >= cycle,
+            AchievementCondition::ReachCycleWithoutDamage(cycle) => cycle_number >= cycle && !took_damage,
+            AchievementCondition::ChainLightningKills(count) => progress.best_chain_lightning_kills >= count,
+            AchievementCondition::CollectRelics(count) => progress.relics_collected.len() as u32 >= count,
+            AchievementCondition::ChooseUpgrades(count) => progress.upgrades_chosen >= count,
+        }
+    }
+}
+
+/// Content that stays locked until the rewarding achievement is earned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnlockReward {
+    Relic(ItemId),
+    StartingWeapon(AutomaticWeaponId),
+}
+
+#[derive(Debug, Clone)]
+pub struct AchievementDefinition { pub id: AchievementId, pub name: String, pub description: String, pub condition: AchievementCondition, pub rewards: Vec<UnlockReward>, }
+
+#[derive(Resource, Default)]
+pub struct AchievementLibrary { pub achievements: Vec<AchievementDefinition>, }
+impl AchievementLibrary { pub fn get_achievement_definition(&self, id: AchievementId) -> Option<&AchievementDefinition> { self.achievements.iter().find(|def| def.id == id) } }
+
+/// Persisted across runs; written to disk whenever something new is unlocked.
+#[derive(Resource, Default, Debug, Serialize, Deserialize)]
+pub struct PlayerProfile {
+    pub unlocked_achievements: Vec<AchievementId>,
+    pub runs_started: u32,
+    #[serde(default)] pub forbidden_lore: u64,
+    /// Achievements whose rewards were bought with Forbidden Lore rather than earned.
+    #[serde(default)] pub lore_claimed_achievements: Vec<AchievementId>,
+}
+
+impl PlayerProfile {
+    pub fn has_achievement(&self, id: AchievementId) -> bool { self.unlocked_achievements.contains(&id) }
+    pub fn has_claimed_rewards(&self, id: AchievementId) -> bool { self.has_achievement(id) || self.lore_claimed_achievements.contains(&id) }
+
+    /// Spends `price` Forbidden Lore to claim an achievement's rewards early. Returns false if the profile can't afford it.
+    pub fn claim_rewards_with_lore(&mut self, id: AchievementId, price: u64) -> bool {
+        if self.has_claimed_rewards(id) || self.forbidden_lore < price { return false; }
+        self.forbidden_lore -= price;
+        self.lore_claimed_achievements.push(id);
+        self.save();
+        true
+    }
+
+    /// Kept next to the executable so the profile follows the install rather than whatever directory the game was launched from.
+    fn path() -> PathBuf {
+        std::env::current_exe().ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.join(PROFILE_FILE_NAME)))
+            .unwrap_or_else(|| PathBuf::from(PROFILE_FILE_NAME))
+    }
+
+    fn load() -> Self {
+        let path = Self::path();
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|err| { warn!("Profile at {} is unreadable ({}), starting fresh.", path.display(), err); Self::default() }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let path = Self::path();
+        let result = serde_json::to_string_pretty(self).map_err(std::io::Error::other).and_then(|json| std::fs::write(&path, json));
+        if let Err(err) = result { warn!("Failed to save profile to {}: {}", path.display(), err); }
+    }
+}
+
+/// Relics and starting weapons that are rewards of achievements the profile has neither earned nor bought yet.
+#[derive(Resource, Default, Debug)]
+pub struct ContentLocks { pub items: HashSet<ItemId>, pub starting_weapons: HashSet<AutomaticWeaponId>, }
+
+impl ContentLocks {
+    fn rebuild(&mut self, library: &AchievementLibrary, profile: &PlayerProfile) {
+        self.items.clear();
+        self.starting_weapons.clear();
+        for achievement in library.achievements.iter().filter(|def| !profile.has_claimed_rewards(def.id)) {
+            for reward in &achievement.rewards {
+                match reward {
+                    UnlockReward::Relic(item_id) => { self.items.insert(*item_id); }
+                    UnlockReward::StartingWeapon(weapon_id) => { self.starting_weapons.insert(*weapon_id); }
+                }
+            }
+        }
+    }
+}
+
+/// Gameplay code sends these for conditions that no existing event describes.
+#[derive(Event, Debug, Clone, Copy)]
+pub enum AchievementTriggerEvent {
+    ChainLightningResolved { kills: u32 },
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct AchievementUnlockedEvent(pub AchievementId);
+
+#[derive(Resource, Default, Debug)]
+struct AchievementRunProgress {
+    kills: u32,
+    kills_by_type: HashMap<HorrorType, u32>,
+    elite_kills: u32,
+    best_chain_lightning_kills: u32,
+    relics_collected: HashSet<ItemId>,
+    upgrades_chosen: u32,
+}
+
+#[derive(Component)]
+struct AchievementToast { timer: Timer, }
+
+/// Holds the toasts as a flex column, so the survivors move up when one expires instead of leaving a gap or overlapping.
+#[derive(Component)]
+struct AchievementToastColumn;
+
+#[derive(SystemParam)]
+struct UnlockRewardNames<'w> { item_library: Res<'w, ItemLibrary>, weapon_library: Res<'w, AutomaticWeaponLibrary>, }
+
+impl UnlockRewardNames<'_> {
+    fn describe(&self, reward: &UnlockReward) -> String {
+        match reward {
+            UnlockReward::Relic(item_id) => format!("New relic: {}", self.item_library.get_item_definition(*item_id).map_or("Unknown", |def| def.name.as_str())),
+            UnlockReward::StartingWeapon(weapon_id) => format!("New starting weapon: {}", self.weapon_library.get_weapon_definition(*weapon_id).map_or("Unknown", |def| def.name.as_str())),
+        }
+    }
+}
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<AchievementTriggerEvent>()
+            .add_event::<AchievementUnlockedEvent>()
+            .init_resource::<AchievementLibrary>()
+            .init_resource::<ContentLocks>()
+            .init_resource::<AchievementRunProgress>()
+            .insert_resource(PlayerProfile::load())
+            .add_systems(Startup, ((populate_achievement_library, rebuild_content_locks_system).chain(), setup_achievement_toast_column))
+            .add_systems(OnExit(AppState::MainMenu), begin_run_progress_system)
+            .add_systems(Update, (
+                track_achievement_progress_system,
+                evaluate_achievements_system,
+            ).chain().run_if(in_state(AppState::InGame).or_else(in_state(AppState::LevelUp))))
+            .add_systems(Update, rebuild_content_locks_system.run_if(resource_changed::<PlayerProfile>))
+            .add_systems(Update, (
+                spawn_achievement_toast_system,
+                achievement_toast_lifetime_system,
+            ).chain());
+    }
+}
+
+fn populate_achievement_library(mut library: ResMut<AchievementLibrary>) {
+    library.achievements.push(AchievementDefinition { id: AchievementId(1), name: "Culling the Swarm".to_string(), description: "Slay 500 horrors in a single run.".to_string(), condition: AchievementCondition::KillHorrors(500), rewards: vec![], });
+    library.achievements.push(AchievementDefinition { id: AchievementId(2), name: "Stormcaller".to_string(), description: "Slay 50 horrors with a single chain lightning.".to_string(), condition: AchievementCondition::ChainLightningKills(50), rewards: vec![UnlockReward::Relic(ItemId(14))], });
+    library.achievements.push(AchievementDefinition { id: AchievementId(3), name: "Untouched by Madness".to_string(), description: "Reach cycle 10 without taking damage.".to_string(), condition: AchievementCondition::ReachCycleWithoutDamage(10), rewards: vec![UnlockReward::Relic(ItemId(13))], });
+    library.achievements.push(AchievementDefinition { id: AchievementId(4), name: "The Endless Night".to_string(), description: "Reach cycle 20.".to_string(), condition: AchievementCondition::ReachCycle(20), rewards: vec![UnlockReward::StartingWeapon(AutomaticWeaponId(2))], });
+    library.achievements.push(AchievementDefinition { id: AchievementId(5), name: "Relic Hoarder".to_string(), description: "Collect 6 different relics in a single run.".to_string(), condition: AchievementCondition::CollectRelics(6), rewards: vec![UnlockReward::StartingWeapon(AutomaticWeaponId(1))], });
+    library.achievements.push(AchievementDefinition { id: AchievementId(6), name: "Behemoth Breaker".to_string(), description: "Slay 25 Frenzied Behemoths in a single run.".to_string(), condition: AchievementCondition::KillHorrorsOfType { horror_type: HorrorType::FrenziedBehemoth, count: 25 }, rewards: vec![], });
+    library.achievements.push(AchievementDefinition { id: AchievementId(7), name: "Elite Hunter".to_string(), description: "Slay 10 elite horrors in a single run.".to_string(), condition: AchievementCondition::KillElites(10), rewards: vec![], });
+    library.achievements.push(AchievementDefinition { id: AchievementId(8), name: "Student of the Void".to_string(), description: "Choose 15 upgrades in a single run.".to_string(), condition: AchievementCondition::ChooseUpgrades(15), rewards: vec![], });
+}
+
+fn rebuild_content_locks_system(library: Res<AchievementLibrary>, profile: Res<PlayerProfile>, mut content_locks: ResMut<ContentLocks>) {
+    content_locks.rebuild(&library, &profile);
+}
+
+fn begin_run_progress_system(mut progress: ResMut<AchievementRunProgress>, mut profile: ResMut<PlayerProfile>) {
+    *progress = AchievementRunProgress::default();
+    profile.runs_started += 1;
+    profile.save();
+}
+
+fn track_achievement_progress_system(
+    mut progress: ResMut<AchievementRunProgress>,
+    mut kill_events: EventReader<HorrorKilledEvent>,
+    mut item_events: EventReader<ItemCollectedEvent>,
+    mut upgrade_events: EventReader<UpgradeChosenEvent>,
+    mut trigger_events: EventReader<AchievementTriggerEvent>,
+) {
+    for event in kill_events.read() {
+        progress.kills += 1;
+        *progress.kills_by_type.entry(event.horror_type).or_insert(0) += 1;
+        if event.is_elite { progress.elite_kills += 1; }
+    }
+    for event in item_events.read() { progress.relics_collected.insert(event.0); }
+    progress.upgrades_chosen += upgrade_events.read().count() as u32;
+    for event in trigger_events.read() {
+        match event {
+            AchievementTriggerEvent::ChainLightningResolved { kills } => { progress.best_chain_lightning_kills = progress.best_chain_lightning_kills.max(*kills); }
+        }
+    }
+}
+
+fn evaluate_achievements_system(
+    library: Res<AchievementLibrary>,
+    mut profile: ResMut<PlayerProfile>,
+    mut content_locks: ResMut<ContentLocks>,
+    progress: Res<AchievementRunProgress>,
+    game_state: Res<GameState>,
+    run_stats: Res<RunStatistics>,
+    mut unlocked_event_writer: EventWriter<AchievementUnlockedEvent>,
+) {
+    let took_damage = run_stats.damage_taken_by_horror_type.values().any(|damage| *damage > 0);
+    let newly_unlocked: Vec<AchievementId> = library.achievements.iter()
+        .filter(|def| !profile.has_achievement(def.id) && def.condition.is_met(&progress, game_state.cycle_number, took_damage))
+        .map(|def| def.id)
+        .collect();
+    if newly_unlocked.is_empty() { return; }
+    for achievement_id in newly_unlocked {
+        profile.unlocked_achievements.push(achievement_id);
+        unlocked_event_writer.send(AchievementUnlockedEvent(achievement_id));
+    }
+    content_locks.rebuild(&library, &profile);
+    profile.save();
+}
+
+fn setup_achievement_toast_column(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style { position_type: PositionType::Absolute, right: Val::Px(20.0), top: Val::Px(60.0), width: Val::Px(TOAST_WIDTH), flex_direction: FlexDirection::Column, row_gap: Val::Px(TOAST_GAP), ..default() },
+            z_index: ZIndex::Global(10),
+            ..default()
+        },
+        AchievementToastColumn,
+        Name::new("AchievementToastColumn"),
+    ));
+}
+
+fn spawn_achievement_toast_system(
+    mut commands: Commands,
+    mut events: EventReader<AchievementUnlockedEvent>,
+    library: Res<AchievementLibrary>,
+    reward_names: UnlockRewardNames,
+    column_query: Query<Entity, With<AchievementToastColumn>>,
+    asset_server: Res<AssetServer>,
+    mut sound_event_writer: EventWriter<PlaySoundEvent>,
+) {
+    let Ok(column) = column_query.get_single() else { return; };
+    for event in events.read() {
+        let Some(achievement) = library.get_achievement_definition(event.0) else { continue; };
+        let reward_lines: Vec<String> = achievement.rewards.iter().map(|reward| reward_names.describe(reward)).collect();
+        let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+        sound_event_writer.send(PlaySoundEvent(SoundEffect::Revelation));
+        let toast = commands.spawn((
+            NodeBundle {
+                style: Style { width: Val::Percent(100.0), flex_direction: FlexDirection::Column, padding: UiRect::all(Val::Px(10.0)), row_gap: Val::Px(2.0), ..default() },
+                background_color: Color::rgba(0.1, 0.05, 0.15, 0.9).into(),
+                border_color: Color::rgb(0.9, 0.8, 0.5).into(),
+                ..default()
+            },
+            AchievementToast { timer: Timer::from_seconds(TOAST_DURATION_SECS, TimerMode::Once) },
+            Name::new("AchievementToast"),
+        )).with_children(|toast| {
+            toast.spawn(TextBundle::from_section(format!("Achievement: {}", achievement.name), TextStyle { font: font.clone(), font_size: 20.0, color: Color::rgb(0.9, 0.8, 0.5) }));
+            toast.spawn(TextBundle::from_section(achievement.description.clone(), TextStyle { font: font.clone(), font_size: 15.0, color: Color::WHITE }));
+            for line in reward_lines { toast.spawn(TextBundle::from_section(line, TextStyle { font: font.clone(), font_size: 15.0, color: Color::rgb(0.6, 1.0, 0.6) })); }
+        }).id();
+        commands.entity(column).add_child(toast);
+    }
+}
+
+fn achievement_toast_lifetime_system(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut toast_query: Query<(Entity, &mut AchievementToast, &mut BackgroundColor, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (toast_entity, mut toast, mut background, children) in toast_query.iter_mut() {
+        toast.timer.tick(time.delta());
+        if toast.timer.finished() { commands.entity(toast_entity).despawn_recursive(); continue; }
+        let alpha = (toast.timer.remaining_secs() / TOAST_FADE_SECS).min(1.0);
+        background.0.set_a(0.9 * alpha);
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                for section in text.sections.iter_mut() { section.style.color.set_a(alpha); }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn achievement(id: u32, condition: AchievementCondition, rewards: Vec<UnlockReward>) -> AchievementDefinition {
+        AchievementDefinition { id: AchievementId(id), name: format!("Achievement {}", id), description: String::new(), condition, rewards }
+    }
+
+    #[test]
+    fn kill_conditions_unlock_at_their_thresholds() {
+        let mut progress = AchievementRunProgress { kills: 499, elite_kills: 10, ..default() };
+        progress.kills_by_type.insert(HorrorType::FrenziedBehemoth, 25);
+        assert!(!AchievementCondition::KillHorrors(500).is_met(&progress, 1, false));
+        progress.kills += 1;
+        assert!(AchievementCondition::KillHorrors(500).is_met(&progress, 1, false));
+        assert!(AchievementCondition::KillElites(10).is_met(&progress, 1, false));
+        assert!(AchievementCondition::KillHorrorsOfType { horror_type: HorrorType::FrenziedBehemoth, count: 25 }.is_met(&progress, 1, false));
+        assert!(!AchievementCondition::KillHorrorsOfType { horror_type: HorrorType::SkitteringShadowling, count: 1 }.is_met(&progress, 1, false));
+    }
+
+    #[test]
+    fn damage_taken_blocks_the_untouched_cycle_condition_only() {
+        let progress = AchievementRunProgress::default();
+        assert!(AchievementCondition::ReachCycleWithoutDamage(10).is_met(&progress, 10, false));
+        assert!(!AchievementCondition::ReachCycleWithoutDamage(10).is_met(&progress, 10, true));
+        assert!(!AchievementCondition::ReachCycleWithoutDamage(10).is_met(&progress, 9, false));
+        assert!(AchievementCondition::ReachCycle(10).is_met(&progress, 10, true));
+    }
+
+    #[test]
+    fn chain_lightning_condition_uses_the_best_single_chain() {
+        let progress = AchievementRunProgress { best_chain_lightning_kills: 49, ..default() };
+        assert!(!AchievementCondition::ChainLightningKills(50).is_met(&progress, 1, false));
+        let progress = AchievementRunProgress { best_chain_lightning_kills: 50, ..default() };
+        assert!(AchievementCondition::ChainLightningKills(50).is_met(&progress, 1, false));
+    }
+
+    #[test]
+    fn rebuild_locks_only_rewards_of_unearned_achievements() {
+        let library = AchievementLibrary { achievements: vec![
+            achievement(1, AchievementCondition::KillHorrors(1), vec![UnlockReward::Relic(ItemId(13))]),
+            achievement(2, AchievementCondition::KillHorrors(1), vec![UnlockReward::Relic(ItemId(14)), UnlockReward::StartingWeapon(AutomaticWeaponId(2))]),
+        ] };
+        let mut profile = PlayerProfile::default();
+        let mut locks = ContentLocks::default();
+        locks.rebuild(&library, &profile);
+        assert_eq!(locks.items, HashSet::from([ItemId(13), ItemId(14)]));
+        assert_eq!(locks.starting_weapons, HashSet::from([AutomaticWeaponId(2)]));
+
+        profile.unlocked_achievements.push(AchievementId(2));
+        locks.rebuild(&library, &profile);
+        assert_eq!(locks.items, HashSet::from([ItemId(13)]));
+        assert!(locks.starting_weapons.is_empty());
+    }
+}
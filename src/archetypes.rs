@@ -0,0 +1,309 @@
+use bevy::{prelude::*, ecs::system::SystemParam};
+use crate::{
+    game::AppState,
+    items::{AutomaticWeaponId, AutomaticWeaponLibrary},
+    skills::{SkillId, SkillLibrary},
+    achievements::{AchievementDefinition, AchievementLibrary, ContentLocks, PlayerProfile, UnlockReward},
+    audio::{PlaySoundEvent, SoundEffect},
+    survivor::Survivor,
+    weapons::CircleOfWarding,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Default)]
+pub struct ArchetypeId(pub u32);
+
+/// The one trait each archetype has that no other archetype shares.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub enum ArchetypePassive {
+    ExtraChains { chains: u32, range_bonus: f32 },
+    EchoAttunement { xp_gain_bonus: f32 },
+    Bloodletting { regen_per_second: f32 },
+    WardingAura { damage_per_tick: i32, radius: f32, tick_interval_secs: f32 },
+}
+
+impl ArchetypePassive {
+    pub fn describe(&self) -> String {
+        match self {
+            ArchetypePassive::ExtraChains { chains, range_bonus } => format!("Stormbound: chain lightning arcs to {} more horror(s) with {:.0}% more range.", chains, range_bonus * 100.0),
+            ArchetypePassive::EchoAttunement { xp_gain_bonus } => format!("Echo Attunement: gains {:.0}% more insight from echoes.", xp_gain_bonus * 100.0),
+            ArchetypePassive::Bloodletting { regen_per_second } => format!("Bloodletting: regenerates {:.1} endurance per second.", regen_per_second),
+            ArchetypePassive::WardingAura { .. } => "Warding Aura: begins every run within a Circle of Warding.".to_string(),
+        }
+    }
+
+    pub fn apply(&self, survivor: &mut Survivor, circle_of_warding: &mut CircleOfWarding) {
+        match self {
+            ArchetypePassive::ExtraChains { chains, range_bonus } => { survivor.auto_weapon_chain_bonus += chains; survivor.auto_weapon_chain_range_multiplier *= 1.0 + range_bonus; }
+            ArchetypePassive::EchoAttunement { xp_gain_bonus } => { survivor.xp_gain_multiplier *= 1.0 + xp_gain_bonus; }
+            ArchetypePassive::Bloodletting { regen_per_second } => { survivor.health_regen_rate += regen_per_second; }
+            ArchetypePassive::WardingAura { damage_per_tick, radius, tick_interval_secs } => {
+                circle_of_warding.is_active = true;
+                circle_of_warding.base_damage_per_tick = *damage_per_tick;
+                circle_of_warding.current_radius = *radius;
+                circle_of_warding.damage_tick_timer = Timer::from_seconds(*tick_interval_secs, TimerMode::Repeating);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Reflect)]
+pub struct SurvivorArchetype {
+    pub id: ArchetypeId,
+    pub name: String,
+    pub description: String,
+    pub sprite_path: String,
+    pub max_health: i32,
+    pub speed: f32,
+    pub pickup_radius_multiplier: f32,
+    pub starting_weapon: AutomaticWeaponId,
+    pub starting_skill: SkillId,
+    pub passive: ArchetypePassive,
+}
+
+impl SurvivorArchetype {
+    /// Overrides a fresh survivor's base stats and weapon with this archetype's, then grants its passive.
+    pub fn apply_to(&self, survivor: &mut Survivor, circle_of_warding: &mut CircleOfWarding) {
+        survivor.max_health = self.max_health;
+        survivor.speed = self.speed;
+        survivor.pickup_radius_multiplier = self.pickup_radius_multiplier;
+        survivor.equipped_weapon_id = Some(self.starting_weapon);
+        self.passive.apply(survivor, circle_of_warding);
+    }
+}
+
+#[derive(Resource, Default, Reflect)] #[reflect(Resource)]
+pub struct ArchetypeLibrary { pub archetypes: Vec<SurvivorArchetype>, }
+impl ArchetypeLibrary { pub fn get_archetype(&self, id: ArchetypeId) -> Option<&SurvivorArchetype> { self.archetypes.iter().find(|def| def.id == id) } }
+
+/// Archetype picked on the character select screen; read by `spawn_survivor`.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct SelectedArchetype(pub ArchetypeId);
+impl Default for SelectedArchetype { fn default() -> Self { Self(ArchetypeId(1)) } }
+
+#[derive(Component)] struct CharacterSelectUI;
+#[derive(Component)] struct ArchetypeCard(usize);
+#[derive(Component)] struct CharacterSelectStatusText;
+
+#[derive(Resource, Default)]
+struct CharacterSelectCursor(usize);
+
+/// The archetype under the character select cursor.
+#[derive(SystemParam)]
+struct HighlightedArchetype<'w> { archetype_library: Res<'w, ArchetypeLibrary>, content_locks: Res<'w, ContentLocks>, cursor: Res<'w, CharacterSelectCursor>, }
+
+/// What the character select screen needs to explain, and lift, an archetype's lock.
+#[derive(SystemParam)]
+struct ArchetypeLocks<'w> { content_locks: Res<'w, ContentLocks>, achievement_library: Res<'w, AchievementLibrary>, profile: Res<'w, PlayerProfile>, }
+
+impl ArchetypeLocks<'_> {
+    fn lock_label(&self, archetype: &SurvivorArchetype) -> Option<String> {
+        if !self.content_locks.starting_weapons.contains(&archetype.starting_weapon) { return None; }
+        Some(unlocking_achievement(archetype, &self.achievement_library).map_or_else(
+            || "Locked".to_string(),
+            |def| format!("Locked: earn \"{}\"\nor offer {} Forbidden Lore", def.name, ARCHETYPE_LORE_PRICE),
+        ))
+    }
+}
+
+impl HighlightedArchetype<'_> {
+    fn get(&self) -> Option<&SurvivorArchetype> { self.archetype_library.archetypes.get(self.cursor.0) }
+    fn is_locked(&self, archetype: &SurvivorArchetype) -> bool { self.content_locks.starting_weapons.contains(&archetype.starting_weapon) }
+}
+
+/// Forbidden Lore it costs to claim a locked archetype without earning its achievement.
+const ARCHETYPE_LORE_PRICE: u64 = 150;
+const CARD_COLOR: Color = Color::rgba(0.1, 0.1, 0.15, 0.9);
+const CARD_SELECTED_COLOR: Color = Color::rgba(0.25, 0.15, 0.35, 0.95);
+const CARD_BORDER_COLOR: Color = Color::rgb(0.3, 0.3, 0.35);
+const CARD_SELECTED_BORDER_COLOR: Color = Color::rgb(0.9, 0.8, 0.5);
+
+pub struct ArchetypesPlugin;
+
+impl Plugin for ArchetypesPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .register_type::<ArchetypeLibrary>()
+            .init_resource::<ArchetypeLibrary>()
+            .init_resource::<SelectedArchetype>()
+            .init_resource::<CharacterSelectCursor>()
+            .add_systems(Startup, populate_archetype_library)
+            .add_systems(OnEnter(AppState::CharacterSelect), (reset_character_select_cursor, setup_character_select_ui))
+            .add_systems(Update, (
+                character_select_navigation_system,
+                character_select_confirm_system,
+                offer_forbidden_lore_system,
+                update_character_select_ui,
+            ).chain().run_if(in_state(AppState::CharacterSelect)))
+            // Rebuilt rather than patched when something is unlocked, since a lock touches several parts of a card.
+            .add_systems(Update, (despawn_character_select_ui, setup_character_select_ui).chain().run_if(in_state(AppState::CharacterSelect).and_then(resource_changed::<ContentLocks>)))
+            .add_systems(OnExit(AppState::CharacterSelect), despawn_character_select_ui);
+    }
+}
+
+fn populate_archetype_library(mut library: ResMut<ArchetypeLibrary>) {
+    library.archetypes.push(SurvivorArchetype { id: ArchetypeId(1), name: "The Wanderer".to_string(), description: "A drifter who learned to call the storm.".to_string(), sprite_path: "sprites/survivor_placeholder.png".to_string(), max_health: 100, speed: 250.0, pickup_radius_multiplier: 1.0, starting_weapon: AutomaticWeaponId(3), starting_skill: SkillId(1), passive: ArchetypePassive::ExtraChains { chains: 1, range_bonus: 0.15 }, });
+    library.archetypes.push(SurvivorArchetype { id: ArchetypeId(2), name: "The Scholar".to_string(), description: "Frail, but hears the echoes long before others.".to_string(), sprite_path: "sprites/survivor_scholar_placeholder.png".to_string(), max_health: 80, speed: 260.0, pickup_radius_multiplier: 1.4, starting_weapon: AutomaticWeaponId(0), starting_skill: SkillId(2), passive: ArchetypePassive::EchoAttunement { xp_gain_bonus: 0.25 }, });
+    library.archetypes.push(SurvivorArchetype { id: ArchetypeId(3), name: "The Flagellant".to_string(), description: "Pain is a prayer, and the flesh always answers.".to_string(), sprite_path: "sprites/survivor_flagellant_placeholder.png".to_string(), max_health: 140, speed: 220.0, pickup_radius_multiplier: 0.9, starting_weapon: AutomaticWeaponId(1), starting_skill: SkillId(7), passive: ArchetypePassive::Bloodletting { regen_per_second: 1.5 }, });
+    library.archetypes.push(SurvivorArchetype { id: ArchetypeId(4), name: "The Warden".to_string(), description: "Keeper of the old glyphs, slow and unbreakable.".to_string(), sprite_path: "sprites/survivor_warden_placeholder.png".to_string(), max_health: 120, speed: 230.0, pickup_radius_multiplier: 1.0, starting_weapon: AutomaticWeaponId(2), starting_skill: SkillId(5), passive: ArchetypePassive::WardingAura { damage_per_tick: 3, radius: 90.0, tick_interval_secs: 0.5 }, });
+}
+
+/// The achievement whose reward is this archetype's starting weapon, if any.
+fn unlocking_achievement<'a>(archetype: &SurvivorArchetype, achievement_library: &'a AchievementLibrary) -> Option<&'a AchievementDefinition> {
+    achievement_library.achievements.iter().find(|def| def.rewards.contains(&UnlockReward::StartingWeapon(archetype.starting_weapon)))
+}
+
+/// Start the cursor on whichever archetype was picked last.
+fn reset_character_select_cursor(archetype_library: Res<ArchetypeLibrary>, selected: Res<SelectedArchetype>, mut cursor: ResMut<CharacterSelectCursor>) {
+    cursor.0 = archetype_library.archetypes.iter().position(|def| def.id == selected.0).unwrap_or(0);
+}
+
+fn setup_character_select_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    archetype_library: Res<ArchetypeLibrary>,
+    weapon_library: Res<AutomaticWeaponLibrary>,
+    skill_library: Res<SkillLibrary>,
+    locks: ArchetypeLocks,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands.spawn((
+        NodeBundle { style: Style { width: Val::Percent(100.0), height: Val::Percent(100.0), justify_content: JustifyContent::Center, align_items: AlignItems::Center, flex_direction: FlexDirection::Column, row_gap: Val::Px(20.0), ..default() }, ..default() },
+        CharacterSelectUI,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("Choose Your Vessel", TextStyle { font: font.clone(), font_size: 60.0, color: Color::WHITE }).with_text_justify(JustifyText::Center));
+        parent.spawn(NodeBundle { style: Style { flex_direction: FlexDirection::Row, column_gap: Val::Px(16.0), ..default() }, ..default() }).with_children(|row| {
+            for (index, archetype) in archetype_library.archetypes.iter().enumerate() {
+                let weapon_name = weapon_library.get_weapon_definition(archetype.starting_weapon).map_or("Unknown", |def| def.name.as_str());
+                let skill_name = skill_library.get_skill_definition(archetype.starting_skill).map_or("Unknown", |def| def.name.as_str());
+                let lock_label = locks.lock_label(archetype);
+                row.spawn((
+                    NodeBundle {
+                        style: Style { width: Val::Px(230.0), flex_direction: FlexDirection::Column, align_items: AlignItems::Center, padding: UiRect::all(Val::Px(10.0)), row_gap: Val::Px(6.0), border: UiRect::all(Val::Px(3.0)), ..default() },
+                        background_color: CARD_COLOR.into(),
+                        border_color: CARD_BORDER_COLOR.into(),
+                        ..default()
+                    },
+                    ArchetypeCard(index),
+                )).with_children(|card| {
+                    card.spawn(ImageBundle { style: Style { width: Val::Px(64.0), height: Val::Px(64.0), ..default() }, image: UiImage::new(asset_server.load(archetype.sprite_path.clone())), background_color: if lock_label.is_some() { Color::rgb(0.2, 0.2, 0.2).into() } else { Color::WHITE.into() }, ..default() });
+                    card.spawn(TextBundle::from_section(archetype.name.clone(), TextStyle { font: font.clone(), font_size: 24.0, color: Color::rgb(0.9, 0.8, 0.5) }));
+                    card.spawn(TextBundle::from_section(archetype.description.clone(), TextStyle { font: font.clone(), font_size: 14.0, color: Color::rgba(0.8, 0.8, 0.8, 1.0) }).with_text_justify(JustifyText::Center));
+                    card.spawn(TextBundle::from_section(format!("Endurance {}  |  Speed {:.0}  |  Reach {:.0}%", archetype.max_health, archetype.speed, archetype.pickup_radius_multiplier * 100.0), TextStyle { font: font.clone(), font_size: 14.0, color: Color::WHITE }).with_text_justify(JustifyText::Center));
+                    card.spawn(TextBundle::from_section(format!("Weapon: {}\nSkill: {}", weapon_name, skill_name), TextStyle { font: font.clone(), font_size: 14.0, color: Color::CYAN }).with_text_justify(JustifyText::Center));
+                    card.spawn(TextBundle::from_section(archetype.passive.describe(), TextStyle { font: font.clone(), font_size: 14.0, color: Color::rgb(0.6, 1.0, 0.6) }).with_text_justify(JustifyText::Center));
+                    if let Some(lock_label) = lock_label {
+                        card.spawn(TextBundle::from_section(lock_label, TextStyle { font: font.clone(), font_size: 14.0, color: Color::rgb(1.0, 0.4, 0.4) }).with_text_justify(JustifyText::Center));
+                    }
+                });
+            }
+        });
+        parent.spawn((TextBundle::from_section("", TextStyle { font: font.clone(), font_size: 22.0, color: Color::rgb(1.0, 0.4, 0.4) }).with_text_justify(JustifyText::Center), CharacterSelectStatusText));
+        parent.spawn(TextBundle::from_section(format!("A/D or Arrows to choose  |  SPACE to descend  |  U to offer lore ({} held)  |  ESC to return", locks.profile.forbidden_lore), TextStyle { font, font_size: 24.0, color: Color::rgba(0.8, 0.8, 0.8, 1.0) }).with_text_justify(JustifyText::Center));
+    });
+}
+
+fn character_select_navigation_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    archetype_library: Res<ArchetypeLibrary>,
+    mut cursor: ResMut<CharacterSelectCursor>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    let archetype_count = archetype_library.archetypes.len();
+    if archetype_count == 0 { return; }
+    if keyboard_input.any_just_pressed([KeyCode::KeyD, KeyCode::ArrowRight]) { cursor.0 = (cursor.0 + 1) % archetype_count; }
+    if keyboard_input.any_just_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]) { cursor.0 = (cursor.0 + archetype_count - 1) % archetype_count; }
+    if keyboard_input.just_pressed(KeyCode::Escape) { next_app_state.set(AppState::MainMenu); }
+}
+
+fn character_select_confirm_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    highlighted: HighlightedArchetype,
+    mut selected: ResMut<SelectedArchetype>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut status_text_query: Query<&mut Text, With<CharacterSelectStatusText>>,
+    mut sound_event_writer: EventWriter<PlaySoundEvent>,
+) {
+    // Escape was pressed this frame; the navigation system has already queued the way back.
+    if next_app_state.0.is_some() || !keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Enter]) { return; }
+    let Some(archetype) = highlighted.get() else { return; };
+    if highlighted.is_locked(archetype) {
+        for mut text in status_text_query.iter_mut() { text.sections[0].value = format!("{} has not yet answered your call.", archetype.name); }
+        return;
+    }
+    selected.0 = archetype.id;
+    sound_event_writer.send(PlaySoundEvent(SoundEffect::RitualCast));
+    next_app_state.set(AppState::OmenSelect);
+}
+
+fn offer_forbidden_lore_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    highlighted: HighlightedArchetype,
+    achievement_library: Res<AchievementLibrary>,
+    mut profile: ResMut<PlayerProfile>,
+    mut status_text_query: Query<&mut Text, With<CharacterSelectStatusText>>,
+    mut sound_event_writer: EventWriter<PlaySoundEvent>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyU) { return; }
+    let Some(archetype) = highlighted.get() else { return; };
+    if !highlighted.is_locked(archetype) { return; }
+    let Some(achievement) = unlocking_achievement(archetype, &achievement_library) else { return; };
+    // Checked up front so a refused offer doesn't touch the profile, which would rebuild the screen and wipe this message.
+    if profile.forbidden_lore < ARCHETYPE_LORE_PRICE {
+        for mut text in status_text_query.iter_mut() { text.sections[0].value = format!("{} demands {} Forbidden Lore; you hold {}.", archetype.name, ARCHETYPE_LORE_PRICE, profile.forbidden_lore); }
+        return;
+    }
+    // The profile change rebuilds the content locks, and with them this screen.
+    if profile.claim_rewards_with_lore(achievement.id, ARCHETYPE_LORE_PRICE) { sound_event_writer.send(PlaySoundEvent(SoundEffect::Revelation)); }
+}
+
+fn update_character_select_ui(cursor: Res<CharacterSelectCursor>, mut card_query: Query<(Ref<ArchetypeCard>, &mut BackgroundColor, &mut BorderColor)>) {
+    for (card, mut background, mut border) in card_query.iter_mut() {
+        if !cursor.is_changed() && !card.is_added() { continue; }
+        let is_selected = card.0 == cursor.0;
+        background.0 = if is_selected { CARD_SELECTED_COLOR } else { CARD_COLOR };
+        border.0 = if is_selected { CARD_SELECTED_BORDER_COLOR } else { CARD_BORDER_COLOR };
+    }
+}
+
+fn despawn_character_select_ui(mut commands: Commands, query: Query<Entity, With<CharacterSelectUI>>) { for entity in query.iter() { commands.entity(entity).despawn_recursive(); } }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn each_archetype_applies_its_stats_weapon_and_passive_to_a_fresh_survivor() {
+        let mut world = World::new();
+        world.init_resource::<AutomaticWeaponLibrary>();
+        world.init_resource::<ArchetypeLibrary>();
+        world.run_system_once(populate_archetype_library);
+        let fresh = world.run_system_once(|library: Res<AutomaticWeaponLibrary>| Survivor::new_with_skills_and_items(Vec::new(), Vec::new(), None, &library));
+        let archetypes = world.resource::<ArchetypeLibrary>().archetypes.clone();
+        assert!(!archetypes.is_empty());
+
+        for archetype in archetypes {
+            let mut survivor = world.run_system_once(|library: Res<AutomaticWeaponLibrary>| Survivor::new_with_skills_and_items(Vec::new(), Vec::new(), None, &library));
+            let mut circle_of_warding = CircleOfWarding::default();
+            archetype.apply_to(&mut survivor, &mut circle_of_warding);
+
+            assert_eq!(survivor.max_health, archetype.max_health, "{}", archetype.name);
+            assert_eq!(survivor.speed, archetype.speed, "{}", archetype.name);
+            assert_eq!(survivor.pickup_radius_multiplier, archetype.pickup_radius_multiplier, "{}", archetype.name);
+            assert_eq!(survivor.equipped_weapon_id, Some(archetype.starting_weapon), "{}", archetype.name);
+            match archetype.passive {
+                ArchetypePassive::ExtraChains { chains, range_bonus } => {
+                    assert_eq!(survivor.auto_weapon_chain_bonus, fresh.auto_weapon_chain_bonus + chains);
+                    assert!((survivor.auto_weapon_chain_range_multiplier - (1.0 + range_bonus)).abs() < f32::EPSILON);
+                }
+                ArchetypePassive::EchoAttunement { xp_gain_bonus } => assert!((survivor.xp_gain_multiplier - (1.0 + xp_gain_bonus)).abs() < f32::EPSILON),
+                ArchetypePassive::Bloodletting { regen_per_second } => assert_eq!(survivor.health_regen_rate, fresh.health_regen_rate + regen_per_second),
+                ArchetypePassive::WardingAura { damage_per_tick, radius, .. } => {
+                    assert!(circle_of_warding.is_active);
+                    assert_eq!(circle_of_warding.base_damage_per_tick, damage_per_tick);
+                    assert_eq!(circle_of_warding.current_radius, radius);
+                }
+            }
+        }
+    }
+}
@@ -1,10 +1,38 @@
 // mescgit/bulletheavengame/bulletheavengame-a4c13a6183f1601049189db29b13bcfdace86153/src/audio.rs
-use bevy::prelude::*;
-use crate::game::AppState;
+use bevy::{prelude::*, audio::{PlaybackMode, SpatialScale, Volume}, ecs::system::SystemParam, utils::HashMap};
+use rand::Rng;
+use crate::survivor::Survivor;
 
+/// Hard cap on simultaneous sound effects. Past this, a new sound has to out-rank a playing one to be heard.
+const MAX_SFX_VOICES: usize = 24;
+/// World units to audio units. Sounds a screen away are clearly off to one side and noticeably quieter.
+const SPATIAL_SCALE: f32 = 1.0 / 400.0;
+const SPATIAL_EAR_GAP: f32 = 200.0;
+const BUS_VOLUME_STEP: f32 = 0.1;
+
+/// A sound with no place in the arena: menus, level-ups and other stingers. Anything that happens somewhere uses `PlaySoundAtEvent`.
 #[derive(Event)]
 pub struct PlaySoundEvent(pub SoundEffect);
 
+/// Like `PlaySoundEvent`, but panned and attenuated by where it happened relative to the survivor.
+#[derive(Event)]
+pub struct PlaySoundAtEvent(pub SoundEffect, pub Vec3);
+
+/// Both kinds of sound request, read together so positional and flat sounds compete for the same voices.
+#[derive(SystemParam)]
+struct SoundRequests<'w, 's> {
+    sound_events: EventReader<'w, 's, PlaySoundEvent>,
+    spatial_sound_events: EventReader<'w, 's, PlaySoundAtEvent>,
+}
+
+impl SoundRequests<'_, '_> {
+    fn drain(&mut self) -> Vec<(SoundEffect, Option<Vec3>)> {
+        self.sound_events.read().map(|event| (event.0, None))
+            .chain(self.spatial_sound_events.read().map(|event| (event.0, Some(event.1))))
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SoundEffect {
     RitualCast,
@@ -16,6 +44,61 @@ pub enum SoundEffect {
     MadnessConsumes,
     OmenAccepted,
     HorrorProjectile, 
+    FleshConsumed,
+    VoidMagnet,
+    ElderSign,
+    WardingOrb,
+    ProjectileBlocked,
+    Dodge,
+}
+
+/// How the mixer treats one kind of sound: how many may overlap, how soon it may repeat and what it may interrupt.
+struct SoundProfile { max_voices: usize, cooldown_secs: f32, priority: u8, volume: f32, pitch_variance: f32, }
+
+impl SoundEffect {
+    fn profile(&self) -> SoundProfile {
+        let (max_voices, cooldown_secs, priority, volume, pitch_variance) = match self {
+            SoundEffect::MadnessConsumes => (1, 0.0, 10, 1.0, 0.0),
+            SoundEffect::SurvivorHit => (2, 0.05, 9, 1.0, 0.05),
+            SoundEffect::Revelation => (1, 0.1, 8, 1.0, 0.0),
+            SoundEffect::OmenAccepted => (2, 0.05, 7, 1.0, 0.0),
+            SoundEffect::ElderSign | SoundEffect::Dodge | SoundEffect::VoidMagnet => (2, 0.05, 6, 0.9, 0.05),
+            SoundEffect::FleshConsumed => (2, 0.05, 5, 0.9, 0.05),
+            SoundEffect::HorrorDeath => (6, 0.03, 4, 0.8, 0.1),
+            SoundEffect::HorrorProjectile => (4, 0.08, 3, 0.7, 0.1),
+            SoundEffect::ProjectileBlocked => (3, 0.06, 3, 0.8, 0.1),
+            SoundEffect::RitualCast | SoundEffect::WardingOrb => (3, 0.05, 2, 0.6, 0.08),
+            SoundEffect::SoulCollect => (4, 0.04, 2, 0.6, 0.15),
+            SoundEffect::HorrorHit => (6, 0.03, 1, 0.6, 0.12),
+        };
+        SoundProfile { max_voices, cooldown_secs, priority, volume, pitch_variance }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioBus { Music, Sfx, }
+
+/// Bus volumes, applied on top of each sound's own volume. `-`/`=` adjust effects, `[`/`]` adjust music.
+#[derive(Resource, Debug)]
+pub struct AudioMixer { pub music_volume: f32, pub sfx_volume: f32, }
+
+impl Default for AudioMixer {
+    fn default() -> Self { Self { music_volume: 1.0, sfx_volume: 1.0 } }
+}
+
+impl AudioMixer {
+    pub fn bus_volume(&self, bus: AudioBus) -> f32 {
+        match bus { AudioBus::Music => self.music_volume, AudioBus::Sfx => self.sfx_volume }
+    }
+}
+
+/// Every sound the mixer starts. Effects carry their kind and priority so they can be counted and, if need be, cut off.
+#[derive(Component, Debug)]
+pub struct MixerVoice { pub bus: AudioBus, pub base_volume: f32, effect: Option<SoundEffect>, priority: u8, started_at_secs: f32, }
+
+impl MixerVoice {
+    /// Music is never counted against the effect voice limit or stolen from.
+    pub fn music(base_volume: f32) -> Self { Self { bus: AudioBus::Music, base_volume, effect: None, priority: 0, started_at_secs: 0.0 } }
 }
 
 #[derive(Resource)]
@@ -29,22 +112,29 @@ pub struct GameAudioHandles {
     pub madness_consumes: Handle<AudioSource>,
     pub omen_accepted: Handle<AudioSource>,
     pub horror_projectile: Handle<AudioSource>,
-    pub background_music: Handle<AudioSource>,
+    pub flesh_consumed: Handle<AudioSource>,
+    pub void_magnet: Handle<AudioSource>,
+    pub elder_sign: Handle<AudioSource>,
+    pub warding_orb: Handle<AudioSource>,
+    pub projectile_blocked: Handle<AudioSource>,
+    pub dodge: Handle<AudioSource>,
 }
 
-#[derive(Component)]
-struct BackgroundMusicController;
-
 pub struct GameAudioPlugin;
 
 impl Plugin for GameAudioPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_event::<PlaySoundEvent>()
+            .add_event::<PlaySoundAtEvent>()
+            .init_resource::<AudioMixer>()
             .add_systems(Startup, setup_audio_handles)
-            .add_systems(Update, play_sound_system)
-            .add_systems(OnEnter(AppState::InGame), start_background_music)
-            .add_systems(OnExit(AppState::InGame), stop_background_music);
+            .add_systems(Update, (
+                attach_survivor_listener_system,
+                adjust_mixer_volume_system,
+                apply_bus_volumes_system,
+                play_sound_system,
+            ).chain());
     }
 }
 
@@ -59,60 +149,111 @@ fn setup_audio_handles(mut commands: Commands, asset_server: Res<AssetServer>) {
         madness_consumes: asset_server.load("audio/madness_consumes_placeholder.ogg"),
         omen_accepted: asset_server.load("audio/omen_accepted_placeholder.ogg"),
         horror_projectile: asset_server.load("audio/horror_projectile_placeholder.ogg"), 
-        background_music: asset_server.load("audio/cyclopean_ruins_ambience_placeholder.ogg"),
+        flesh_consumed: asset_server.load("audio/flesh_consumed_placeholder.ogg"),
+        void_magnet: asset_server.load("audio/void_magnet_placeholder.ogg"),
+        elder_sign: asset_server.load("audio/elder_sign_placeholder.ogg"),
+        warding_orb: asset_server.load("audio/warding_orb_placeholder.ogg"),
+        projectile_blocked: asset_server.load("audio/projectile_blocked_placeholder.ogg"),
+        dodge: asset_server.load("audio/dodge_placeholder.ogg"),
     });
 }
 
-fn play_sound_system(
-    mut commands: Commands,
-    mut sound_events: EventReader<PlaySoundEvent>,
-    audio_handles: Res<GameAudioHandles>,
-) {
-    for event in sound_events.read() {
-        let source = match event.0 {
-            SoundEffect::RitualCast => audio_handles.ritual_cast.clone(),
-            SoundEffect::HorrorHit => audio_handles.horror_hit.clone(),
-            SoundEffect::HorrorDeath => audio_handles.horror_death.clone(),
-            SoundEffect::SurvivorHit => audio_handles.survivor_hit.clone(),
-            SoundEffect::Revelation => audio_handles.revelation.clone(),
-            SoundEffect::SoulCollect => audio_handles.soul_collect.clone(),
-            SoundEffect::MadnessConsumes => audio_handles.madness_consumes.clone(),
-            SoundEffect::OmenAccepted => audio_handles.omen_accepted.clone(),
-            SoundEffect::HorrorProjectile => audio_handles.horror_projectile.clone(),
-        };
-        commands.spawn(AudioBundle {
-            source,
-            settings: PlaybackSettings::DESPAWN, 
-        });
+impl GameAudioHandles {
+    fn handle_for(&self, effect: SoundEffect) -> Handle<AudioSource> {
+        match effect {
+            SoundEffect::RitualCast => self.ritual_cast.clone(),
+            SoundEffect::HorrorHit => self.horror_hit.clone(),
+            SoundEffect::HorrorDeath => self.horror_death.clone(),
+            SoundEffect::SurvivorHit => self.survivor_hit.clone(),
+            SoundEffect::Revelation => self.revelation.clone(),
+            SoundEffect::SoulCollect => self.soul_collect.clone(),
+            SoundEffect::MadnessConsumes => self.madness_consumes.clone(),
+            SoundEffect::OmenAccepted => self.omen_accepted.clone(),
+            SoundEffect::HorrorProjectile => self.horror_projectile.clone(),
+            SoundEffect::FleshConsumed => self.flesh_consumed.clone(),
+            SoundEffect::VoidMagnet => self.void_magnet.clone(),
+            SoundEffect::ElderSign => self.elder_sign.clone(),
+            SoundEffect::WardingOrb => self.warding_orb.clone(),
+            SoundEffect::ProjectileBlocked => self.projectile_blocked.clone(),
+            SoundEffect::Dodge => self.dodge.clone(),
+        }
     }
 }
 
-fn start_background_music(
-    mut commands: Commands,
-    audio_handles: Res<GameAudioHandles>,
-    music_controller_query: Query<Entity, With<BackgroundMusicController>>, 
+/// Spatial sounds are heard from the survivor's position, not the camera's.
+fn attach_survivor_listener_system(mut commands: Commands, survivor_query: Query<Entity, Added<Survivor>>) {
+    for entity in survivor_query.iter() { commands.entity(entity).try_insert(SpatialListener::new(SPATIAL_EAR_GAP)); }
+}
+
+fn adjust_mixer_volume_system(keyboard_input: Res<ButtonInput<KeyCode>>, mut mixer: ResMut<AudioMixer>) {
+    let step = |down: KeyCode, up: KeyCode| {
+        if keyboard_input.just_pressed(down) { -BUS_VOLUME_STEP } else if keyboard_input.just_pressed(up) { BUS_VOLUME_STEP } else { 0.0 }
+    };
+    let (sfx_step, music_step) = (step(KeyCode::Minus, KeyCode::Equal), step(KeyCode::BracketLeft, KeyCode::BracketRight));
+    if sfx_step != 0.0 { mixer.sfx_volume = (mixer.sfx_volume + sfx_step).clamp(0.0, 1.0); }
+    if music_step != 0.0 { mixer.music_volume = (mixer.music_volume + music_step).clamp(0.0, 1.0); }
+}
+
+/// Pushes bus changes onto sounds that are already playing; new sounds pick the bus volume up when spawned.
+fn apply_bus_volumes_system(
+    mixer: Res<AudioMixer>,
+    sink_query: Query<(&MixerVoice, &AudioSink)>,
+    spatial_sink_query: Query<(&MixerVoice, &SpatialAudioSink)>,
 ) {
-    if !music_controller_query.is_empty() {
-        return;
-    }
-    commands.spawn((
-        AudioBundle {
-            source: audio_handles.background_music.clone(),
-            settings: PlaybackSettings {
-                mode: bevy::audio::PlaybackMode::Loop,
-                volume: bevy::audio::Volume::new(0.3), 
-                ..default()
-            },
-        },
-        BackgroundMusicController,
-    ));
+    if !mixer.is_changed() { return; }
+    for (voice, sink) in sink_query.iter() { sink.set_volume(voice.base_volume * mixer.bus_volume(voice.bus)); }
+    for (voice, sink) in spatial_sink_query.iter() { sink.set_volume(voice.base_volume * mixer.bus_volume(voice.bus)); }
 }
 
-fn stop_background_music(
+fn play_sound_system(
     mut commands: Commands,
-    music_controller_query: Query<Entity, With<BackgroundMusicController>>,
+    real_time: Res<Time<Real>>,
+    mut sound_requests: SoundRequests,
+    audio_handles: Res<GameAudioHandles>,
+    mixer: Res<AudioMixer>,
+    voice_query: Query<(Entity, &MixerVoice)>,
+    mut last_played_secs: Local<HashMap<SoundEffect, f32>>,
 ) {
-    for entity in music_controller_query.iter() {
-        commands.entity(entity).despawn_recursive();
+    let mut requests = sound_requests.drain();
+    if requests.is_empty() { return; }
+    // The most important sounds claim voices first.
+    requests.sort_by_key(|(effect, _)| std::cmp::Reverse(effect.profile().priority));
+
+    let now = real_time.elapsed_seconds();
+    let mut rng = rand::thread_rng();
+    let mut active_voices: Vec<(Entity, SoundEffect, u8, f32)> = voice_query.iter()
+        .filter_map(|(entity, voice)| voice.effect.map(|effect| (entity, effect, voice.priority, voice.started_at_secs)))
+        .collect();
+
+    for (effect, position) in requests {
+        let profile = effect.profile();
+        if last_played_secs.get(&effect).is_some_and(|last| now - last < profile.cooldown_secs) { continue; }
+        if active_voices.iter().filter(|(_, playing, ..)| *playing == effect).count() >= profile.max_voices { continue; }
+        if active_voices.len() >= MAX_SFX_VOICES {
+            // Steal the oldest of the least important voices, but only from something that matters less.
+            let victim = active_voices.iter().enumerate()
+                .filter(|(_, (_, _, priority, _))| *priority < profile.priority)
+                .min_by(|(_, a), (_, b)| a.2.cmp(&b.2).then(a.3.total_cmp(&b.3)))
+                .map(|(index, _)| index);
+            let Some(victim) = victim else { continue; };
+            let (victim_entity, ..) = active_voices.swap_remove(victim);
+            commands.entity(victim_entity).despawn_recursive();
+        }
+
+        last_played_secs.insert(effect, now);
+        let settings = PlaybackSettings {
+            mode: PlaybackMode::Despawn,
+            volume: Volume::new(profile.volume * mixer.sfx_volume),
+            speed: 1.0 + rng.gen_range(-profile.pitch_variance..=profile.pitch_variance),
+            spatial: position.is_some(),
+            spatial_scale: position.map(|_| SpatialScale::new_2d(SPATIAL_SCALE)),
+            ..default()
+        };
+        let mut voice_commands = commands.spawn((
+            AudioBundle { source: audio_handles.handle_for(effect), settings },
+            MixerVoice { bus: AudioBus::Sfx, base_volume: profile.volume, effect: Some(effect), priority: profile.priority, started_at_secs: now },
+        ));
+        if let Some(position) = position { voice_commands.insert(SpatialBundle::from_transform(Transform::from_translation(position))); }
+        active_voices.push((voice_commands.id(), effect, profile.priority, now));
     }
-}
\ No newline at end of file
+}
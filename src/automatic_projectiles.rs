@@ -4,11 +4,15 @@ use bevy_hanabi::prelude::*; // Hanabi prelude
 use rand::Rng;
 use crate::{
     components::{Velocity, Damage, Lifetime, Health},
-    visual_effects::spawn_damage_text,
-    audio::{PlaySoundEvent, SoundEffect},
+    visual_effects::{HitFlash, HitstopEvent, HorrorHitWriters, HEAVY_HIT_KNOCKBACK_THRESHOLD, HEAVY_HIT_HITSTOP_SECS},
+    audio::{PlaySoundAtEvent, SoundEffect},
     survivor::Survivor,
     items::{ItemLibrary, ItemEffect, ExplosionEffect, AutomaticWeaponId},
-    horror::Horror,
+    horror::Knockback,
+    elite_affixes::{AffixWard, HorrorHitTarget, HorrorHitTargetItem, try_mirror_reflect, apply_horror_damage},
+    run_statistics::{DamageDealtEvent, DamageSource},
+    achievements::AchievementTriggerEvent,
+    critical_hits::CritStats,
 };
 
 pub const BASE_CHAIN_LIGHTNING_RANGE: f32 = 300.0;
@@ -21,6 +25,25 @@ pub struct AutomaticProjectile {
     pub weapon_id: AutomaticWeaponId,
     pub already_hit_entities: Vec<Entity>,
     pub damage_amount: i32,
+    pub knockback_force: f32,
+    pub crit: CritStats,
+}
+
+/// Everything a single automatic projectile is fired with, resolved from the weapon definition and survivor bonuses.
+pub struct ProjectileSpawnParams {
+    pub position: Vec3,
+    pub direction: Vec2,
+    pub damage: i32,
+    pub speed: f32,
+    pub piercing: u32,
+    pub chains: u32,
+    pub knockback_force: f32,
+    pub weapon_id: AutomaticWeaponId,
+    pub sprite_path: &'static str,
+    pub size: Vec2,
+    pub color: Color,
+    pub lifetime_secs: f32,
+    pub crit: CritStats,
 }
 
 #[derive(Component)]
@@ -30,12 +53,15 @@ pub struct ChainLightningVisual {
 
 #[derive(Component)]
 pub struct ChainLightningStrikeEvent {
+    pub weapon_id: AutomaticWeaponId,
     pub source_position: Vec3,
     pub target_entity: Entity,
     pub damage: i32,
+    pub crit: CritStats,
     pub remaining_chains: u32,
     pub already_hit_in_chain: Vec<Entity>,
     pub chain_range_sq: f32,
+    pub kills: u32, // Horrors slain by this chain so far, reported once the chain ends
 }
 
 #[derive(Resource)]
@@ -104,19 +130,10 @@ fn setup_lightning_particle_effects(
 pub fn spawn_automatic_projectile(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
-    position: Vec3,
-    direction: Vec2,
-    damage: i32,
-    speed: f32,
-    piercing: u32,
-    chains: u32,
-    weapon_id: AutomaticWeaponId,
-    sprite_path: &'static str,
-    size: Vec2,
-    color: Color,
-    lifetime_secs: f32,
+    params: ProjectileSpawnParams,
     particle_effects: Option<&Res<LightningParticleEffects>>,
 ) {
+    let ProjectileSpawnParams { position, direction, damage, speed, piercing, chains, knockback_force, weapon_id, sprite_path, size, color, lifetime_secs, crit } = params;
     let mut projectile_entity_commands = commands.spawn_empty();
 
     projectile_entity_commands.insert((
@@ -126,6 +143,8 @@ pub fn spawn_automatic_projectile(
             weapon_id,
             already_hit_entities: Vec::new(),
             damage_amount: damage,
+            knockback_force,
+            crit,
         },
         Velocity(direction * speed),
         Damage(damage),
@@ -191,20 +210,19 @@ fn automatic_projectile_lifetime_system(
 fn automatic_projectile_collision_system(
     mut commands: Commands,
     mut projectile_query: Query<(Entity, &GlobalTransform, &Damage, &mut AutomaticProjectile, &Velocity)>,
-    mut horror_query: Query<(Entity, &GlobalTransform, &mut Health, &Horror)>,
+    mut horror_query: Query<HorrorHitTarget>,
     player_query: Query<&Survivor>,
     item_library: Res<ItemLibrary>,
     asset_server: Res<AssetServer>,
-    time: Res<Time>,
-    mut sound_event_writer: EventWriter<PlaySoundEvent>,
+    mut hit_writers: HorrorHitWriters,
 ) {
     let Ok(player) = player_query.get_single() else { return; };
 
-    for (projectile_entity, proj_gtransform, _proj_main_hit_damage_comp, mut proj_stats, _proj_velocity) in projectile_query.iter_mut() {
+    for (projectile_entity, proj_gtransform, _proj_main_hit_damage_comp, mut proj_stats, proj_velocity) in projectile_query.iter_mut() {
         let proj_pos = proj_gtransform.translation();
         let projectile_radius = 5.0;
 
-        for (horror_entity, horror_gtransform, mut horror_health, horror_data) in horror_query.iter_mut() {
+        for HorrorHitTargetItem { entity: horror_entity, transform: horror_gtransform, health: mut horror_health, ward: mut ward_opt, horror: horror_data, warden: warden_opt, affixes: affixes_opt } in horror_query.iter_mut() {
             if proj_stats.already_hit_entities.contains(&horror_entity) {
                 continue;
             }
@@ -212,10 +230,29 @@ fn automatic_projectile_collision_system(
             let horror_radius = horror_data.size.x / 2.0;
 
             if distance < projectile_radius + horror_radius {
-                sound_event_writer.send(PlaySoundEvent(SoundEffect::HorrorHit));
-                horror_health.0 -= proj_stats.damage_amount;
-                spawn_damage_text(&mut commands, &asset_server, horror_gtransform.translation(), proj_stats.damage_amount, &time);
+                if warden_opt.is_some_and(|warden| warden.blocks_projectile(horror_gtransform.translation().truncate(), proj_pos.truncate())) {
+                    hit_writers.sound.send(PlaySoundAtEvent(SoundEffect::ProjectileBlocked, proj_pos));
+                    commands.entity(projectile_entity).despawn_recursive();
+                    break;
+                }
+                if try_mirror_reflect(&mut commands, &asset_server, affixes_opt, horror_data, horror_gtransform.translation(), proj_velocity.0) {
+                    hit_writers.sound.send(PlaySoundAtEvent(SoundEffect::ProjectileBlocked, proj_pos));
+                    commands.entity(projectile_entity).despawn_recursive();
+                    break;
+                }
+                hit_writers.sound.send(PlaySoundAtEvent(SoundEffect::HorrorHit, horror_gtransform.translation()));
+                let was_alive = horror_health.0 > 0;
+                let (hit_damage, is_crit) = proj_stats.crit.roll(proj_stats.damage_amount);
+                let dealt = apply_horror_damage(&mut horror_health, ward_opt.as_deref_mut(), hit_damage);
+                if dealt > 0 {
+                    hit_writers.damage.send(DamageDealtEvent { source: DamageSource::AutomaticWeapon(proj_stats.weapon_id), amount: dealt, target: horror_entity, position: horror_gtransform.translation(), is_crit });
+                }
                 proj_stats.already_hit_entities.push(horror_entity);
+                commands.entity(horror_entity).try_insert(HitFlash::default());
+                if proj_stats.knockback_force > 0.0 {
+                    commands.entity(horror_entity).try_insert(Knockback::from_hit(proj_velocity.0.normalize_or_zero(), proj_stats.knockback_force, horror_data.mass()));
+                    if proj_stats.knockback_force >= HEAVY_HIT_KNOCKBACK_THRESHOLD { hit_writers.hitstop.send(HitstopEvent { duration_secs: HEAVY_HIT_HITSTOP_SECS }); }
+                }
 
                 for item_id in player.collected_item_ids.iter() {
                     if let Some(item_def) = item_library.get_item_definition(*item_id) {
@@ -229,7 +266,7 @@ fn automatic_projectile_collision_system(
                                             sprite: Sprite { custom_size: Some(Vec2::splat(0.1)), color: *explosion_color, ..default() },
                                             transform: Transform::from_translation(horror_gtransform.translation().truncate().extend(0.3)), ..default()
                                         },
-                                        ExplosionEffect { damage: *explosion_damage, radius_sq: explosion_radius.powi(2), timer: Timer::from_seconds(0.3, TimerMode::Once), already_hit_entities: vec![horror_entity], },
+                                        ExplosionEffect { damage: *explosion_damage, radius_sq: explosion_radius.powi(2), timer: Timer::from_seconds(0.3, TimerMode::Once), already_hit_entities: vec![horror_entity], source: DamageSource::ItemExplosion, },
                                         Name::new("ItemHitExplosion"),
                                     ));
                                 }
@@ -241,12 +278,15 @@ fn automatic_projectile_collision_system(
                 if proj_stats.chains_left > 0 && proj_stats.weapon_id == AutomaticWeaponId(3) {
                     let effective_chain_range = BASE_CHAIN_LIGHTNING_RANGE * player.auto_weapon_chain_range_multiplier;
                     commands.spawn(ChainLightningStrikeEvent {
+                        weapon_id: proj_stats.weapon_id,
                         source_position: horror_gtransform.translation(),
                         target_entity: horror_entity,
                         damage: (proj_stats.damage_amount as f32 * CHAIN_LIGHTNING_DAMAGE_MULTIPLIER).round() as i32,
+                        crit: proj_stats.crit,
                         remaining_chains: proj_stats.chains_left,
                         already_hit_in_chain: vec![horror_entity],
                         chain_range_sq: effective_chain_range.powi(2),
+                        kills: (was_alive && horror_health.0 <= 0) as u32,
                     });
                     proj_stats.chains_left = 0;
                 }
@@ -265,14 +305,16 @@ fn automatic_projectile_collision_system(
 fn chain_lightning_strike_processor_system(
     mut commands: Commands,
     strike_query: Query<(Entity, &ChainLightningStrikeEvent)>,
-    mut horror_query: Query<(Entity, &GlobalTransform, &mut Health, &Horror)>,
+    mut horror_query: Query<(Entity, &GlobalTransform, &mut Health, Option<&mut AffixWard>)>,
     asset_server: Res<AssetServer>,
-    time: Res<Time>,
-    mut sound_event_writer: EventWriter<PlaySoundEvent>,
+    mut hit_writers: HorrorHitWriters,
+    mut achievement_trigger_writer: EventWriter<AchievementTriggerEvent>,
 ) {
     for (event_entity, strike_data) in strike_query.iter() {
+        let mut chain_continues = false;
+        let mut chain_kills = strike_data.kills;
         let mut potential_targets: Vec<(Entity, f32)> = Vec::new();
-        for (horror_entity, horror_gtransform, _horror_health, _horror_data) in horror_query.iter() {
+        for (horror_entity, horror_gtransform, _horror_health, _ward) in horror_query.iter() {
             if strike_data.already_hit_in_chain.contains(&horror_entity) {
                 continue;
             }
@@ -284,7 +326,7 @@ fn chain_lightning_strike_processor_system(
         potential_targets.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
 
         if let Some((target_horror_entity, _)) = potential_targets.first() {
-            if let Ok((_h_ent, target_gtransform, mut target_health, _h_data)) = horror_query.get_mut(*target_horror_entity) {
+            if let Ok((_h_ent, target_gtransform, mut target_health, mut ward_opt)) = horror_query.get_mut(*target_horror_entity) {
                 let target_pos = target_gtransform.translation();
                 let midpoint = (strike_data.source_position + target_pos) / 2.0;
                 let distance = strike_data.source_position.distance(target_pos);
@@ -306,25 +348,36 @@ fn chain_lightning_strike_processor_system(
                     Name::new("ChainLightningSegment"),
                 ));
 
-                sound_event_writer.send(PlaySoundEvent(SoundEffect::HorrorHit));
-                target_health.0 -= strike_data.damage;
-                spawn_damage_text(&mut commands, &asset_server, target_gtransform.translation(), strike_data.damage, &time);
+                hit_writers.sound.send(PlaySoundAtEvent(SoundEffect::HorrorHit, target_pos));
+                let was_alive = target_health.0 > 0;
+                let (strike_damage, is_crit) = strike_data.crit.roll(strike_data.damage);
+                let dealt = apply_horror_damage(&mut target_health, ward_opt.as_deref_mut(), strike_damage);
+                if was_alive && target_health.0 <= 0 { chain_kills += 1; }
+                if dealt > 0 {
+                    hit_writers.damage.send(DamageDealtEvent { source: DamageSource::AutomaticWeapon(strike_data.weapon_id), amount: dealt, target: *target_horror_entity, position: target_gtransform.translation(), is_crit });
+                }
+                commands.entity(*target_horror_entity).try_insert(HitFlash::default());
 
                 let mut next_hit_list = strike_data.already_hit_in_chain.clone();
                 next_hit_list.push(*target_horror_entity);
 
                 if strike_data.remaining_chains > 1 {
                     commands.spawn(ChainLightningStrikeEvent {
+                        weapon_id: strike_data.weapon_id,
                         source_position: target_gtransform.translation(),
                         target_entity: *target_horror_entity,
                         damage: (strike_data.damage as f32 * CHAIN_LIGHTNING_DAMAGE_MULTIPLIER).round() as i32,
+                        crit: strike_data.crit,
                         remaining_chains: strike_data.remaining_chains - 1,
                         already_hit_in_chain: next_hit_list,
                         chain_range_sq: strike_data.chain_range_sq,
+                        kills: chain_kills,
                     });
+                    chain_continues = true;
                 }
             }
         }
+        if !chain_continues { achievement_trigger_writer.send(AchievementTriggerEvent::ChainLightningResolved { kills: chain_kills }); }
         commands.entity(event_entity).despawn();
     }
 }
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 use crate::camera_systems::MainCamera;
 use crate::game::AppState;
+use crate::pathfinding::Obstacle;
 
 pub const BACKGROUND_TILE_SIZE: f32 = 2048.0;
 const BACKGROUND_Z: f32 = -10.0;
@@ -8,6 +9,16 @@ const GRID_DIMENSION: i32 = 5;
 const NUM_TILES: usize = (GRID_DIMENSION * GRID_DIMENSION) as usize;
 // Shift the grid when camera moves this fraction of a tile size past the center tile's edge
 const GRID_SHIFT_THRESHOLD_FACTOR: f32 = 0.45; // Previously effectively 0.5
+const RUIN_Z_OFFSET: f32 = 1.0;
+const RUIN_COLOR: Color = Color::rgb(0.16, 0.14, 0.2);
+// (offset from the tile centre, radius). Every tile repeats the same layout, so the ruins wrap with the tiles and never
+// sit on the survivor's spawn point at the centre of the middle tile.
+const RUIN_LAYOUT: [(Vec2, f32); 4] = [
+    (Vec2::new(-520.0, 340.0), 70.0),
+    (Vec2::new(610.0, 480.0), 55.0),
+    (Vec2::new(380.0, -560.0), 90.0),
+    (Vec2::new(-700.0, -420.0), 60.0),
+];
 
 #[derive(Component)]
 struct BackgroundTile;
@@ -50,7 +61,19 @@ fn setup_background(mut commands: Commands, asset_server: Res<AssetServer>) {
                 },
                 BackgroundTile,
                 Name::new(format!("BackgroundTile_{}_{}", i, j)),
-            )).id();
+            )).with_children(|tile| {
+                for (offset, radius) in RUIN_LAYOUT {
+                    tile.spawn((
+                        SpriteBundle {
+                            sprite: Sprite { custom_size: Some(Vec2::splat(radius * 2.0)), color: RUIN_COLOR, ..default() },
+                            transform: Transform::from_translation(offset.extend(RUIN_Z_OFFSET)),
+                            ..default()
+                        },
+                        Obstacle { radius },
+                        Name::new("CyclopeanRuin"),
+                    ));
+                }
+            }).id();
             tiles[(i * GRID_DIMENSION + j) as usize] = tile_entity;
         }
     }
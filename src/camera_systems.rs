@@ -1,35 +1,139 @@
 use bevy::prelude::*;
 use crate::survivor::Survivor; // Corrected: player::Player to survivor::Survivor
-use crate::game::AppState;
+use crate::game::{AppState, GameState};
 
 const CAMERA_LERP_FACTOR: f32 = 0.05; // Adjust for more or less "softness" (lower is softer)
 
+const TRAUMA_DECAY_PER_SEC: f32 = 1.6;
+const SHAKE_MAX_OFFSET: f32 = 18.0;
+const SHAKE_MAX_ROLL_RADIANS: f32 = 0.035;
+const SHAKE_FREQUENCY: f32 = 27.0;
+
+const PUNCH_ZOOM_MAX: f32 = 0.3;
+const PUNCH_ZOOM_RECOVERY_RATE: f32 = 6.0;
+
+const CYCLE_ZOOM_OUT_PER_CYCLE: f32 = 0.05;
+const CYCLE_ZOOM_MAX_SCALE: f32 = 1.4;
+const CYCLE_ZOOM_EASE_RATE: f32 = 0.5;
+
+pub const SURVIVOR_HIT_TRAUMA: f32 = 0.35;
+pub const BEHEMOTH_SLAM_TRAUMA: f32 = 0.8;
+pub const LEVEL_UP_PUNCH_ZOOM: f32 = 0.12;
+
 #[derive(Component)]
 pub struct MainCamera; // Marker component for the main game camera
 
+/// The world-space rectangle the camera currently shows, including the cycle and punch zoom.
+pub fn visible_world_rect(camera_transform: &Transform, projection: &OrthographicProjection) -> Rect {
+    Rect::from_center_size(camera_transform.translation.truncate() + projection.area.center(), projection.area.size())
+}
+
+/// Adds trauma to the camera. Shake grows with the square of trauma, so small hits stay subtle and big slams land hard.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CameraShakeEvent { pub trauma: f32, }
+
+/// Briefly pulls the camera in by `amount` (a fraction of the current zoom) before easing back out.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CameraZoomPunchEvent { pub amount: f32, }
+
+/// With `reduce_motion` on (M), shake and punch-zoom requests are ignored; only the slow cycle zoom-out remains.
+#[derive(Resource, Default, Debug)]
+pub struct CameraMotionSettings { pub reduce_motion: bool, }
+
+/// Effect state layered on top of the follow position each frame. The follow position itself is never shaken.
+#[derive(Resource, Debug)]
+pub struct CameraEffects {
+    pub trauma: f32,
+    pub punch_zoom: f32,
+    pub cycle_zoom: f32,
+    pub follow_translation: Option<Vec3>,
+}
+
+impl Default for CameraEffects {
+    fn default() -> Self { Self { trauma: 0.0, punch_zoom: 0.0, cycle_zoom: 1.0, follow_translation: None } }
+}
+
 pub struct CameraSystemsPlugin;
 
 impl Plugin for CameraSystemsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, 
-            soft_camera_follow_system.run_if(in_state(AppState::InGame))
-        );
+        app.add_event::<CameraShakeEvent>()
+            .add_event::<CameraZoomPunchEvent>()
+            .init_resource::<CameraMotionSettings>()
+            .init_resource::<CameraEffects>()
+            .add_systems(Update, (
+                toggle_camera_motion_settings_system,
+                soft_camera_follow_system.run_if(in_state(AppState::InGame)),
+                camera_effect_events_system,
+                apply_camera_effects_system,
+            ).chain());
     }
 }
 
+fn toggle_camera_motion_settings_system(keyboard_input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<CameraMotionSettings>, mut effects: ResMut<CameraEffects>) {
+    if !keyboard_input.just_pressed(KeyCode::KeyM) { return; }
+    settings.reduce_motion = !settings.reduce_motion;
+    if settings.reduce_motion { effects.trauma = 0.0; effects.punch_zoom = 0.0; }
+}
+
 fn soft_camera_follow_system(
     player_query: Query<&Transform, (With<Survivor>, Without<MainCamera>)>, // Corrected: With<Player> to With<Survivor>
-    mut camera_query: Query<&mut Transform, (With<MainCamera>, Without<Survivor>)>, // Corrected: Without<Player> to Without<Survivor>
+    camera_query: Query<&Transform, (With<MainCamera>, Without<Survivor>)>, // Corrected: Without<Player> to Without<Survivor>
+    mut effects: ResMut<CameraEffects>,
 ) {
     if let Ok(player_transform) = player_query.get_single() {
-        if let Ok(mut camera_transform) = camera_query.get_single_mut() {
+        if let Ok(camera_transform) = camera_query.get_single() {
             let target_position = player_transform.translation;
-            
+            let follow_translation = effects.follow_translation.unwrap_or(camera_transform.translation);
+
             // Interpolate camera position towards player position
             // Only interpolate X and Y, keep Z fixed unless desired.
-            camera_transform.translation = camera_transform.translation.lerp(target_position, CAMERA_LERP_FACTOR);
-            // Ensure camera Z remains constant if it was set specifically
-            // camera_transform.translation.z = desired_camera_z_value; // e.g. 10.0 or what was set at spawn
+            let mut next_translation = follow_translation.lerp(target_position, CAMERA_LERP_FACTOR);
+            next_translation.z = camera_transform.translation.z;
+            effects.follow_translation = Some(next_translation);
         }
     }
-}
\ No newline at end of file
+}
+
+fn camera_effect_events_system(
+    settings: Res<CameraMotionSettings>,
+    mut effects: ResMut<CameraEffects>,
+    mut shake_events: EventReader<CameraShakeEvent>,
+    mut punch_events: EventReader<CameraZoomPunchEvent>,
+) {
+    if settings.reduce_motion {
+        shake_events.clear();
+        punch_events.clear();
+        return;
+    }
+    for event in shake_events.read() { effects.trauma = (effects.trauma + event.trauma).min(1.0); }
+    for event in punch_events.read() { effects.punch_zoom = effects.punch_zoom.max(event.amount).min(PUNCH_ZOOM_MAX); }
+}
+
+/// Runs on real time, so hitstop and paused states still let the camera settle.
+fn apply_camera_effects_system(
+    real_time: Res<Time<Real>>,
+    game_state: Res<GameState>,
+    mut effects: ResMut<CameraEffects>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
+) {
+    let Ok((mut camera_transform, mut projection)) = camera_query.get_single_mut() else { return; };
+    let delta = real_time.delta_seconds();
+    effects.trauma = (effects.trauma - TRAUMA_DECAY_PER_SEC * delta).max(0.0);
+    effects.punch_zoom *= (-PUNCH_ZOOM_RECOVERY_RATE * delta).exp();
+
+    // Later cycles put more horrors on screen at once, so give the survivor a little more room to read them.
+    let cycle_zoom_target = (1.0 + game_state.cycle_number.saturating_sub(1) as f32 * CYCLE_ZOOM_OUT_PER_CYCLE).min(CYCLE_ZOOM_MAX_SCALE);
+    effects.cycle_zoom += (cycle_zoom_target - effects.cycle_zoom) * (1.0 - (-CYCLE_ZOOM_EASE_RATE * delta).exp());
+    let scale = effects.cycle_zoom * (1.0 - effects.punch_zoom);
+    if projection.scale != scale { projection.scale = scale; }
+
+    let follow_translation = effects.follow_translation.unwrap_or(camera_transform.translation);
+    let shake = effects.trauma * effects.trauma;
+    // Three sines per axis at unrelated frequencies stand in for noise; cheap and never repeats visibly.
+    let t = real_time.elapsed_seconds() * SHAKE_FREQUENCY;
+    let wobble = |phase: f32| ((t + phase).sin() + (t * 1.7 + phase * 2.3).sin() * 0.5 + (t * 3.1 + phase * 0.7).sin() * 0.25) / 1.75;
+    let offset = Vec2::new(wobble(0.0), wobble(11.0)) * SHAKE_MAX_OFFSET * shake;
+    camera_transform.translation = follow_translation + offset.extend(0.0);
+    camera_transform.rotation = Quat::from_rotation_z(wobble(23.0) * SHAKE_MAX_ROLL_RADIANS * shake);
+}
@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+use rand::Rng;
+use crate::{
+    game::AppState,
+    survivor::Survivor,
+    items::{ItemLibrary, ItemEffect, ExplosionEffect},
+    run_statistics::{DamageDealtEvent, DamageSource},
+};
+
+pub const BASE_CRIT_CHANCE: f32 = 0.05;
+pub const BASE_CRIT_MULTIPLIER: f32 = 1.5;
+const CRIT_EXPLOSION_DURATION_SECS: f32 = 0.3;
+
+/// Chance and damage multiplier for a single attack, snapshotted when the projectile or effect is spawned.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct CritStats { pub chance: f32, pub multiplier: f32, }
+
+impl Default for CritStats {
+    fn default() -> Self { Self { chance: BASE_CRIT_CHANCE, multiplier: BASE_CRIT_MULTIPLIER } }
+}
+
+impl CritStats {
+    /// A weapon or skill may replace the base chance and multiplier; the survivor's bonuses from upgrades and relics still stack on top.
+    pub fn for_attack(survivor: &Survivor, chance_override: Option<f32>, multiplier_override: Option<f32>) -> Self {
+        Self {
+            chance: (survivor.crit_chance + chance_override.map_or(0.0, |chance| chance - BASE_CRIT_CHANCE)).clamp(0.0, 1.0),
+            multiplier: (survivor.crit_multiplier + multiplier_override.map_or(0.0, |multiplier| multiplier - BASE_CRIT_MULTIPLIER)).max(1.0),
+        }
+    }
+
+    /// Returns the damage to deal and whether it crit.
+    pub fn roll(&self, damage: i32) -> (i32, bool) {
+        if self.chance <= 0.0 || !rand::thread_rng().gen_bool(self.chance.min(1.0) as f64) { return (damage, false); }
+        ((damage as f32 * self.multiplier).round() as i32, true)
+    }
+}
+
+pub struct CriticalHitsPlugin;
+
+impl Plugin for CriticalHitsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, critical_hit_relic_system.run_if(in_state(AppState::InGame)));
+    }
+}
+
+/// Relics that react to crits. Their explosions deal `ItemExplosion` damage, which never crits, so they cannot chain.
+fn critical_hit_relic_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut damage_events: EventReader<DamageDealtEvent>,
+    survivor_query: Query<&Survivor>,
+    item_library: Res<ItemLibrary>,
+) {
+    let Ok(survivor) = survivor_query.get_single() else { damage_events.clear(); return; };
+    let mut rng = rand::thread_rng();
+    for event in damage_events.read().filter(|event| event.is_crit) {
+        for item_def in survivor.collected_item_ids.iter().filter_map(|item_id| item_library.get_item_definition(*item_id)) {
+            for effect in &item_def.effects {
+                let ItemEffect::OnCriticalHitExplode { chance, explosion_damage_fraction, explosion_radius, explosion_color } = effect else { continue; };
+                if !rng.gen_bool((*chance).into()) { continue; }
+                let explosion_damage = (event.amount as f32 * explosion_damage_fraction).round() as i32;
+                if explosion_damage <= 0 { continue; }
+                commands.spawn((
+                    SpriteBundle {
+                        texture: asset_server.load("sprites/eldritch_nova_effect_placeholder.png"),
+                        sprite: Sprite { custom_size: Some(Vec2::splat(0.1)), color: *explosion_color, ..default() },
+                        transform: Transform::from_translation(event.position.truncate().extend(0.3)),
+                        ..default()
+                    },
+                    ExplosionEffect { damage: explosion_damage, radius_sq: explosion_radius.powi(2), timer: Timer::from_seconds(CRIT_EXPLOSION_DURATION_SECS, TimerMode::Once), already_hit_entities: vec![event.target], source: DamageSource::ItemExplosion },
+                    Name::new("CritExplosion"),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use crate::items::AutomaticWeaponLibrary;
+
+    fn survivor_with_crit(chance: f32, multiplier: f32) -> Survivor {
+        let mut world = World::new();
+        world.init_resource::<AutomaticWeaponLibrary>();
+        let mut survivor = world.run_system_once(|library: Res<AutomaticWeaponLibrary>| Survivor::new_with_skills_and_items(Vec::new(), Vec::new(), None, &library));
+        survivor.crit_chance = chance;
+        survivor.crit_multiplier = multiplier;
+        survivor
+    }
+
+    #[test]
+    fn for_attack_uses_survivor_stats_without_overrides() {
+        let survivor = survivor_with_crit(0.2, 2.0);
+        assert_eq!(CritStats::for_attack(&survivor, None, None), CritStats { chance: 0.2, multiplier: 2.0 });
+    }
+
+    #[test]
+    fn for_attack_stacks_survivor_bonuses_on_overrides() {
+        let survivor = survivor_with_crit(BASE_CRIT_CHANCE + 0.1, BASE_CRIT_MULTIPLIER + 0.5);
+        let crit = CritStats::for_attack(&survivor, Some(0.25), Some(3.0));
+        assert!((crit.chance - 0.35).abs() < 1e-5);
+        assert!((crit.multiplier - 3.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn for_attack_clamps_chance_and_multiplier() {
+        let survivor = survivor_with_crit(0.9, 1.2);
+        let crit = CritStats::for_attack(&survivor, Some(0.5), Some(0.5));
+        assert_eq!(crit.chance, 1.0);
+        assert_eq!(crit.multiplier, 1.0);
+        assert_eq!(CritStats::for_attack(&survivor_with_crit(0.02, 1.5), Some(0.0), None).chance, 0.0);
+    }
+
+    #[test]
+    fn roll_never_crits_at_zero_chance() {
+        let crit = CritStats { chance: 0.0, multiplier: 3.0 };
+        for _ in 0..100 { assert_eq!(crit.roll(10), (10, false)); }
+    }
+
+    #[test]
+    fn roll_always_crits_at_full_chance() {
+        let crit = CritStats { chance: 1.0, multiplier: 1.5 };
+        for _ in 0..100 { assert_eq!(crit.roll(11), (17, true)); }
+    }
+}
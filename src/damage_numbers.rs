@@ -0,0 +1,313 @@
+use bevy::prelude::*;
+use rand::Rng;
+use crate::{
+    game::{AppState, OnSessionEnd},
+    components::Health,
+    survivor::Survivor,
+    run_statistics::{DamageDealtEvent, DamageSource},
+};
+
+/// Enough for a busy screen; past this the oldest number on screen is recycled rather than spawning more.
+const DAMAGE_NUMBER_POOL_SIZE: usize = 96;
+/// Hits on the same target from the same kind of source within this window add to one number instead of stacking new ones.
+const MERGE_WINDOW_SECS: f32 = 0.35;
+
+const FONT_SIZE: f32 = 20.0;
+const CRIT_FONT_SIZE: f32 = 30.0;
+const SURVIVOR_FONT_SIZE: f32 = 24.0;
+
+const RISE_HEIGHT: f32 = 40.0;
+const RISE_SECS: f32 = 0.6;
+const MAX_DRIFT_X: f32 = 12.0;
+/// How long a number holds at full opacity after its latest hit before fading.
+const LINGER_SECS: f32 = 0.45;
+const FADE_SECS: f32 = 0.3;
+/// Each merged hit briefly swells the number; crits swell it further.
+const HIT_POP_SCALE: f32 = 0.25;
+const CRIT_POP_SCALE: f32 = 0.6;
+const POP_DECAY_RATE: f32 = 12.0;
+
+const Z_OFFSET: f32 = 5.0;
+
+/// Broad groups of damage, each with its own color so a glance at the numbers says what is doing the work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageNumberKind {
+    Weapon,
+    Skill,
+    Aura,
+    Summon,
+    Explosion,
+    ElderSign,
+    Dodge,
+    /// Damage taken by the survivor rather than dealt.
+    Survivor,
+}
+
+impl DamageNumberKind {
+    pub fn from_source(source: DamageSource) -> Self {
+        match source {
+            DamageSource::AutomaticWeapon(_) => DamageNumberKind::Weapon,
+            DamageSource::Skill(_) => DamageNumberKind::Skill,
+            DamageSource::CircleOfWarding => DamageNumberKind::Aura,
+            DamageSource::SwarmOfNightmares => DamageNumberKind::Summon,
+            DamageSource::ItemExplosion | DamageSource::RetaliationNova => DamageNumberKind::Explosion,
+            DamageSource::ElderSign => DamageNumberKind::ElderSign,
+            DamageSource::Dodge => DamageNumberKind::Dodge,
+        }
+    }
+
+    fn color(&self, is_crit: bool) -> Color {
+        if is_crit && *self != DamageNumberKind::Survivor { return Color::rgb(1.0, 0.85, 0.1); }
+        match self {
+            DamageNumberKind::Weapon => Color::rgb(1.0, 0.95, 0.9),
+            DamageNumberKind::Skill => Color::rgb(0.55, 0.8, 1.0),
+            DamageNumberKind::Aura => Color::rgb(0.7, 0.55, 1.0),
+            DamageNumberKind::Summon => Color::rgb(0.6, 1.0, 0.6),
+            DamageNumberKind::Explosion => Color::rgb(1.0, 0.6, 0.25),
+            DamageNumberKind::ElderSign => Color::rgb(0.3, 1.0, 0.85),
+            DamageNumberKind::Dodge => Color::rgb(0.8, 0.6, 1.0),
+            DamageNumberKind::Survivor => Color::rgb(1.0, 0.2, 0.2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DamageNumberMode {
+    #[default]
+    Full,
+    /// Only crits and damage taken by the survivor.
+    Reduced,
+    Hidden,
+}
+
+impl DamageNumberMode {
+    fn next(&self) -> Self {
+        match self {
+            DamageNumberMode::Full => DamageNumberMode::Reduced,
+            DamageNumberMode::Reduced => DamageNumberMode::Hidden,
+            DamageNumberMode::Hidden => DamageNumberMode::Full,
+        }
+    }
+
+    fn shows(&self, kind: DamageNumberKind, is_crit: bool) -> bool {
+        match self {
+            DamageNumberMode::Full => true,
+            DamageNumberMode::Reduced => is_crit || kind == DamageNumberKind::Survivor,
+            DamageNumberMode::Hidden => false,
+        }
+    }
+}
+
+/// Cycled with N: full, reduced, hidden.
+#[derive(Resource, Default, Debug)]
+pub struct DamageNumberSettings { pub mode: DamageNumberMode, }
+
+#[derive(Resource)]
+struct DamageNumberFont(Handle<Font>);
+
+/// One hit (or one drop in survivor endurance) waiting to be shown.
+#[derive(Event, Debug, Clone, Copy)]
+struct DamageNumberRequest { target: Entity, kind: DamageNumberKind, amount: i32, is_crit: bool, position: Vec3, }
+
+/// A pooled damage number. Inactive entries are hidden and wait to be reused.
+#[derive(Component, Debug)]
+struct DamageNumber {
+    active: bool,
+    target: Entity,
+    kind: DamageNumberKind,
+    total: i32,
+    is_crit: bool,
+    anchor: Vec3,
+    drift_x: f32,
+    age_secs: f32,
+    since_last_hit_secs: f32,
+    pop: f32,
+}
+
+impl DamageNumber {
+    fn inactive() -> Self {
+        Self { active: false, target: Entity::PLACEHOLDER, kind: DamageNumberKind::Weapon, total: 0, is_crit: false, anchor: Vec3::ZERO, drift_x: 0.0, age_secs: 0.0, since_last_hit_secs: 0.0, pop: 0.0 }
+    }
+}
+
+pub struct DamageNumbersPlugin;
+
+impl Plugin for DamageNumbersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageNumberRequest>()
+            .init_resource::<DamageNumberSettings>()
+            .add_systems(Startup, load_damage_number_font)
+            .add_systems(OnEnter(AppState::InGame), spawn_damage_number_pool)
+            .add_systems(Update, toggle_damage_number_mode_system)
+            .add_systems(Update, (
+                queue_dealt_damage_numbers_system,
+                queue_survivor_damage_numbers_system,
+                assign_damage_numbers_system,
+                animate_damage_numbers_system,
+            ).chain().run_if(in_state(AppState::InGame)))
+            .add_systems(OnExit(AppState::InGame), hide_damage_numbers)
+            .add_systems(OnSessionEnd, despawn_damage_number_pool);
+    }
+}
+
+fn load_damage_number_font(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(DamageNumberFont(asset_server.load("fonts/FiraSans-Bold.ttf")));
+}
+
+/// The whole pool is created up front and reused for the run, so heavy fights never spawn or despawn text.
+fn spawn_damage_number_pool(mut commands: Commands, font: Res<DamageNumberFont>, pool_query: Query<(), With<DamageNumber>>) {
+    if !pool_query.is_empty() { return; }
+    for _ in 0..DAMAGE_NUMBER_POOL_SIZE {
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section("", TextStyle { font: font.0.clone(), font_size: FONT_SIZE, color: Color::WHITE }),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            DamageNumber::inactive(),
+            Name::new("DamageNumber"),
+        ));
+    }
+}
+
+/// Numbers freeze while the game is paused, so they are cleared rather than left hanging over a menu.
+fn hide_damage_numbers(mut pool_query: Query<(&mut DamageNumber, &mut Visibility)>) {
+    for (mut number, mut visibility) in pool_query.iter_mut() {
+        if !number.active { continue; }
+        *number = DamageNumber::inactive();
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn despawn_damage_number_pool(mut commands: Commands, query: Query<Entity, With<DamageNumber>>) { for entity in query.iter() { commands.entity(entity).despawn_recursive(); } }
+
+fn toggle_damage_number_mode_system(keyboard_input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<DamageNumberSettings>) {
+    if keyboard_input.just_pressed(KeyCode::KeyN) { settings.mode = settings.mode.next(); }
+}
+
+fn queue_dealt_damage_numbers_system(mut damage_events: EventReader<DamageDealtEvent>, mut request_writer: EventWriter<DamageNumberRequest>) {
+    for event in damage_events.read() {
+        if event.amount <= 0 { continue; }
+        request_writer.send(DamageNumberRequest { target: event.target, kind: DamageNumberKind::from_source(event.source), amount: event.amount, is_crit: event.is_crit, position: event.position });
+    }
+}
+
+/// Survivor damage comes from many places; watching endurance catches all of them without touching each one.
+fn queue_survivor_damage_numbers_system(
+    survivor_query: Query<(Entity, &Transform, &Health), With<Survivor>>,
+    mut last_health: Local<Option<(Entity, i32)>>,
+    mut request_writer: EventWriter<DamageNumberRequest>,
+) {
+    let Ok((survivor_entity, transform, health)) = survivor_query.get_single() else { *last_health = None; return; };
+    if let Some((last_entity, last_value)) = *last_health {
+        if last_entity == survivor_entity && health.0 < last_value {
+            request_writer.send(DamageNumberRequest { target: survivor_entity, kind: DamageNumberKind::Survivor, amount: last_value - health.0, is_crit: false, position: transform.translation });
+        }
+    }
+    *last_health = Some((survivor_entity, health.0));
+}
+
+fn assign_damage_numbers_system(
+    settings: Res<DamageNumberSettings>,
+    mut requests: EventReader<DamageNumberRequest>,
+    mut pool_query: Query<(Entity, &mut DamageNumber, &mut Text, &mut Visibility)>,
+) {
+    let mut rng = rand::thread_rng();
+    for request in requests.read() {
+        if !settings.mode.shows(request.kind, request.is_crit) { continue; }
+
+        let mergeable = pool_query.iter().find(|(_, number, _, _)| number.active && number.target == request.target && number.kind == request.kind && number.since_last_hit_secs < MERGE_WINDOW_SECS).map(|(entity, ..)| entity);
+        if let Some(Ok((_, mut number, mut text, _))) = mergeable.map(|entity| pool_query.get_mut(entity)) {
+            number.total += request.amount;
+            number.is_crit |= request.is_crit;
+            number.since_last_hit_secs = 0.0;
+            number.pop = if request.is_crit { CRIT_POP_SCALE } else { HIT_POP_SCALE };
+            style_damage_number(&number, &mut text);
+            continue;
+        }
+
+        // Prefer a free entry; with none free, take over whichever active number has been around longest.
+        let slot = pool_query.iter().find(|(_, number, _, _)| !number.active)
+            .or_else(|| pool_query.iter().max_by(|a, b| a.1.age_secs.total_cmp(&b.1.age_secs)))
+            .map(|(entity, ..)| entity);
+        let Some(Ok((_, mut number, mut text, mut visibility))) = slot.map(|entity| pool_query.get_mut(entity)) else { continue; };
+        *number = DamageNumber {
+            active: true,
+            target: request.target,
+            kind: request.kind,
+            total: request.amount,
+            is_crit: request.is_crit,
+            anchor: request.position + Vec3::new(0.0, 10.0, Z_OFFSET),
+            drift_x: rng.gen_range(-MAX_DRIFT_X..MAX_DRIFT_X),
+            age_secs: 0.0,
+            since_last_hit_secs: 0.0,
+            pop: if request.is_crit { CRIT_POP_SCALE } else { 0.0 },
+        };
+        style_damage_number(&number, &mut text);
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn style_damage_number(number: &DamageNumber, text: &mut Text) {
+    let Some(section) = text.sections.get_mut(0) else { return; };
+    section.value = if number.is_crit { format!("{}!", number.total) } else { number.total.to_string() };
+    section.style.font_size = if number.is_crit { CRIT_FONT_SIZE } else if number.kind == DamageNumberKind::Survivor { SURVIVOR_FONT_SIZE } else { FONT_SIZE };
+    section.style.color = number.kind.color(number.is_crit);
+}
+
+fn animate_damage_numbers_system(time: Res<Time>, mut pool_query: Query<(&mut DamageNumber, &mut Transform, &mut Text, &mut Visibility)>) {
+    let delta = time.delta_seconds();
+    for (mut number, mut transform, mut text, mut visibility) in pool_query.iter_mut() {
+        if !number.active { continue; }
+        number.age_secs += delta;
+        number.since_last_hit_secs += delta;
+        number.pop *= (-POP_DECAY_RATE * delta).exp();
+        if number.since_last_hit_secs >= LINGER_SECS + FADE_SECS {
+            number.active = false;
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+
+        // Ease out: quick off the target, then settling.
+        let rise = 1.0 - (1.0 - (number.age_secs / RISE_SECS).min(1.0)).powi(2);
+        transform.translation = number.anchor + Vec3::new(number.drift_x * rise, RISE_HEIGHT * rise, 0.0);
+        transform.scale = Vec3::splat(1.0 + number.pop);
+
+        let fade = ((number.since_last_hit_secs - LINGER_SECS) / FADE_SECS).clamp(0.0, 1.0);
+        if let Some(section) = text.sections.get_mut(0) { section.style.color.set_a(1.0 - fade); }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::AutomaticWeaponId;
+
+    fn damage_numbers_test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Font>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .add_event::<DamageDealtEvent>()
+            .init_state::<AppState>()
+            .add_plugins(DamageNumbersPlugin);
+        app
+    }
+
+    #[test]
+    fn leaving_in_game_hides_the_active_numbers() {
+        let mut app = damage_numbers_test_app();
+        app.world.resource_mut::<NextState<AppState>>().set(AppState::InGame);
+        app.update();
+
+        let target = app.world.spawn_empty().id();
+        app.world.send_event(DamageDealtEvent { source: DamageSource::AutomaticWeapon(AutomaticWeaponId(0)), amount: 12, target, position: Vec3::ZERO, is_crit: false });
+        app.update();
+        let mut number_query = app.world.query::<(&DamageNumber, &Visibility)>();
+        assert_eq!(number_query.iter(&app.world).filter(|(number, visibility)| number.active && **visibility == Visibility::Visible).count(), 1);
+
+        app.world.resource_mut::<NextState<AppState>>().set(AppState::LevelUp);
+        app.update();
+        assert!(number_query.iter(&app.world).all(|(number, visibility)| !number.active && *visibility == Visibility::Hidden));
+    }
+}
@@ -0,0 +1,267 @@
+use bevy::prelude::*;
+use std::time::Duration;
+use crate::{
+    game::{AppState, OnSessionEnd},
+    components::Health,
+    survivor::{Survivor, SURVIVOR_SIZE},
+    horror::Horror,
+    skills::SurvivorDash,
+    audio::{PlaySoundAtEvent, SoundEffect},
+    run_statistics::{DamageDealtEvent, DamageSource},
+    elite_affixes::{AffixWard, apply_horror_damage},
+    visual_effects::HitFlash,
+};
+
+const DODGE_KEYS: [KeyCode; 2] = [KeyCode::Space, KeyCode::ShiftLeft];
+const BASE_DODGE_CHARGES: u32 = 1;
+const BASE_DODGE_COOLDOWN_SECS: f32 = 2.5;
+const MIN_DODGE_COOLDOWN_SECS: f32 = 0.5;
+const BASE_DODGE_DISTANCE: f32 = 160.0;
+const BASE_DODGE_DURATION_SECS: f32 = 0.18;
+/// Slightly longer than the dash itself, so the survivor is still safe on the frame they land.
+const BASE_DODGE_INVULNERABILITY_SECS: f32 = 0.3;
+
+const AFTERIMAGE_INTERVAL_SECS: f32 = 0.03;
+const AFTERIMAGE_LIFETIME_SECS: f32 = 0.25;
+const AFTERIMAGE_COLOR: Color = Color::rgba(0.6, 0.4, 1.0, 0.6);
+
+/// The survivor's core dodge. Charges recover one at a time; upgrades add charges, speed up recovery and let the dash wound horrors.
+#[derive(Component, Debug)]
+pub struct SurvivorDodge {
+    pub max_charges: u32,
+    pub charges: u32,
+    pub recharge_timer: Timer,
+    pub distance: f32,
+    pub duration_secs: f32,
+    pub invulnerability_secs: f32,
+    /// Damage dealt to every horror passed through during a dodge. Zero until upgraded.
+    pub damage: i32,
+}
+
+impl Default for SurvivorDodge {
+    fn default() -> Self {
+        Self {
+            max_charges: BASE_DODGE_CHARGES,
+            charges: BASE_DODGE_CHARGES,
+            recharge_timer: Timer::from_seconds(BASE_DODGE_COOLDOWN_SECS, TimerMode::Once),
+            distance: BASE_DODGE_DISTANCE,
+            duration_secs: BASE_DODGE_DURATION_SECS,
+            invulnerability_secs: BASE_DODGE_INVULNERABILITY_SECS,
+            damage: 0,
+        }
+    }
+}
+
+impl SurvivorDodge {
+    pub fn reduce_cooldown(&mut self, percent: u32) {
+        let current = self.recharge_timer.duration().as_secs_f32();
+        let reduced = (current * (1.0 - percent as f32 / 100.0)).max(MIN_DODGE_COOLDOWN_SECS);
+        self.recharge_timer.set_duration(Duration::from_secs_f32(reduced));
+    }
+
+    pub fn add_charges(&mut self, amount: u32) {
+        self.max_charges += amount;
+        self.charges += amount;
+    }
+}
+
+/// Present on the survivor for the length of a dodge, alongside the `SurvivorDash` that moves them.
+#[derive(Component, Debug)]
+pub struct DodgeInProgress { pub afterimage_timer: Timer, pub already_hit_entities: Vec<Entity>, }
+
+#[derive(Component)]
+struct DodgeAfterimage { timer: Timer, }
+
+pub struct DodgePlugin;
+
+impl Plugin for DodgePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+                dodge_recharge_system,
+                dodge_input_system,
+                dodge_afterimage_spawn_system,
+                dodge_damage_system,
+                dodge_end_system,
+                dodge_afterimage_fade_system,
+            ).chain().run_if(in_state(AppState::InGame)))
+            .add_systems(OnSessionEnd, despawn_dodge_afterimages);
+    }
+}
+
+fn dodge_recharge_system(time: Res<Time>, mut dodge_query: Query<&mut SurvivorDodge>) {
+    for mut dodge in dodge_query.iter_mut() {
+        if dodge.charges >= dodge.max_charges { continue; }
+        dodge.recharge_timer.tick(time.delta());
+        if dodge.recharge_timer.finished() {
+            dodge.charges += 1;
+            dodge.recharge_timer.reset();
+        }
+    }
+}
+
+fn dodge_input_system(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut survivor_query: Query<(Entity, &Transform, &mut Survivor, &mut SurvivorDodge, Has<SurvivorDash>)>,
+    mut sound_event_writer: EventWriter<PlaySoundAtEvent>,
+) {
+    if !keyboard_input.any_just_pressed(DODGE_KEYS) { return; }
+    let Ok((survivor_entity, survivor_transform, mut survivor, mut dodge, is_dashing)) = survivor_query.get_single_mut() else { return; };
+    if is_dashing || dodge.charges == 0 { return; }
+
+    // Dodge the way the survivor is moving; standing still, dodge towards the cursor.
+    let mut direction = Vec2::ZERO;
+    if keyboard_input.pressed(KeyCode::KeyA) { direction.x -= 1.0; }
+    if keyboard_input.pressed(KeyCode::KeyD) { direction.x += 1.0; }
+    if keyboard_input.pressed(KeyCode::KeyW) { direction.y += 1.0; }
+    if keyboard_input.pressed(KeyCode::KeyS) { direction.y -= 1.0; }
+    let direction = if direction != Vec2::ZERO { direction.normalize() } else { survivor.aim_direction };
+    if direction == Vec2::ZERO { return; }
+
+    if dodge.charges == dodge.max_charges { dodge.recharge_timer.reset(); }
+    dodge.charges -= 1;
+    survivor.grant_invulnerability(dodge.invulnerability_secs);
+    commands.entity(survivor_entity).insert((
+        SurvivorDash { velocity: direction * dodge.distance / dodge.duration_secs.max(0.01), timer: Timer::from_seconds(dodge.duration_secs, TimerMode::Once) },
+        DodgeInProgress { afterimage_timer: Timer::from_seconds(AFTERIMAGE_INTERVAL_SECS, TimerMode::Repeating), already_hit_entities: Vec::new() },
+    ));
+    sound_event_writer.send(PlaySoundAtEvent(SoundEffect::Dodge, survivor_transform.translation));
+}
+
+fn dodge_afterimage_spawn_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut survivor_query: Query<(&Transform, &Handle<Image>, &mut DodgeInProgress)>,
+) {
+    for (transform, texture, mut dodge) in survivor_query.iter_mut() {
+        dodge.afterimage_timer.tick(time.delta());
+        if !dodge.afterimage_timer.just_finished() { continue; }
+        commands.spawn((
+            SpriteBundle {
+                texture: texture.clone(),
+                sprite: Sprite { custom_size: Some(SURVIVOR_SIZE), color: AFTERIMAGE_COLOR, ..default() },
+                transform: Transform::from_translation(transform.translation.truncate().extend(transform.translation.z - 0.1)),
+                ..default()
+            },
+            DodgeAfterimage { timer: Timer::from_seconds(AFTERIMAGE_LIFETIME_SECS, TimerMode::Once) },
+            Name::new("DodgeAfterimage"),
+        ));
+    }
+}
+
+fn dodge_damage_system(
+    mut commands: Commands,
+    mut survivor_query: Query<(&Transform, &SurvivorDodge, &mut DodgeInProgress)>,
+    mut horror_query: Query<(Entity, &GlobalTransform, &mut Health, Option<&mut AffixWard>, &Horror)>,
+    mut sound_event_writer: EventWriter<PlaySoundAtEvent>,
+    mut damage_event_writer: EventWriter<DamageDealtEvent>,
+) {
+    let Ok((survivor_transform, dodge, mut in_progress)) = survivor_query.get_single_mut() else { return; };
+    if dodge.damage <= 0 { return; }
+    let survivor_pos = survivor_transform.translation.truncate();
+    for (horror_entity, horror_gtransform, mut health, mut ward_opt, horror_data) in horror_query.iter_mut() {
+        if health.0 <= 0 || in_progress.already_hit_entities.contains(&horror_entity) { continue; }
+        if horror_gtransform.translation().truncate().distance(survivor_pos) >= SURVIVOR_SIZE.x / 2.0 + horror_data.size.x / 2.0 { continue; }
+        let dealt = apply_horror_damage(&mut health, ward_opt.as_deref_mut(), dodge.damage);
+        in_progress.already_hit_entities.push(horror_entity);
+        sound_event_writer.send(PlaySoundAtEvent(SoundEffect::HorrorHit, horror_gtransform.translation()));
+        if dealt > 0 {
+            damage_event_writer.send(DamageDealtEvent { source: DamageSource::Dodge, amount: dealt, target: horror_entity, position: horror_gtransform.translation(), is_crit: false });
+        }
+        commands.entity(horror_entity).try_insert(HitFlash::default());
+    }
+}
+
+fn dodge_end_system(mut commands: Commands, survivor_query: Query<Entity, (With<DodgeInProgress>, Without<SurvivorDash>)>) {
+    for entity in survivor_query.iter() { commands.entity(entity).remove::<DodgeInProgress>(); }
+}
+
+fn dodge_afterimage_fade_system(mut commands: Commands, time: Res<Time>, mut afterimage_query: Query<(Entity, &mut DodgeAfterimage, &mut Sprite)>) {
+    for (entity, mut afterimage, mut sprite) in afterimage_query.iter_mut() {
+        afterimage.timer.tick(time.delta());
+        sprite.color.set_a(AFTERIMAGE_COLOR.a() * (1.0 - afterimage.timer.fraction()));
+        if afterimage.timer.finished() { commands.entity(entity).despawn_recursive(); }
+    }
+}
+
+fn despawn_dodge_afterimages(mut commands: Commands, query: Query<Entity, With<DodgeAfterimage>>) { for entity in query.iter() { commands.entity(entity).despawn_recursive(); } }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use crate::items::AutomaticWeaponLibrary;
+
+    fn spawn_dodging_survivor(world: &mut World, dodge: SurvivorDodge) -> Entity {
+        world.init_resource::<AutomaticWeaponLibrary>();
+        let mut survivor = world.run_system_once(|library: Res<AutomaticWeaponLibrary>| Survivor::new_with_skills_and_items(Vec::new(), Vec::new(), None, &library));
+        survivor.invincibility_timer.tick(survivor.invincibility_timer.duration());
+        survivor.aim_direction = Vec2::X;
+        world.spawn((survivor, dodge, Transform::default())).id()
+    }
+
+    fn press_dodge(world: &mut World) {
+        let mut keyboard_input = world.resource_mut::<ButtonInput<KeyCode>>();
+        keyboard_input.release(KeyCode::Space);
+        keyboard_input.clear();
+        keyboard_input.press(KeyCode::Space);
+    }
+
+    #[test]
+    fn dodge_spends_a_charge_and_cannot_chain_mid_dash() {
+        let mut world = World::new();
+        world.init_resource::<ButtonInput<KeyCode>>();
+        world.init_resource::<Events<PlaySoundAtEvent>>();
+        let mut dodge = SurvivorDodge::default();
+        dodge.add_charges(1);
+        let survivor = spawn_dodging_survivor(&mut world, dodge);
+
+        press_dodge(&mut world);
+        world.run_system_once(dodge_input_system);
+        assert_eq!(world.get::<SurvivorDodge>(survivor).unwrap().charges, 1);
+        assert!(world.get::<SurvivorDash>(survivor).is_some());
+
+        press_dodge(&mut world);
+        world.run_system_once(dodge_input_system);
+        assert_eq!(world.get::<SurvivorDodge>(survivor).unwrap().charges, 1);
+    }
+
+    #[test]
+    fn dodge_grants_its_invulnerability_window() {
+        let mut world = World::new();
+        world.init_resource::<ButtonInput<KeyCode>>();
+        world.init_resource::<Events<PlaySoundAtEvent>>();
+        let survivor = spawn_dodging_survivor(&mut world, SurvivorDodge::default());
+
+        press_dodge(&mut world);
+        world.run_system_once(dodge_input_system);
+        let mut survivor_component = world.get_mut::<Survivor>(survivor).unwrap();
+        assert_eq!(survivor_component.invincibility_timer.duration(), Duration::from_secs_f32(BASE_DODGE_INVULNERABILITY_SECS));
+        survivor_component.invincibility_timer.tick(Duration::from_secs_f32(BASE_DODGE_INVULNERABILITY_SECS * 0.9));
+        assert!(!survivor_component.invincibility_timer.finished());
+        survivor_component.invincibility_timer.tick(Duration::from_secs_f32(BASE_DODGE_INVULNERABILITY_SECS * 0.2));
+        assert!(survivor_component.invincibility_timer.finished());
+    }
+
+    #[test]
+    fn charges_recover_one_per_cooldown() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        let mut dodge = SurvivorDodge::default();
+        dodge.add_charges(1);
+        dodge.charges = 0;
+        let survivor = world.spawn(dodge).id();
+
+        world.resource_mut::<Time>().advance_by(Duration::from_secs_f32(BASE_DODGE_COOLDOWN_SECS * 0.5));
+        world.run_system_once(dodge_recharge_system);
+        assert_eq!(world.get::<SurvivorDodge>(survivor).unwrap().charges, 0);
+
+        world.resource_mut::<Time>().advance_by(Duration::from_secs_f32(BASE_DODGE_COOLDOWN_SECS * 0.6));
+        world.run_system_once(dodge_recharge_system);
+        assert_eq!(world.get::<SurvivorDodge>(survivor).unwrap().charges, 1);
+
+        world.resource_mut::<Time>().advance_by(Duration::from_secs_f32(BASE_DODGE_COOLDOWN_SECS));
+        world.run_system_once(dodge_recharge_system);
+        assert_eq!(world.get::<SurvivorDodge>(survivor).unwrap().charges, 2);
+    }
+}
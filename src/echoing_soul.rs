@@ -3,14 +3,22 @@ use crate::{
     survivor::{Survivor, SURVIVOR_SIZE}, // Updated
     components::Velocity,
     game::AppState,
-    audio::{PlaySoundEvent, SoundEffect},
+    audio::{PlaySoundEvent, PlaySoundAtEvent, SoundEffect},
+    run_statistics::RunStatistics,
+    xp_curve::XpCurve,
 };
 
 pub const ECHOING_SOUL_SIZE: Vec2 = Vec2::new(10.0, 10.0);
 pub const ECHOING_SOUL_VALUE: u32 = 25; 
 const SOUL_GRAVITATE_SPEED: f32 = 300.0;
+const MAGNETIZED_SOUL_SPEED_MULTIPLIER: f32 = 2.5;
 // Updated to use SURVIVOR_SIZE
 const SOUL_PICKUP_RADIUS_COLLISION: f32 = SURVIVOR_SIZE.x / 2.0 + ECHOING_SOUL_SIZE.x / 2.0 - 5.0; 
+/// A freshly dropped soul within this distance of a resting one is absorbed into it.
+const SOUL_MERGE_RADIUS: f32 = 40.0;
+const MAX_ECHOING_SOULS: usize = 300;
+/// Once over the cap, fold this many extra souls so the cluster isn't rebuilt every frame.
+const SOUL_CLUSTER_FOLD_SLACK: usize = 50;
 
 
 pub struct EchoingSoulPlugin; // Renamed
@@ -18,7 +26,10 @@ pub struct EchoingSoulPlugin; // Renamed
 impl Plugin for EchoingSoulPlugin { // Renamed
     fn build(&self, app: &mut App) {
         app
+            .init_resource::<SoulSpawnSequence>()
             .add_systems(Update, (
+                echoing_soul_merge_system,
+                echoing_soul_cap_system,
                 echoing_soul_gravitation_and_movement_system,
                 echoing_soul_collection_system,
             ).chain().run_if(in_state(AppState::InGame)));
@@ -28,46 +39,188 @@ impl Plugin for EchoingSoulPlugin { // Renamed
 #[derive(Component)]
 pub struct EchoingSoul {
     pub value: u32,
+    /// Drop order, used to find the oldest souls when folding into a cluster. Stamped by the merge system.
+    pub sequence: u64,
 }
 
+/// The single large soul that the oldest souls are folded into once the cap is exceeded.
+#[derive(Component)]
+pub struct SoulCluster;
+
+#[derive(Resource, Default)]
+struct SoulSpawnSequence(u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoulTier { Faint, Bright, Radiant, Cluster, }
+
+impl SoulTier {
+    pub fn from_value(value: u32) -> Self {
+        match value {
+            0..=59 => SoulTier::Faint,
+            60..=249 => SoulTier::Bright,
+            _ => SoulTier::Radiant,
+        }
+    }
+
+    fn sprite_path(&self) -> &'static str {
+        match self {
+            SoulTier::Faint => "sprites/echoing_soul_orb_placeholder.png",
+            SoulTier::Bright => "sprites/echoing_soul_bright_placeholder.png",
+            SoulTier::Radiant => "sprites/echoing_soul_radiant_placeholder.png",
+            SoulTier::Cluster => "sprites/soul_cluster_placeholder.png",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            SoulTier::Faint => Color::WHITE,
+            SoulTier::Bright => Color::rgb(0.5, 1.0, 0.7),
+            SoulTier::Radiant => Color::rgb(1.0, 0.85, 0.4),
+            SoulTier::Cluster => Color::rgb(1.0, 0.4, 0.6),
+        }
+    }
+
+    fn size(&self) -> Vec2 {
+        match self {
+            SoulTier::Faint => ECHOING_SOUL_SIZE,
+            SoulTier::Bright => ECHOING_SOUL_SIZE * 1.4,
+            SoulTier::Radiant => ECHOING_SOUL_SIZE * 1.8,
+            SoulTier::Cluster => ECHOING_SOUL_SIZE * 3.0,
+        }
+    }
+}
+
+fn apply_soul_tier(tier: SoulTier, sprite: &mut Sprite, texture: &mut Handle<Image>, asset_server: &AssetServer) {
+    sprite.custom_size = Some(tier.size());
+    sprite.color = tier.color();
+    *texture = asset_server.load(tier.sprite_path());
+}
+
+/// Pulls the soul towards the survivor no matter how far away it is, until collected.
+#[derive(Component)]
+pub struct VoidMagnetized;
+
 pub fn spawn_echoing_soul(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
     position: Vec3,
     value: u32,
 ) {
+    let tier = SoulTier::from_value(value);
     commands.spawn((
         SpriteBundle {
-            texture: asset_server.load("sprites/echoing_soul_orb_placeholder.png"),
+            texture: asset_server.load(tier.sprite_path()),
             sprite: Sprite {
-                custom_size: Some(ECHOING_SOUL_SIZE),
+                custom_size: Some(tier.size()),
+                color: tier.color(),
                 ..default()
             },
             transform: Transform::from_translation(position),
             ..default()
         },
-        EchoingSoul { value },
+        EchoingSoul { value, sequence: 0 },
         Velocity(Vec2::ZERO),
         Name::new("EchoingSoul"),
     ));
 }
 
+/// The parts of a soul that change with its tier.
+type SoulAppearance<'a> = (&'a mut Sprite, &'a mut Handle<Image>);
+
+/// Every soul the merge system can fold a new drop into.
+type MergeableSoulQuery<'w, 's> = Query<'w, 's, (Entity, &'static Transform, &'static Velocity, &'static mut EchoingSoul, SoulAppearance<'static>, Has<SoulCluster>)>;
+
+/// Folds newly dropped souls into a resting soul nearby, promoting it to a higher tier as its value grows.
+fn echoing_soul_merge_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut sequence: ResMut<SoulSpawnSequence>,
+    mut soul_query: MergeableSoulQuery,
+) {
+    // Souls the merge system has not stamped yet are the ones dropped since it last ran.
+    let new_souls: Vec<Entity> = soul_query.iter().filter(|(_, _, _, soul, ..)| soul.sequence == 0).map(|(entity, ..)| entity).collect();
+    for new_entity in new_souls {
+        let Ok((_, new_transform, _, new_soul, _, _)) = soul_query.get(new_entity) else { continue; };
+        let new_pos = new_transform.translation.truncate();
+        let new_value = new_soul.value;
+
+        let merge_target = soul_query.iter()
+            .filter(|(entity, _, velocity, soul, _, is_cluster)| *entity != new_entity && soul.sequence != 0 && velocity.0 == Vec2::ZERO && !is_cluster)
+            .map(|(entity, transform, ..)| (entity, transform.translation.truncate().distance_squared(new_pos)))
+            .filter(|(_, distance_sq)| *distance_sq < SOUL_MERGE_RADIUS * SOUL_MERGE_RADIUS)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(entity, _)| entity);
+
+        if let Some(target_entity) = merge_target {
+            if let Ok((_, _, _, mut target_soul, (mut sprite, mut texture), _)) = soul_query.get_mut(target_entity) {
+                let previous_tier = SoulTier::from_value(target_soul.value);
+                target_soul.value += new_value;
+                let new_tier = SoulTier::from_value(target_soul.value);
+                if new_tier != previous_tier { apply_soul_tier(new_tier, &mut sprite, &mut texture, &asset_server); }
+            }
+            commands.entity(new_entity).despawn();
+        } else if let Ok((_, _, _, mut new_soul, _, _)) = soul_query.get_mut(new_entity) {
+            sequence.0 += 1;
+            new_soul.sequence = sequence.0;
+        }
+    }
+}
+
+/// Keeps the soul count bounded by folding the oldest souls into a single cluster. Total value is preserved.
+fn echoing_soul_cap_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut soul_query: Query<(Entity, &mut EchoingSoul, SoulAppearance, Has<SoulCluster>), Without<VoidMagnetized>>,
+) {
+    let soul_count = soul_query.iter().count();
+    if soul_count <= MAX_ECHOING_SOULS { return; }
+
+    let mut candidates: Vec<(Entity, u64)> = soul_query.iter()
+        .filter(|(_, soul, _, is_cluster)| !is_cluster && soul.sequence != 0)
+        .map(|(entity, soul, ..)| (entity, soul.sequence))
+        .collect();
+    candidates.sort_unstable_by_key(|(_, sequence)| *sequence);
+    let fold_count = (soul_count - MAX_ECHOING_SOULS + SOUL_CLUSTER_FOLD_SLACK).min(candidates.len());
+    if fold_count == 0 { return; }
+
+    let existing_cluster = soul_query.iter().find(|(.., is_cluster)| *is_cluster).map(|(entity, ..)| entity);
+    // Without a cluster yet, the oldest soul becomes one and absorbs the rest.
+    let (cluster_entity, folded) = match existing_cluster {
+        Some(entity) => (entity, &candidates[..fold_count]),
+        None => (candidates[0].0, &candidates[1..fold_count]),
+    };
+
+    let mut folded_value = 0;
+    for (entity, _) in folded {
+        if let Ok((_, soul, ..)) = soul_query.get(*entity) { folded_value += soul.value; }
+        commands.entity(*entity).despawn();
+    }
+    if let Ok((_, mut cluster_soul, (mut sprite, mut texture), is_cluster)) = soul_query.get_mut(cluster_entity) {
+        cluster_soul.value += folded_value;
+        if !is_cluster {
+            commands.entity(cluster_entity).insert((SoulCluster, Name::new("SoulCluster")));
+            apply_soul_tier(SoulTier::Cluster, &mut sprite, &mut texture, &asset_server);
+        }
+    }
+}
+
 fn echoing_soul_gravitation_and_movement_system(
-    mut soul_query: Query<(&mut Transform, &mut Velocity), With<EchoingSoul>>,
-    player_query: Query<(&Transform, &Survivor), (With<Survivor>, Without<EchoingSoul>)>,
+    mut soul_query: Query<(&mut Transform, &mut Velocity, Has<VoidMagnetized>), With<EchoingSoul>>,
+    player_query: Query<(&Transform, &Survivor), Without<EchoingSoul>>,
     time: Res<Time>,
 ) {
     if let Ok((player_transform, player_stats)) = player_query.get_single() {
         let player_pos = player_transform.translation.truncate();
         let effective_gravitate_radius = player_stats.get_effective_pickup_radius();
 
-        for (mut soul_transform, mut soul_velocity) in soul_query.iter_mut() {
+        for (mut soul_transform, mut soul_velocity, is_magnetized) in soul_query.iter_mut() {
             let soul_pos = soul_transform.translation.truncate();
             let distance_to_player = player_pos.distance(soul_pos);
 
-            if distance_to_player < effective_gravitate_radius {
+            if is_magnetized || distance_to_player < effective_gravitate_radius {
                 let direction_to_player = (player_pos - soul_pos).normalize_or_zero();
-                soul_velocity.0 = direction_to_player * SOUL_GRAVITATE_SPEED;
+                let speed = if is_magnetized { SOUL_GRAVITATE_SPEED * MAGNETIZED_SOUL_SPEED_MULTIPLIER } else { SOUL_GRAVITATE_SPEED };
+                soul_velocity.0 = direction_to_player * speed;
             } else {
                  if soul_velocity.0 != Vec2::ZERO && distance_to_player > effective_gravitate_radius + 20.0 {
                      soul_velocity.0 = Vec2::ZERO;
@@ -78,7 +231,7 @@ fn echoing_soul_gravitation_and_movement_system(
             soul_transform.translation.y += soul_velocity.0.y * time.delta_seconds();
         }
     } else {
-        for (mut soul_transform, mut soul_velocity) in soul_query.iter_mut() {
+        for (mut soul_transform, mut soul_velocity, _) in soul_query.iter_mut() {
             if soul_velocity.0 != Vec2::ZERO {
                  soul_velocity.0 *= 0.9; 
                  if soul_velocity.0.length_squared() < 0.1 {
@@ -95,8 +248,10 @@ fn echoing_soul_collection_system(
     mut commands: Commands,
     soul_query: Query<(Entity, &Transform, &EchoingSoul)>,
     mut player_query: Query<(&Transform, &mut Survivor), With<Survivor>>,
-    mut next_app_state: ResMut<NextState<AppState>>,
+    xp_curve: Res<XpCurve>,
     mut sound_event_writer: EventWriter<PlaySoundEvent>,
+    mut sound_at_event_writer: EventWriter<PlaySoundAtEvent>,
+    mut run_stats: ResMut<RunStatistics>,
 ) {
     if let Ok((player_transform, mut player_stats)) = player_query.get_single_mut() {
         let player_pos = player_transform.translation.truncate();
@@ -104,9 +259,65 @@ fn echoing_soul_collection_system(
             let soul_pos = soul_transform.translation.truncate();
             if player_pos.distance(soul_pos) < SOUL_PICKUP_RADIUS_COLLISION { 
                 commands.entity(soul_entity).despawn();
-                sound_event_writer.send(PlaySoundEvent(SoundEffect::SoulCollect));
-                player_stats.add_experience(soul_data.value, &mut next_app_state, &mut sound_event_writer);
+                sound_at_event_writer.send(PlaySoundAtEvent(SoundEffect::SoulCollect, soul_transform.translation));
+                run_stats.record_experience(soul_data.value);
+                player_stats.add_experience(soul_data.value, &xp_curve, &mut sound_event_writer);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn soul_test_world() -> (App, Schedule) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_resource::<SoulSpawnSequence>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems((echoing_soul_merge_system, echoing_soul_cap_system).chain());
+        (app, schedule)
+    }
+
+    fn spawn_test_soul(world: &mut World, position: Vec2, value: u32) -> Entity {
+        world.spawn((
+            SpriteBundle { transform: Transform::from_translation(position.extend(0.0)), ..default() },
+            EchoingSoul { value, sequence: 0 },
+            Velocity(Vec2::ZERO),
+        )).id()
+    }
+
+    #[test]
+    fn new_soul_merges_into_a_resting_soul_within_the_merge_radius() {
+        let (mut app, mut schedule) = soul_test_world();
+        let resting = spawn_test_soul(&mut app.world, Vec2::ZERO, 25);
+        schedule.run(&mut app.world);
+
+        let nearby = spawn_test_soul(&mut app.world, Vec2::new(SOUL_MERGE_RADIUS * 0.5, 0.0), 40);
+        let distant = spawn_test_soul(&mut app.world, Vec2::new(SOUL_MERGE_RADIUS * 2.0, 0.0), 10);
+        schedule.run(&mut app.world);
+
+        assert!(app.world.get_entity(nearby).is_none());
+        assert_eq!(app.world.get::<EchoingSoul>(resting).unwrap().value, 65);
+        assert_eq!(app.world.get::<Sprite>(resting).unwrap().custom_size, Some(SoulTier::Bright.size()));
+        assert_eq!(app.world.get::<EchoingSoul>(distant).unwrap().value, 10);
+    }
+
+    #[test]
+    fn soul_count_stays_under_the_cap_without_losing_value() {
+        let (mut app, mut schedule) = soul_test_world();
+        let overflow = 10;
+        for index in 0..MAX_ECHOING_SOULS + overflow {
+            spawn_test_soul(&mut app.world, Vec2::new(index as f32 * SOUL_MERGE_RADIUS * 2.0, 0.0), 5);
+        }
+        schedule.run(&mut app.world);
+
+        let mut soul_query = app.world.query::<(&EchoingSoul, Has<SoulCluster>)>();
+        let souls: Vec<(u32, bool)> = soul_query.iter(&app.world).map(|(soul, is_cluster)| (soul.value, is_cluster)).collect();
+        assert!(souls.len() <= MAX_ECHOING_SOULS);
+        assert_eq!(souls.iter().filter(|(_, is_cluster)| *is_cluster).count(), 1);
+        assert_eq!(souls.iter().map(|(value, _)| value).sum::<u32>(), 5 * (MAX_ECHOING_SOULS + overflow) as u32);
+    }
+}
@@ -0,0 +1,348 @@
+use bevy::{prelude::*, ecs::query::QueryData};
+use rand::{Rng, seq::SliceRandom};
+use crate::{
+    game::{AppState, GameState},
+    components::Health,
+    survivor::Survivor,
+    visual_effects::UprightAnchor,
+    horror::{
+        Horror, HorrorType, HorrorKilledEvent, CarapaceWardenBehavior,
+        spawn_horror_projectile, spawn_horror_ground_telegraph, spawn_and_return_horror_entity, brood_sac_split_system,
+    },
+};
+
+pub const HASTED_SPEED_MULTIPLIER: f32 = 1.6;
+pub const HASTED_FIRE_RATE_MULTIPLIER: f32 = 0.6;
+
+const AFFIX_COUNT_WEIGHTS: [(usize, u32); 3] = [(1, 55), (2, 33), (3, 12)];
+
+const VAMPIRIC_FEED_RADIUS: f32 = 200.0;
+const VAMPIRIC_HEAL_FRACTION: f32 = 0.1;
+
+const WARD_HEALTH_FRACTION: f32 = 0.4;
+const WARD_RECHARGE_DELAY_SECS: f32 = 4.0;
+const WARD_HALO_SCALE: f32 = 1.5;
+
+const EXPLOSIVE_RADIUS: f32 = 110.0;
+const EXPLOSIVE_DELAY_SECS: f32 = 1.0;
+const EXPLOSIVE_DAMAGE_MULTIPLIER: f32 = 2.0;
+
+const BLINK_INTERVAL_SECS: f32 = 4.5;
+const BLINK_RANGE_MIN: f32 = 120.0;
+const BLINK_RANGE_MAX: f32 = 220.0;
+
+const SUMMON_INTERVAL_SECS: f32 = 8.0;
+const SUMMON_MINIONS_PER_CALL: usize = 2;
+const SUMMON_MAX_MINIONS: usize = 4;
+const SUMMON_SPAWN_OFFSET: f32 = 40.0;
+
+const MIRROR_REFLECT_CHANCE: f64 = 0.35;
+const MIRROR_REFLECT_SPEED: f32 = 320.0;
+
+const NAMEPLATE_FONT_SIZE: f32 = 14.0;
+const NAMEPLATE_OFFSET_Y: f32 = 18.0;
+
+/// A trait rolled onto an elite horror. Each one asks the survivor for a different answer than "hit it more".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EliteAffix {
+    /// Heals whenever a horror dies nearby. Clear the crowd around it last.
+    Vampiric,
+    /// Moves and attacks much faster.
+    Hasted,
+    /// A ward soaks damage and recovers if left alone for a few seconds.
+    Shielded,
+    /// Erupts a moment after death.
+    ExplosiveOnDeath,
+    /// Blinks to a random spot near the survivor every few seconds.
+    Teleporting,
+    /// Sometimes sends projectiles back at the survivor.
+    Mirror,
+    /// Calls crawling torments to its side.
+    Summoner,
+}
+
+impl EliteAffix {
+    pub const ALL: [EliteAffix; 7] = [
+        EliteAffix::Vampiric, EliteAffix::Hasted, EliteAffix::Shielded, EliteAffix::ExplosiveOnDeath,
+        EliteAffix::Teleporting, EliteAffix::Mirror, EliteAffix::Summoner,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EliteAffix::Vampiric => "Vampiric",
+            EliteAffix::Hasted => "Hasted",
+            EliteAffix::Shielded => "Shielded",
+            EliteAffix::ExplosiveOnDeath => "Explosive",
+            EliteAffix::Teleporting => "Teleporting",
+            EliteAffix::Mirror => "Mirror",
+            EliteAffix::Summoner => "Summoner",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            EliteAffix::Vampiric => Color::rgb(0.8, 0.1, 0.2),
+            EliteAffix::Hasted => Color::rgb(1.0, 0.9, 0.3),
+            EliteAffix::Shielded => Color::rgb(0.4, 0.7, 1.0),
+            EliteAffix::ExplosiveOnDeath => Color::rgb(1.0, 0.5, 0.1),
+            EliteAffix::Teleporting => Color::rgb(0.7, 0.4, 1.0),
+            EliteAffix::Mirror => Color::rgb(0.85, 0.95, 1.0),
+            EliteAffix::Summoner => Color::rgb(0.4, 0.9, 0.4),
+        }
+    }
+
+    /// Affixes that would duplicate or fight a horror's own behavior are never rolled onto it.
+    fn suits(&self, horror_type: HorrorType) -> bool {
+        match self {
+            EliteAffix::Teleporting => horror_type != HorrorType::VoidBlinker,
+            EliteAffix::Summoner => !matches!(horror_type, HorrorType::FleshWeaver | HorrorType::BroodSac),
+            EliteAffix::ExplosiveOnDeath => horror_type != HorrorType::BloatedMartyr,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct EliteAffixes { pub affixes: Vec<EliteAffix>, pub death_triggered: bool, }
+impl EliteAffixes {
+    pub fn has(&self, affix: EliteAffix) -> bool { self.affixes.contains(&affix) }
+}
+
+/// Damage soaked before the elite's own health is touched. Refills after `recharge_timer` runs out undisturbed.
+#[derive(Component, Debug)]
+pub struct AffixWard { pub amount: i32, pub max_amount: i32, pub recharge_timer: Timer, }
+
+/// Teleporting affix. Kept apart from `VoidBlinkerBehavior` so the elite keeps its own movement between blinks.
+#[derive(Component, Debug)]
+pub struct AffixBlink { pub timer: Timer, }
+
+/// Summoner affix. Kept apart from `FleshWeaverBehavior` so the elite doesn't take on the weaver's kiting.
+#[derive(Component, Debug)]
+pub struct AffixSummoner { pub timer: Timer, pub minions: Vec<Entity>, }
+
+#[derive(Component)] struct AffixWardHalo;
+#[derive(Component)] struct EliteNameplate;
+
+pub struct EliteAffixesPlugin;
+
+impl Plugin for EliteAffixesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+                affix_ward_system,
+                explosive_affix_death_system,
+            ).chain().before(brood_sac_split_system).run_if(in_state(AppState::InGame)))
+            .add_systems(Update, (
+                vampiric_affix_feed_system,
+                affix_ward_halo_system,
+                blink_affix_system,
+                summoner_affix_system,
+            ).run_if(in_state(AppState::InGame)));
+    }
+}
+
+pub fn roll_elite_affixes(horror_type: HorrorType, rng: &mut impl Rng) -> Vec<EliteAffix> {
+    let count = AFFIX_COUNT_WEIGHTS.choose_weighted(rng, |(_, weight)| *weight).map_or(1, |(count, _)| *count);
+    let candidates: Vec<EliteAffix> = EliteAffix::ALL.into_iter().filter(|affix| affix.suits(horror_type)).collect();
+    candidates.choose_multiple(rng, count).copied().collect()
+}
+
+/// "[Hasted, Vampiric] Floating Eyeball"
+pub fn elite_display_name(affixes: &[EliteAffix], horror_type: HorrorType) -> String {
+    let names: Vec<&str> = affixes.iter().map(|affix| affix.name()).collect();
+    format!("[{}] {}", names.join(", "), horror_type.display_name())
+}
+
+/// Gives a freshly spawned elite the components its affixes rely on, plus its halo and nameplate.
+pub fn attach_elite_affixes(entity_commands: &mut bevy::ecs::system::EntityCommands, asset_server: &Res<AssetServer>, affixes: Vec<EliteAffix>, horror_type: HorrorType, max_health: i32, size: Vec2) {
+    for affix in &affixes {
+        match affix {
+            EliteAffix::Teleporting => { entity_commands.insert(AffixBlink { timer: Timer::from_seconds(BLINK_INTERVAL_SECS, TimerMode::Repeating) }); }
+            EliteAffix::Summoner => { entity_commands.insert(AffixSummoner { timer: Timer::from_seconds(SUMMON_INTERVAL_SECS, TimerMode::Repeating), minions: Vec::new() }); }
+            EliteAffix::Shielded => {
+                let ward = ((max_health as f32 * WARD_HEALTH_FRACTION).ceil() as i32).max(1);
+                entity_commands.insert(AffixWard { amount: ward, max_amount: ward, recharge_timer: Timer::from_seconds(WARD_RECHARGE_DELAY_SECS, TimerMode::Once) });
+            }
+            _ => {}
+        }
+    }
+    let display_name = elite_display_name(&affixes, horror_type);
+    let has_ward = affixes.contains(&EliteAffix::Shielded);
+    entity_commands.with_children(|parent| {
+        if has_ward {
+            parent.spawn((
+                SpriteBundle {
+                    texture: asset_server.load("sprites/aura_effect.png"),
+                    sprite: Sprite { custom_size: Some(size * WARD_HALO_SCALE), color: EliteAffix::Shielded.color().with_a(0.35), ..default() },
+                    transform: Transform::from_xyz(0.0, 0.0, -0.05),
+                    ..default()
+                },
+                AffixWardHalo,
+                Name::new("AffixWardHalo"),
+            ));
+        }
+        parent.spawn((
+            Text2dBundle {
+                text: Text::from_section(display_name.clone(), TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: NAMEPLATE_FONT_SIZE, color: affixes.first().map_or(Color::WHITE, |affix| affix.color()) }),
+                transform: Transform::from_xyz(0.0, size.y / 2.0 + NAMEPLATE_OFFSET_Y, 0.5),
+                ..default()
+            },
+            UprightAnchor { offset: Vec3::new(0.0, size.y / 2.0 + NAMEPLATE_OFFSET_Y, 0.5) },
+            EliteNameplate,
+        ));
+    });
+    entity_commands.insert((EliteAffixes { affixes, death_triggered: false }, Name::new(display_name)));
+}
+
+/// Rolls the mirror affix against an incoming projectile. On success a horror projectile is sent back the way it came.
+pub fn try_mirror_reflect(commands: &mut Commands, asset_server: &Res<AssetServer>, affixes: Option<&EliteAffixes>, horror_data: &Horror, horror_position: Vec3, incoming_velocity: Vec2) -> bool {
+    if !affixes.is_some_and(|affixes| affixes.has(EliteAffix::Mirror)) || !rand::thread_rng().gen_bool(MIRROR_REFLECT_CHANCE) { return false; }
+    let direction = -incoming_velocity.normalize_or_zero();
+    if direction == Vec2::ZERO { return false; }
+    let damage = (horror_data.damage_on_collision / 2).max(1);
+    spawn_horror_projectile(commands, asset_server, horror_position, direction, MIRROR_REFLECT_SPEED, damage, horror_data.horror_type);
+    true
+}
+
+/// Deals `amount` to a horror, letting its ward soak what it can first. Returns how much reached `Health`, which is
+/// what damage sites should report.
+pub fn apply_horror_damage(health: &mut Health, ward: Option<&mut AffixWard>, amount: i32) -> i32 {
+    let absorbed = ward.map_or(0, |ward| {
+        ward.recharge_timer.reset();
+        let absorbed = amount.clamp(0, ward.amount);
+        ward.amount -= absorbed;
+        absorbed
+    });
+    let dealt = amount - absorbed;
+    health.0 -= dealt;
+    dealt
+}
+
+/// A horror as the survivor's projectiles meet it: what soaks the hit, and what can turn the projectile away first.
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct HorrorHitTarget {
+    pub entity: Entity,
+    pub transform: &'static GlobalTransform,
+    pub health: &'static mut Health,
+    pub ward: Option<&'static mut AffixWard>,
+    pub horror: &'static Horror,
+    pub warden: Option<&'static CarapaceWardenBehavior>,
+    pub affixes: Option<&'static EliteAffixes>,
+}
+
+/// Refills wards that have gone a while without being hit.
+fn affix_ward_system(time: Res<Time>, mut ward_query: Query<&mut AffixWard>) {
+    for mut ward in ward_query.iter_mut() {
+        ward.recharge_timer.tick(time.delta());
+        if ward.recharge_timer.finished() { ward.amount = ward.max_amount; }
+    }
+}
+
+fn affix_ward_halo_system(ward_query: Query<(&AffixWard, &Children)>, mut halo_query: Query<&mut Sprite, With<AffixWardHalo>>) {
+    for (ward, children) in ward_query.iter() {
+        for &child in children.iter() {
+            if let Ok(mut sprite) = halo_query.get_mut(child) { sprite.color.set_a(0.05 + 0.35 * ward.amount as f32 / ward.max_amount.max(1) as f32); }
+        }
+    }
+}
+
+fn explosive_affix_death_system(mut commands: Commands, asset_server: Res<AssetServer>, mut elite_query: Query<(&Transform, &Health, &Horror, &mut EliteAffixes), Changed<Health>>) {
+    for (transform, health, horror_data, mut affixes) in elite_query.iter_mut() {
+        if health.0 > 0 || affixes.death_triggered { continue; }
+        affixes.death_triggered = true;
+        if !affixes.has(EliteAffix::ExplosiveOnDeath) { continue; }
+        let damage = (horror_data.damage_on_collision as f32 * EXPLOSIVE_DAMAGE_MULTIPLIER).round() as i32;
+        spawn_horror_ground_telegraph(&mut commands, &asset_server, transform.translation.truncate(), EXPLOSIVE_RADIUS, EXPLOSIVE_DELAY_SECS, damage, horror_data.horror_type);
+    }
+}
+
+fn vampiric_affix_feed_system(mut kill_events: EventReader<HorrorKilledEvent>, mut elite_query: Query<(&Transform, &mut Health, &Horror, &EliteAffixes)>) {
+    for event in kill_events.read() {
+        for (transform, mut health, horror_data, affixes) in elite_query.iter_mut() {
+            if !affixes.has(EliteAffix::Vampiric) || health.0 <= 0 || health.0 >= horror_data.max_health { continue; }
+            if transform.translation.truncate().distance(event.position.truncate()) > VAMPIRIC_FEED_RADIUS { continue; }
+            let heal = ((horror_data.max_health as f32 * VAMPIRIC_HEAL_FRACTION).ceil() as i32).max(1);
+            health.0 = (health.0 + heal).min(horror_data.max_health);
+        }
+    }
+}
+
+fn blink_affix_system(time: Res<Time>, mut blink_query: Query<(&mut Transform, &mut AffixBlink), Without<Survivor>>, survivor_query: Query<&Transform, With<Survivor>>) {
+    let Ok(survivor_transform) = survivor_query.get_single() else { return; };
+    let survivor_pos = survivor_transform.translation.truncate();
+    let mut rng = rand::thread_rng();
+    for (mut transform, mut blink) in blink_query.iter_mut() {
+        if !blink.timer.tick(time.delta()).just_finished() { continue; }
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let destination = survivor_pos + Vec2::from_angle(angle) * rng.gen_range(BLINK_RANGE_MIN..BLINK_RANGE_MAX);
+        transform.translation = destination.extend(transform.translation.z);
+    }
+}
+
+fn summoner_affix_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    game_state: Res<GameState>,
+    mut summoner_query: Query<(&Transform, &mut AffixSummoner)>,
+    horror_query: Query<(), With<Horror>>,
+) {
+    let mut rng = rand::thread_rng();
+    for (transform, mut summoner) in summoner_query.iter_mut() {
+        summoner.minions.retain(|minion| horror_query.contains(*minion));
+        if !summoner.timer.tick(time.delta()).just_finished() { continue; }
+        let to_spawn = SUMMON_MINIONS_PER_CALL.min(SUMMON_MAX_MINIONS.saturating_sub(summoner.minions.len()));
+        for _ in 0..to_spawn {
+            let offset = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU)) * SUMMON_SPAWN_OFFSET;
+            let position = (transform.translation.truncate() + offset).extend(transform.translation.z);
+            summoner.minions.push(spawn_and_return_horror_entity(&mut commands, &asset_server, HorrorType::CrawlingTorment, position, game_state.current_difficulty_multiplier));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn rolls_between_one_and_three_distinct_affixes() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..200 {
+            let affixes = roll_elite_affixes(HorrorType::SkitteringShadowling, &mut rng);
+            assert!((1..=3).contains(&affixes.len()));
+            for (index, affix) in affixes.iter().enumerate() { assert!(!affixes[index + 1..].contains(affix)); }
+        }
+    }
+
+    #[test]
+    fn never_rolls_affixes_that_clash_with_the_horror() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..200 {
+            assert!(!roll_elite_affixes(HorrorType::VoidBlinker, &mut rng).contains(&EliteAffix::Teleporting));
+            assert!(!roll_elite_affixes(HorrorType::FleshWeaver, &mut rng).contains(&EliteAffix::Summoner));
+            assert!(!roll_elite_affixes(HorrorType::BroodSac, &mut rng).contains(&EliteAffix::Summoner));
+            assert!(!roll_elite_affixes(HorrorType::BloatedMartyr, &mut rng).contains(&EliteAffix::ExplosiveOnDeath));
+        }
+    }
+
+    #[test]
+    fn every_affix_can_be_rolled() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut seen = Vec::new();
+        for _ in 0..500 { seen.extend(roll_elite_affixes(HorrorType::SkitteringShadowling, &mut rng)); }
+        for affix in EliteAffix::ALL { assert!(seen.contains(&affix), "{:?} never rolled", affix); }
+    }
+
+    #[test]
+    fn ward_soaks_damage_before_health() {
+        let mut health = Health(100);
+        let mut ward = AffixWard { amount: 30, max_amount: 30, recharge_timer: Timer::from_seconds(1.0, TimerMode::Once) };
+        assert_eq!(apply_horror_damage(&mut health, Some(&mut ward), 20), 0);
+        assert_eq!((health.0, ward.amount), (100, 10));
+        assert_eq!(apply_horror_damage(&mut health, Some(&mut ward), 150), 140);
+        assert_eq!((health.0, ward.amount), (-40, 0));
+        assert_eq!(apply_horror_damage(&mut health, None, 5), 5);
+        assert_eq!(health.0, -45);
+    }
+}
@@ -1,5 +1,6 @@
 // src/game.rs
 use bevy::prelude::*;
+use bevy::ecs::schedule::ScheduleLabel;
 use rand::seq::SliceRandom;
 use crate::{
     horror::{HorrorSpawnTimer, MaxHorrors},
@@ -13,6 +14,11 @@ use crate::{
     items::{ItemId, ItemLibrary, AutomaticWeaponId, AutomaticWeaponLibrary},
     skills::{ActiveSkillInstance, SkillLibrary as SkillsSkillLibrary}, // Removed SkillDefinition
     automatic_projectiles::AutomaticProjectile, // This should be pub in automatic_projectiles.rs
+    run_statistics::{RunStatistics, spawn_run_statistics_panel},
+    achievements::ContentLocks,
+    omens::RunModifiers,
+    xp_curve::XpCurve,
+    dodge::SurvivorDodge,
 };
 
 pub const SCREEN_WIDTH: f32 = 1280.0;
@@ -29,12 +35,24 @@ const MIN_SPAWN_INTERVAL_SECONDS: f32 = 0.3;
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub enum AppState {
     #[default] MainMenu,
+    CharacterSelect,
+    OmenSelect,
     InGame,
     LevelUp,
+    ChestReveal,
+    Shop,
     GameOver,
     DebugUpgradeMenu,
 }
 
+/// Runs once when a run is over, on entering `GameOver` or `MainMenu`. Leaving `InGame` for a pause-like
+/// state must keep the world intact, and `NextState` is already taken by the time `OnExit` runs, so session
+/// cleanup lives here instead of behind an `OnExit(InGame)` condition.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OnSessionEnd;
+
+fn run_session_end_schedule(world: &mut World) { let _ = world.try_run_schedule(OnSessionEnd); }
+
 #[derive(Resource, Default)]
 struct PreviousGameState(Option<AppState>);
 
@@ -46,6 +64,7 @@ pub struct GamePlugin;
 #[derive(Resource, Default)]
 pub struct GameState {
     pub score: u32,
+    pub gold: u32,
     pub cycle_number: u32,
     pub horror_count: u32,
     pub game_over_timer: Timer,
@@ -67,6 +86,8 @@ pub struct GameState {
 #[derive(Component)] struct ScoreText;
 #[derive(Component)] struct TimerText;
 #[derive(Component)] struct CycleText;
+#[derive(Component)] struct GoldText;
+#[derive(Component)] struct DodgeText;
 
 
 fn reset_for_new_game_session(
@@ -75,6 +96,7 @@ fn reset_for_new_game_session(
     mut max_horrors: ResMut<MaxHorrors>,
 ) {
     game_state.score = 0;
+    game_state.gold = 0;
     game_state.cycle_number = 1;
     game_state.horror_count = 0;
     game_state.game_timer = Timer::from_seconds(3600.0, TimerMode::Once);
@@ -110,13 +132,15 @@ impl Plugin for GamePlugin {
             .insert_resource(HorrorSpawnTimer {timer: Timer::from_seconds(INITIAL_SPAWN_INTERVAL_SECONDS, TimerMode::Repeating)})
             .insert_resource(MaxHorrors(INITIAL_MAX_HORRORS)) .add_plugins(EchoingSoulPlugin)
 
-            .add_systems(OnEnter(AppState::MainMenu), setup_main_menu_ui)
+            .add_systems(OnEnter(AppState::MainMenu), (run_session_end_schedule, setup_main_menu_ui))
             .add_systems(Update, main_menu_input_system.run_if(in_state(AppState::MainMenu)))
             .add_systems(OnExit(AppState::MainMenu), despawn_ui_by_marker::<MainMenuUI>)
 
             .add_systems(OnEnter(AppState::InGame), (on_enter_ingame_state_actions, setup_ingame_ui,))
             .add_systems(Update, (
                 update_ingame_ui,
+                update_gold_text,
+                update_dodge_text,
                 update_game_timer,
                 difficulty_scaling_system,
                 global_key_listener,
@@ -129,10 +153,15 @@ impl Plugin for GamePlugin {
             .add_systems(Update, apply_chosen_upgrade.run_if(on_event::<UpgradeChosenEvent>()))
             .add_systems(OnExit(AppState::LevelUp), (despawn_ui_by_marker::<LevelUpUI>, on_enter_ingame_state_actions))
 
+            .add_systems(OnEnter(AppState::ChestReveal), on_enter_pause_like_state_actions)
+            .add_systems(OnExit(AppState::ChestReveal), on_enter_ingame_state_actions)
+            .add_systems(OnEnter(AppState::Shop), on_enter_pause_like_state_actions)
+            .add_systems(OnExit(AppState::Shop), on_enter_ingame_state_actions)
+
             .add_systems(OnEnter(AppState::DebugUpgradeMenu), (on_enter_pause_like_state_actions, log_entering_debug_menu_state))
             .add_systems(OnExit(AppState::DebugUpgradeMenu), (on_enter_ingame_state_actions, log_exiting_debug_menu_state));
 
-            app.add_systems(OnEnter(AppState::GameOver), setup_game_over_ui)
+            app.add_systems(OnEnter(AppState::GameOver), (run_session_end_schedule, setup_game_over_ui))
             .add_systems(Update, game_over_input_system.run_if(in_state(AppState::GameOver)))
             .add_systems(OnExit(AppState::GameOver), despawn_ui_by_marker::<GameOverUI>);
     }
@@ -196,8 +225,8 @@ fn debug_weapon_switch_system(
 
 fn despawn_ui_by_marker<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) { for entity in query.iter() { commands.entity(entity).despawn_recursive(); } }
 fn setup_main_menu_ui(mut commands: Commands, asset_server: Res<AssetServer>) { commands.spawn(( NodeBundle { style: Style { width: Val::Percent(100.0), height: Val::Percent(100.0), justify_content: JustifyContent::Center, align_items: AlignItems::Center, flex_direction: FlexDirection::Column, row_gap: Val::Px(20.0), ..default() }, ..default() }, MainMenuUI, )).with_children(|parent| { parent.spawn( TextBundle::from_section( "Eldritch Hero", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 70.0, color: Color::WHITE, }, ).with_text_justify(JustifyText::Center) ); parent.spawn( TextBundle::from_section( "Embrace the Madness (SPACE)", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 40.0, color: Color::rgba(0.8, 0.8, 0.8, 1.0), }, ).with_text_justify(JustifyText::Center) ); }); }
-fn main_menu_input_system(mut commands: Commands, keyboard_input: Res<ButtonInput<KeyCode>>, mut next_app_state: ResMut<NextState<AppState>>, game_state: ResMut<GameState>, horror_spawn_timer: ResMut<HorrorSpawnTimer>, max_horrors: ResMut<MaxHorrors>, player_entity_query: Query<Entity, With<Survivor>>,) { if keyboard_input.just_pressed(KeyCode::Space) { for entity in player_entity_query.iter() { commands.entity(entity).despawn_recursive(); } reset_for_new_game_session(game_state, horror_spawn_timer, max_horrors); next_app_state.set(AppState::InGame); } }
-fn setup_ingame_ui(mut commands: Commands, asset_server: Res<AssetServer>) { commands.spawn(( NodeBundle { style: Style { width: Val::Percent(100.0), height: Val::Percent(100.0), flex_direction: FlexDirection::Column, justify_content: JustifyContent::SpaceBetween, padding: UiRect::all(Val::Px(10.0)), position_type: PositionType::Absolute, ..default() }, z_index: ZIndex::Global(1), ..default() }, InGameUI, )).with_children(|parent| { parent.spawn(NodeBundle { style: Style { width: Val::Percent(100.0), justify_content: JustifyContent::SpaceAround, align_items: AlignItems::Center, padding: UiRect::all(Val::Px(5.0)), ..default() }, background_color: Color::rgba(0.0, 0.0, 0.0, 0.3).into(), ..default() }).with_children(|top_bar| { top_bar.spawn((TextBundle::from_section( "Endurance: 100", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 20.0, color: Color::GREEN, }, ), EnduranceText)); top_bar.spawn((TextBundle::from_section( "Insight: 1", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 20.0, color: Color::CYAN, }, ), InsightText)); top_bar.spawn((TextBundle::from_section( "Echoes: 0/100", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 20.0, color: Color::YELLOW, }, ), EchoesText)); top_bar.spawn((TextBundle::from_section( "Cycle: 1", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 20.0, color: Color::ORANGE_RED, }, ), CycleText)); }); parent.spawn(NodeBundle { style: Style { width: Val::Percent(100.0), justify_content: JustifyContent::SpaceBetween, align_items: AlignItems::FlexEnd, padding: UiRect::all(Val::Px(5.0)), ..default() }, ..default() }).with_children(|bottom_bar| { bottom_bar.spawn((TextBundle::from_section( "Score: 0", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 20.0, color: Color::WHITE, }, ), ScoreText)); bottom_bar.spawn((TextBundle::from_section( "Time: 00:00", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 20.0, color: Color::WHITE, }, ), TimerText)); }); }); }
+fn main_menu_input_system(mut commands: Commands, keyboard_input: Res<ButtonInput<KeyCode>>, mut next_app_state: ResMut<NextState<AppState>>, game_state: ResMut<GameState>, horror_spawn_timer: ResMut<HorrorSpawnTimer>, max_horrors: ResMut<MaxHorrors>, player_entity_query: Query<Entity, With<Survivor>>,) { if keyboard_input.just_pressed(KeyCode::Space) { for entity in player_entity_query.iter() { commands.entity(entity).despawn_recursive(); } reset_for_new_game_session(game_state, horror_spawn_timer, max_horrors); next_app_state.set(AppState::CharacterSelect); } }
+fn setup_ingame_ui(mut commands: Commands, asset_server: Res<AssetServer>) { commands.spawn(( NodeBundle { style: Style { width: Val::Percent(100.0), height: Val::Percent(100.0), flex_direction: FlexDirection::Column, justify_content: JustifyContent::SpaceBetween, padding: UiRect::all(Val::Px(10.0)), position_type: PositionType::Absolute, ..default() }, z_index: ZIndex::Global(1), ..default() }, InGameUI, )).with_children(|parent| { parent.spawn(NodeBundle { style: Style { width: Val::Percent(100.0), justify_content: JustifyContent::SpaceAround, align_items: AlignItems::Center, padding: UiRect::all(Val::Px(5.0)), ..default() }, background_color: Color::rgba(0.0, 0.0, 0.0, 0.3).into(), ..default() }).with_children(|top_bar| { top_bar.spawn((TextBundle::from_section( "Endurance: 100", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 20.0, color: Color::GREEN, }, ), EnduranceText)); top_bar.spawn((TextBundle::from_section( "Insight: 1", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 20.0, color: Color::CYAN, }, ), InsightText)); top_bar.spawn((TextBundle::from_section( "Echoes: 0/100", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 20.0, color: Color::YELLOW, }, ), EchoesText)); top_bar.spawn((TextBundle::from_section( "Cycle: 1", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 20.0, color: Color::ORANGE_RED, }, ), CycleText)); }); parent.spawn(NodeBundle { style: Style { width: Val::Percent(100.0), justify_content: JustifyContent::SpaceBetween, align_items: AlignItems::FlexEnd, padding: UiRect::all(Val::Px(5.0)), ..default() }, ..default() }).with_children(|bottom_bar| { bottom_bar.spawn((TextBundle::from_section( "Score: 0", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 20.0, color: Color::WHITE, }, ), ScoreText)); bottom_bar.spawn((TextBundle::from_section( "Gold: 0", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 20.0, color: Color::GOLD, }, ), GoldText)); bottom_bar.spawn((TextBundle::from_section( "Dodge: 1/1", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 20.0, color: Color::rgb(0.7, 0.5, 1.0), }, ), DodgeText)); bottom_bar.spawn((TextBundle::from_section( "Time: 00:00", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 20.0, color: Color::WHITE, }, ), TimerText)); }); }); }
 fn update_game_timer(mut game_state: ResMut<GameState>, time: Res<Time>) { if !game_state.game_timer.paused() { game_state.game_timer.tick(time.delta()); } }
 
 fn difficulty_scaling_system(
@@ -206,9 +235,10 @@ fn difficulty_scaling_system(
     mut horror_spawn_timer: ResMut<HorrorSpawnTimer>,
     mut max_horrors: ResMut<MaxHorrors>,
     player_query: Query<&Survivor>,
+    run_modifiers: Res<RunModifiers>,
 ) {
     if game_state.difficulty_timer.paused() { return; }
-    game_state.difficulty_timer.tick(time.delta());
+    game_state.difficulty_timer.tick(time.delta().mul_f32(run_modifiers.cycle_speed_multiplier));
 
     let player_level = if let Ok(player) = player_query.get_single() {
         player.level
@@ -242,13 +272,16 @@ fn difficulty_scaling_system(
     }
 }
 
-fn update_ingame_ui(player_query: Query<(&Survivor, &Health)>, game_state: Res<GameState>, mut ui_texts: ParamSet< ( Query<&mut Text, With<EnduranceText>>, Query<&mut Text, With<InsightText>>, Query<&mut Text, With<EchoesText>>, Query<&mut Text, With<ScoreText>>, Query<&mut Text, With<TimerText>>, Query<&mut Text, With<CycleText>>, )>,) { if let Ok((player_stats, player_health)) = player_query.get_single() { if let Ok(mut text) = ui_texts.p0().get_single_mut() { text.sections[0].value = format!("Endurance: {}/{}", player_health.0, player_stats.max_health); if player_health.0 < player_stats.max_health / 3 { text.sections[0].style.color = Color::RED; } else if player_health.0 < player_stats.max_health * 2 / 3 { text.sections[0].style.color = Color::YELLOW; } else { text.sections[0].style.color = Color::GREEN; } } if let Ok(mut text) = ui_texts.p1().get_single_mut() { text.sections[0].value = format!("Insight: {}", player_stats.level); } if let Ok(mut text) = ui_texts.p2().get_single_mut() { text.sections[0].value = format!("Echoes: {}/{}", player_stats.current_level_xp, player_stats.experience_to_next_level()); } } else { if let Ok(mut text) = ui_texts.p0().get_single_mut() { text.sections[0].value = "Endurance: --/--".to_string(); } if let Ok(mut text) = ui_texts.p1().get_single_mut() { text.sections[0].value = "Insight: --".to_string(); } if let Ok(mut text) = ui_texts.p2().get_single_mut() { text.sections[0].value = "Echoes: --/--".to_string(); } } if let Ok(mut text) = ui_texts.p3().get_single_mut() { text.sections[0].value = format!("Score: {}", game_state.score); } if let Ok(mut text) = ui_texts.p4().get_single_mut() { let elapsed_seconds = game_state.game_timer.elapsed().as_secs(); let minutes = elapsed_seconds / 60; let seconds = elapsed_seconds % 60; text.sections[0].value = format!("Time: {:02}:{:02}", minutes, seconds); } if let Ok(mut text) = ui_texts.p5().get_single_mut() { text.sections[0].value = format!("Cycle: {}", game_state.cycle_number); } }
+fn update_ingame_ui(player_query: Query<(&Survivor, &Health)>, game_state: Res<GameState>, xp_curve: Res<XpCurve>, mut ui_texts: ParamSet< ( Query<&mut Text, With<EnduranceText>>, Query<&mut Text, With<InsightText>>, Query<&mut Text, With<EchoesText>>, Query<&mut Text, With<ScoreText>>, Query<&mut Text, With<TimerText>>, Query<&mut Text, With<CycleText>>, )>,) { if let Ok((player_stats, player_health)) = player_query.get_single() { if let Ok(mut text) = ui_texts.p0().get_single_mut() { text.sections[0].value = format!("Endurance: {}/{}", player_health.0, player_stats.max_health); if player_health.0 < player_stats.max_health / 3 { text.sections[0].style.color = Color::RED; } else if player_health.0 < player_stats.max_health * 2 / 3 { text.sections[0].style.color = Color::YELLOW; } else { text.sections[0].style.color = Color::GREEN; } } if let Ok(mut text) = ui_texts.p1().get_single_mut() { text.sections[0].value = format!("Insight: {}", player_stats.level); } if let Ok(mut text) = ui_texts.p2().get_single_mut() { text.sections[0].value = format!("Echoes: {}/{}", player_stats.current_level_xp, player_stats.experience_to_next_level(&xp_curve)); } } else { if let Ok(mut text) = ui_texts.p0().get_single_mut() { text.sections[0].value = "Endurance: --/--".to_string(); } if let Ok(mut text) = ui_texts.p1().get_single_mut() { text.sections[0].value = "Insight: --".to_string(); } if let Ok(mut text) = ui_texts.p2().get_single_mut() { text.sections[0].value = "Echoes: --/--".to_string(); } } if let Ok(mut text) = ui_texts.p3().get_single_mut() { text.sections[0].value = format!("Score: {}", game_state.score); } if let Ok(mut text) = ui_texts.p4().get_single_mut() { let elapsed_seconds = game_state.game_timer.elapsed().as_secs(); let minutes = elapsed_seconds / 60; let seconds = elapsed_seconds % 60; text.sections[0].value = format!("Time: {:02}:{:02}", minutes, seconds); } if let Ok(mut text) = ui_texts.p5().get_single_mut() { text.sections[0].value = format!("Cycle: {}", game_state.cycle_number); } }
+fn update_gold_text(game_state: Res<GameState>, mut gold_text_query: Query<&mut Text, With<GoldText>>) { if let Ok(mut text) = gold_text_query.get_single_mut() { text.sections[0].value = format!("Gold: {}", game_state.gold); } }
+fn update_dodge_text(dodge_query: Query<&SurvivorDodge>, mut dodge_text_query: Query<&mut Text, With<DodgeText>>) { if let Ok(mut text) = dodge_text_query.get_single_mut() { text.sections[0].value = dodge_query.get_single().map_or_else(|_| "Dodge: -/-".to_string(), |dodge| format!("Dodge: {}/{}", dodge.charges, dodge.max_charges)); } }
 
 fn setup_level_up_ui(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     player_query: Query<(&Survivor, Option<&CircleOfWarding>, Option<&SwarmOfNightmares>)>,
     upgrade_pool: Res<UpgradePool>,
+    run_modifiers: Res<RunModifiers>,
 ) {
     let (player_stats, opt_circle_aura, opt_nightmare_swarm) = if let Ok(query_result) = player_query.get_single() {
         query_result
@@ -256,19 +289,22 @@ fn setup_level_up_ui(
         return;
     };
 
-    let player_level = player_stats.level;
+    // With several level-ups queued, each screen shows the level it stands for.
+    let player_level = player_stats.level - player_stats.pending_level_ups;
+    let title = if player_stats.pending_level_ups > 0 { format!("Revelation! Insight: {} ({} more await)", player_level, player_stats.pending_level_ups) } else { format!("Revelation! Insight: {}", player_level) };
 
     let context = SurvivorUpgradeContext {
         is_circle_of_warding_active: opt_circle_aura.map_or(false, |aura| aura.is_active),
         is_swarm_of_nightmares_active: opt_nightmare_swarm.map_or(false, |swarm| swarm.is_active),
         equipped_skills: &player_stats.equipped_skills,
         current_weapon_id: player_stats.equipped_weapon_id,
+        choice_limit: run_modifiers.upgrade_choice_limit,
     };
 
     let current_offered_upgrades = OfferedUpgrades { choices: upgrade_pool.get_random_upgrades(3, &context) };
 
     commands.spawn(( NodeBundle { style: Style { width: Val::Percent(100.0), height: Val::Percent(100.0), position_type: PositionType::Absolute, justify_content: JustifyContent::Center, align_items: AlignItems::Center, flex_direction: FlexDirection::Column, row_gap: Val::Px(30.0), ..default() }, background_color: Color::rgba(0.1, 0.1, 0.2, 0.9).into(), z_index: ZIndex::Global(10), ..default() }, LevelUpUI, current_offered_upgrades.clone(), )).with_children(|parent| {
-        parent.spawn( TextBundle::from_section( format!("Revelation! Insight: {}", player_level), TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 50.0, color: Color::GOLD, }, ).with_style(Style { margin: UiRect::bottom(Val::Px(20.0)), ..default()}) );
+        parent.spawn( TextBundle::from_section( title, TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 50.0, color: Color::GOLD, }, ).with_style(Style { margin: UiRect::bottom(Val::Px(20.0)), ..default()}) );
         for (index, card) in current_offered_upgrades.choices.iter().enumerate() {
             parent.spawn(( ButtonBundle { style: Style { width: Val::Px(450.0), min_height: Val::Px(100.0), padding: UiRect::all(Val::Px(10.0)), justify_content: JustifyContent::Center, align_items: AlignItems::FlexStart, flex_direction: FlexDirection::Column, border: UiRect::all(Val::Px(2.0)), margin: UiRect::bottom(Val::Px(10.0)), ..default() }, border_color: BorderColor(Color::DARK_GRAY), background_color: Color::GRAY.into(), ..default() }, UpgradeButton(card.clone()), Name::new(format!("Upgrade Button {}", index + 1)), )).with_children(|button_parent| {
                 button_parent.spawn(TextBundle::from_section( &card.name, TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 22.0, color: Color::WHITE, }, ).with_style(Style { margin: UiRect::bottom(Val::Px(5.0)), ..default() }));
@@ -285,13 +321,14 @@ fn handle_upgrade_choice_interaction(mut interaction_query: Query< (&Interaction
 
 fn apply_chosen_upgrade(
     mut events: EventReader<UpgradeChosenEvent>,
-    mut player_query: Query<(&mut Survivor, &mut SanityStrain, &mut Health, &mut CircleOfWarding, &mut SwarmOfNightmares)>,
+    mut player_query: Query<(&mut Survivor, &mut SanityStrain, &mut Health, &mut CircleOfWarding, &mut SwarmOfNightmares, &mut SurvivorDodge)>,
     item_library: Res<ItemLibrary>,
     mut item_collected_writer: EventWriter<ItemCollectedEvent>,
     skill_library: Res<SkillsSkillLibrary>,
+    content_locks: Res<ContentLocks>,
 ) {
     for event in events.read() {
-        let Ok((mut player_stats, mut sanity_strain, mut health_stats, mut circle_aura, mut nightmare_swarm)) = player_query.get_single_mut() else { continue; };
+        let Ok((mut player_stats, mut sanity_strain, mut health_stats, mut circle_aura, mut nightmare_swarm, mut dodge)) = player_query.get_single_mut() else { continue; };
         match &event.0.upgrade_type {
             UpgradeType::SurvivorSpeed(percentage) => { player_stats.speed *= 1.0 + (*percentage as f32 / 100.0); }
             UpgradeType::MaxEndurance(amount) => { player_stats.max_health += *amount; health_stats.0 += *amount; health_stats.0 = health_stats.0.min(player_stats.max_health); }
@@ -314,6 +351,7 @@ fn apply_chosen_upgrade(
             UpgradeType::IncreaseAutoWeaponChainRangePercent(percentage) => {
                 player_stats.auto_weapon_chain_range_multiplier *= 1.0 + (*percentage as f32 / 100.0);
             }
+            UpgradeType::IncreaseAutoWeaponKnockback(amount) => { player_stats.auto_weapon_knockback_bonus += *amount; }
 
             UpgradeType::InscribeCircleOfWarding => { if !circle_aura.is_active { circle_aura.is_active = true; } else { circle_aura.base_damage_per_tick += 1; circle_aura.current_radius *= 1.1; }}
             UpgradeType::IncreaseCircleRadius(percentage) => { if circle_aura.is_active { circle_aura.current_radius *= 1.0 + (*percentage as f32 / 100.0); }}
@@ -325,6 +363,11 @@ fn apply_chosen_upgrade(
             UpgradeType::IncreaseNightmareDamage(damage) => { if nightmare_swarm.is_active { nightmare_swarm.damage_per_hit += *damage; }}
             UpgradeType::IncreaseNightmareRadius(radius_increase) => { if nightmare_swarm.is_active { nightmare_swarm.orbit_radius += *radius_increase; }}
             UpgradeType::IncreaseNightmareRotationSpeed(speed_increase) => { if nightmare_swarm.is_active { nightmare_swarm.rotation_speed += *speed_increase; }}
+            UpgradeType::IncreaseDodgeCharges(amount) => { dodge.add_charges(*amount); }
+            UpgradeType::ReduceDodgeCooldown(percentage) => { dodge.reduce_cooldown(*percentage); }
+            UpgradeType::IncreaseDodgeDamage(amount) => { dodge.damage += *amount; }
+            UpgradeType::IncreaseCritChance(percentage) => { player_stats.crit_chance = (player_stats.crit_chance + *percentage as f32 / 100.0).min(1.0); }
+            UpgradeType::IncreaseCritMultiplier(percentage) => { player_stats.crit_multiplier += *percentage as f32 / 100.0; }
 
             UpgradeType::IncreaseSkillDamage { slot_index, amount } => { if let Some(skill_instance) = player_stats.equipped_skills.get_mut(*slot_index) { skill_instance.flat_damage_bonus += *amount; skill_instance.current_level += 1; } }
             UpgradeType::ReduceSkillCooldown { slot_index, percent_reduction } => { if let Some(skill_instance) = player_stats.equipped_skills.get_mut(*slot_index) { skill_instance.cooldown_multiplier *= 1.0 - percent_reduction; skill_instance.cooldown_multiplier = skill_instance.cooldown_multiplier.max(0.1); skill_instance.current_level +=1; } }
@@ -345,8 +388,11 @@ fn apply_chosen_upgrade(
                     skill_instance.current_level += 1;
                  }
             }
+            UpgradeType::IncreaseSkillKnockback { slot_index, amount } => { if let Some(skill_instance) = player_stats.equipped_skills.get_mut(*slot_index) { skill_instance.knockback_bonus += *amount; skill_instance.current_level += 1; } }
+            UpgradeType::IncreaseSkillDuration { skill_id, secs } => { if let Some(skill_instance) = player_stats.equipped_skills.iter_mut().find(|s| s.definition_id == *skill_id) { skill_instance.duration_bonus_secs += *secs; skill_instance.current_level += 1; } }
+            UpgradeType::IncreaseSkillRange { skill_id, percent_increase } => { if let Some(skill_instance) = player_stats.equipped_skills.iter_mut().find(|s| s.definition_id == *skill_id) { skill_instance.range_multiplier *= 1.0 + *percent_increase; skill_instance.current_level += 1; } }
 
-            UpgradeType::GrantRandomRelic => { if !item_library.items.is_empty() { let mut rng = rand::thread_rng(); if let Some(random_item_def) = item_library.items.choose(&mut rng) { item_collected_writer.send(ItemCollectedEvent(random_item_def.id)); } } }
+            UpgradeType::GrantRandomRelic => { let grantable_items: Vec<_> = item_library.items.iter().filter(|def| !content_locks.items.contains(&def.id)).collect(); if !grantable_items.is_empty() { let mut rng = rand::thread_rng(); if let Some(random_item_def) = grantable_items.choose(&mut rng) { item_collected_writer.send(ItemCollectedEvent(random_item_def.id)); } } }
             UpgradeType::GrantSkill(skill_id_to_grant) => {
                 let already_has_skill = player_stats.equipped_skills.iter().any(|s| s.definition_id == *skill_id_to_grant);
                 if !already_has_skill && player_stats.equipped_skills.len() < MAX_ACTIVE_SKILLS {
@@ -358,9 +404,11 @@ fn apply_chosen_upgrade(
         }
     }
 }
-fn setup_game_over_ui(mut commands: Commands, game_state: Res<GameState>, asset_server: Res<AssetServer>) { commands.spawn(( NodeBundle { style: Style { width: Val::Percent(100.0), height: Val::Percent(100.0), justify_content: JustifyContent::Center, align_items: AlignItems::Center, flex_direction: FlexDirection::Column, row_gap: Val::Px(20.0), ..default() }, ..default() }, GameOverUI, )).with_children(|parent| { parent.spawn( TextBundle::from_section( "Consumed by Madness!", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 80.0, color: Color::RED, }, ).with_text_justify(JustifyText::Center) ); parent.spawn( TextBundle::from_section( format!("Score: {}", game_state.score), TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 50.0, color: Color::WHITE, }, ).with_text_justify(JustifyText::Center) ); parent.spawn( TextBundle::from_section( "Succumb Again? (R)", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 40.0, color: Color::rgba(0.8,0.8,0.8,1.0), }, ).with_text_justify(JustifyText::Center) ); }); }
+fn setup_game_over_ui(mut commands: Commands, game_state: Res<GameState>, asset_server: Res<AssetServer>, run_stats: Res<RunStatistics>, weapon_library: Res<AutomaticWeaponLibrary>, skill_library: Res<SkillsSkillLibrary>, run_modifiers: Res<RunModifiers>,) { let report = run_stats.build_report(&game_state, &weapon_library, &skill_library); let lore_earned = run_modifiers.forbidden_lore_for_run(&game_state, &run_stats); commands.spawn(( NodeBundle { style: Style { width: Val::Percent(100.0), height: Val::Percent(100.0), justify_content: JustifyContent::Center, align_items: AlignItems::Center, flex_direction: FlexDirection::Column, row_gap: Val::Px(20.0), ..default() }, ..default() }, GameOverUI, )).with_children(|parent| { parent.spawn( TextBundle::from_section( "Consumed by Madness!", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 80.0, color: Color::RED, }, ).with_text_justify(JustifyText::Center) ); parent.spawn( TextBundle::from_section( format!("Score: {}", game_state.score), TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 50.0, color: Color::WHITE, }, ).with_text_justify(JustifyText::Center) ); parent.spawn( TextBundle::from_section( format!("Omens x{:.2}  |  Forbidden Lore earned: {}", run_modifiers.reward_multiplier, lore_earned), TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 26.0, color: Color::rgb(0.9, 0.8, 0.5), }, ).with_text_justify(JustifyText::Center) ); spawn_run_statistics_panel(parent, &report, &asset_server); parent.spawn( TextBundle::from_section( "Succumb Again? (R)", TextStyle { font: asset_server.load("fonts/FiraSans-Bold.ttf"), font_size: 40.0, color: Color::rgba(0.8,0.8,0.8,1.0), }, ).with_text_justify(JustifyText::Center) ); }); }
 fn game_over_input_system(mut commands: Commands, keyboard_input: Res<ButtonInput<KeyCode>>, mut next_app_state: ResMut<NextState<AppState>>, game_state: ResMut<GameState>, horror_spawn_timer: ResMut<HorrorSpawnTimer>, max_horrors: ResMut<MaxHorrors>, player_entity_query: Query<Entity, With<Survivor>>,) { if keyboard_input.just_pressed(KeyCode::KeyR) { for entity in player_entity_query.iter() { commands.entity(entity).despawn_recursive(); } reset_for_new_game_session(game_state, horror_spawn_timer, max_horrors); next_app_state.set(AppState::MainMenu); } }
 
+type LingeringSkillEffectFilter = Or<(With<crate::skills::ChanneledBeamEffect>, With<crate::skills::VortexEffect>, With<crate::skills::CompanionSummon>)>;
+
 fn cleanup_session_entities(
     mut commands: Commands,
     projectiles_query: Query<Entity, With<AutomaticProjectile>>,
@@ -368,10 +416,12 @@ fn cleanup_session_entities(
     skill_projectiles_query: Query<Entity, With<crate::skills::SkillProjectile>>,
     skill_aoe_query: Query<Entity, With<crate::skills::ActiveSkillAoEEffect>>,
     chain_visual_query: Query<Entity, With<crate::automatic_projectiles::ChainLightningVisual>>,
+    skill_effect_query: Query<Entity, LingeringSkillEffectFilter>,
 ) {
     for entity in projectiles_query.iter() { commands.entity(entity).despawn_recursive(); }
     for entity in orbs_query.iter() { commands.entity(entity).despawn_recursive(); }
     for entity in skill_projectiles_query.iter() { commands.entity(entity).despawn_recursive(); }
     for entity in skill_aoe_query.iter() { commands.entity(entity).despawn_recursive(); }
     for entity in chain_visual_query.iter() { commands.entity(entity).despawn_recursive(); }
+    for entity in skill_effect_query.iter() { commands.entity(entity).despawn_recursive(); }
 }
\ No newline at end of file
@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+use crate::{
+    game::AppState,
+    components::Health,
+    horror::{Horror, HorrorType},
+    visual_effects::UprightAnchor,
+};
+
+const HEALTH_BAR_HEIGHT: f32 = 4.0;
+const HEALTH_BAR_MIN_WIDTH: f32 = 24.0;
+const HEALTH_BAR_MAX_WIDTH: f32 = 60.0;
+const HEALTH_BAR_OFFSET_Y: f32 = 5.0;
+const HEALTH_BAR_Z: f32 = 0.6;
+const HEALTH_BAR_BACKGROUND_COLOR: Color = Color::rgba(0.05, 0.0, 0.05, 0.8);
+const HEALTH_BAR_FULL_COLOR: Color = Color::rgb(0.3, 0.9, 0.3);
+const HEALTH_BAR_EMPTY_COLOR: Color = Color::rgb(0.9, 0.15, 0.1);
+
+/// Elites and behemoths always carry a health bar; other horrors only once damaged, and only if the player asks for it (H).
+#[derive(Resource, Default, Debug)]
+pub struct HealthBarSettings { pub show_for_damaged_horrors: bool, }
+
+/// Points at the bar entities hanging off a horror. Bars are plain untextured sprites, so they batch into a single draw.
+#[derive(Component, Debug)]
+pub struct HorrorHealthBar { pub root: Entity, pub fill: Entity, pub width: f32, }
+
+#[derive(Component)] struct HealthBarFill;
+
+pub struct HealthBarsPlugin;
+
+impl Plugin for HealthBarsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HealthBarSettings>()
+            // PostUpdate, so horrors despawned this frame are already gone before bars are parented to them.
+            .add_systems(PostUpdate, (
+                toggle_health_bar_settings_system,
+                attach_horror_health_bars_system,
+                detach_horror_health_bars_system,
+                update_horror_health_bars_system,
+            ).chain().before(bevy::transform::TransformSystem::TransformPropagate).run_if(in_state(AppState::InGame)));
+    }
+}
+
+fn toggle_health_bar_settings_system(keyboard_input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<HealthBarSettings>) {
+    if keyboard_input.just_pressed(KeyCode::KeyH) { settings.show_for_damaged_horrors = !settings.show_for_damaged_horrors; }
+}
+
+fn wants_health_bar(horror: &Horror, health: &Health, settings: &HealthBarSettings) -> bool {
+    horror.is_elite || horror.horror_type == HorrorType::FrenziedBehemoth || (settings.show_for_damaged_horrors && health.0 < horror.max_health)
+}
+
+fn attach_horror_health_bars_system(
+    mut commands: Commands,
+    settings: Res<HealthBarSettings>,
+    horror_query: Query<(Entity, &Horror, Ref<Health>), Without<HorrorHealthBar>>,
+) {
+    for (entity, horror, health) in horror_query.iter() {
+        if !(settings.is_changed() || health.is_changed()) || health.0 <= 0 || !wants_health_bar(horror, &health, &settings) { continue; }
+        let width = horror.size.x.clamp(HEALTH_BAR_MIN_WIDTH, HEALTH_BAR_MAX_WIDTH);
+        let offset = Vec3::new(0.0, horror.size.y / 2.0 + HEALTH_BAR_OFFSET_Y, HEALTH_BAR_Z);
+        let fill = commands.spawn((
+            SpriteBundle {
+                sprite: Sprite { custom_size: Some(Vec2::new(width, HEALTH_BAR_HEIGHT)), color: HEALTH_BAR_FULL_COLOR, ..default() },
+                transform: Transform::from_xyz(0.0, 0.0, 0.01),
+                ..default()
+            },
+            HealthBarFill,
+        )).id();
+        let root = commands.spawn((
+            SpriteBundle {
+                sprite: Sprite { custom_size: Some(Vec2::new(width + 2.0, HEALTH_BAR_HEIGHT + 2.0)), color: HEALTH_BAR_BACKGROUND_COLOR, ..default() },
+                transform: Transform::from_translation(offset),
+                ..default()
+            },
+            UprightAnchor { offset },
+            Name::new("HorrorHealthBar"),
+        )).add_child(fill).id();
+        commands.entity(entity).add_child(root).insert(HorrorHealthBar { root, fill, width });
+    }
+}
+
+fn detach_horror_health_bars_system(
+    mut commands: Commands,
+    settings: Res<HealthBarSettings>,
+    horror_query: Query<(Entity, &Horror, &Health, &HorrorHealthBar)>,
+) {
+    if !settings.is_changed() { return; }
+    for (entity, horror, health, bar) in horror_query.iter() {
+        if wants_health_bar(horror, health, &settings) { continue; }
+        commands.entity(bar.root).despawn_recursive();
+        commands.entity(entity).remove::<HorrorHealthBar>();
+    }
+}
+
+fn update_horror_health_bars_system(
+    horror_query: Query<(&Horror, Ref<Health>, Ref<HorrorHealthBar>)>,
+    mut fill_query: Query<(&mut Sprite, &mut Transform), With<HealthBarFill>>,
+) {
+    for (horror, health, bar) in horror_query.iter() {
+        if !health.is_changed() && !bar.is_added() { continue; }
+        let Ok((mut sprite, mut transform)) = fill_query.get_mut(bar.fill) else { continue; };
+        let fraction = (health.0 as f32 / horror.max_health.max(1) as f32).clamp(0.0, 1.0);
+        let fill_width = bar.width * fraction;
+        sprite.custom_size = Some(Vec2::new(fill_width, HEALTH_BAR_HEIGHT));
+        // Anchor the fill to the bar's left edge so it drains towards the left.
+        transform.translation.x = -(bar.width - fill_width) / 2.0;
+        let [full_r, full_g, full_b, _] = HEALTH_BAR_FULL_COLOR.as_rgba_f32();
+        let [empty_r, empty_g, empty_b, _] = HEALTH_BAR_EMPTY_COLOR.as_rgba_f32();
+        sprite.color = Color::rgb(
+            empty_r + (full_r - empty_r) * fraction,
+            empty_g + (full_g - empty_g) * fraction,
+            empty_b + (full_b - empty_b) * fraction,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health_bar_test_world() -> (World, Schedule) {
+        let mut world = World::new();
+        world.init_resource::<ButtonInput<KeyCode>>();
+        world.insert_resource(HealthBarSettings { show_for_damaged_horrors: true });
+        let mut schedule = Schedule::default();
+        schedule.add_systems((
+            toggle_health_bar_settings_system,
+            attach_horror_health_bars_system,
+            detach_horror_health_bars_system,
+            update_horror_health_bars_system,
+        ).chain());
+        (world, schedule)
+    }
+
+    fn spawn_test_horror(world: &mut World, is_elite: bool) -> Entity {
+        let horror = Horror { horror_type: HorrorType::SkitteringShadowling, size: Vec2::splat(30.0), damage_on_collision: 5, speed: 100.0, xp_value: 10, item_drop_chance: 0.0, is_elite, max_health: 100 };
+        world.spawn((horror, Health(100), SpatialBundle::default())).id()
+    }
+
+    #[test]
+    fn damaged_horror_bar_appears_on_damage_and_hides_when_toggled_off() {
+        let (mut world, mut schedule) = health_bar_test_world();
+        let horror = spawn_test_horror(&mut world, false);
+        schedule.run(&mut world);
+        assert!(world.get::<HorrorHealthBar>(horror).is_none());
+
+        world.get_mut::<Health>(horror).unwrap().0 = 40;
+        schedule.run(&mut world);
+        let root = world.get::<HorrorHealthBar>(horror).expect("damaged horror should get a bar").root;
+        assert_eq!(world.get::<Parent>(root).map(Parent::get), Some(horror));
+
+        world.resource_mut::<HealthBarSettings>().show_for_damaged_horrors = false;
+        schedule.run(&mut world);
+        assert!(world.get::<HorrorHealthBar>(horror).is_none());
+        assert!(world.get_entity(root).is_none());
+    }
+
+    #[test]
+    fn elite_bar_despawns_with_its_horror() {
+        let (mut world, mut schedule) = health_bar_test_world();
+        let elite = spawn_test_horror(&mut world, true);
+        schedule.run(&mut world);
+        let bar = world.get::<HorrorHealthBar>(elite).expect("elites always carry a bar");
+        let (root, fill) = (bar.root, bar.fill);
+
+        world.entity_mut(elite).despawn_recursive();
+        assert!(world.get_entity(root).is_none());
+        assert!(world.get_entity(fill).is_none());
+    }
+}
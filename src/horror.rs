@@ -1,15 +1,21 @@
 // src/horror.rs
-use bevy::prelude::*;
+use bevy::{prelude::*, ecs::{query::QueryData, system::SystemParam}};
 use rand::{Rng, seq::SliceRandom};
 use std::time::Duration; 
 use crate::{
     components::{Velocity, Health, Damage, Lifetime},
     survivor::Survivor,
-    game::{AppState, GameState},
-    audio::{PlaySoundEvent, SoundEffect},
-    items::{ItemDrop, ItemLibrary, ITEM_DROP_SIZE, ItemEffect, SurvivorTemporaryBuff, TemporaryHealthRegenBuff},
+    game::{AppState, GameState, OnSessionEnd},
+    audio::{PlaySoundAtEvent, SoundEffect},
+    items::{ItemDrop, ItemDefinition, ItemLibrary, ITEM_DROP_SIZE, ItemEffect, SurvivorTemporaryBuff, TemporaryHealthRegenBuff},
     skills::ActiveShield, 
     echoing_soul::{spawn_echoing_soul, ECHOING_SOUL_VALUE},
+    pathfinding::{FlowField, SEPARATION_STEERING_WEIGHT, update_flow_field_system, resolve_horror_crowd_collisions_system},
+    run_statistics::RunStatistics,
+    achievements::ContentLocks,
+    omens::RunModifiers,
+    elite_affixes::{roll_elite_affixes, attach_elite_affixes, EliteAffix, HASTED_SPEED_MULTIPLIER, HASTED_FIRE_RATE_MULTIPLIER},
+    camera_systems::{CameraShakeEvent, SURVIVOR_HIT_TRAUMA},
 };
 
 
@@ -23,11 +29,16 @@ pub const VOID_BLINKER_SIZE: Vec2 = Vec2::new(30.0, 45.0);
 pub const FLESH_WEAVER_SIZE: Vec2 = Vec2::new(45.0, 45.0);
 pub const CRAWLING_TORMENT_SIZE: Vec2 = Vec2::new(25.0, 25.0);
 pub const FRENZIED_BEHEMOTH_SIZE: Vec2 = Vec2::new(55.0, 50.0);
+pub const BLOATED_MARTYR_SIZE: Vec2 = Vec2::new(40.0, 40.0);
+pub const CARAPACE_WARDEN_SIZE: Vec2 = Vec2::new(50.0, 50.0);
+pub const MENDING_THRALL_SIZE: Vec2 = Vec2::new(35.0, 40.0);
+pub const BROOD_SAC_SIZE: Vec2 = Vec2::new(50.0, 50.0);
 
 const ITEM_DROP_CHANCE: f64 = 0.05;
 const MINION_ITEM_DROP_CHANCE: f64 = 0.01;
 const ELITE_ITEM_DROP_CHANCE_BONUS: f64 = 0.10;
 const ELITE_SPAWN_CHANCE: f64 = 0.05;
+const ELITE_HEALTH_MULTIPLIER: f32 = 1.8;
 
 const REPOSITION_DURATION_SECONDS: f32 = 1.5;
 const REPOSITION_SPEED_MULTIPLIER: f32 = 0.7;
@@ -48,12 +59,69 @@ const CHARGER_CHARGE_SPEED_MULTIPLIER: f32 = 3.5;
 const CHARGER_DETECTION_RANGE: f32 = 400.0;
 const CHARGER_MIN_CHARGE_RANGE: f32 = 100.0;
 
+const MARTYR_PRIME_RANGE: f32 = 70.0;
+const MARTYR_FUSE_SECS: f32 = 0.9;
+const MARTYR_BLAST_RADIUS: f32 = 90.0;
+const MARTYR_BLAST_DAMAGE_MULTIPLIER: f32 = 2.0;
+
+const WARDEN_SHIELD_ARC_DEGREES: f32 = 100.0;
+const WARDEN_TURN_RATE: f32 = 1.8;
+
+const THRALL_HEAL_COOLDOWN_SECS: f32 = 3.0;
+const THRALL_HEAL_RADIUS: f32 = 180.0;
+const THRALL_HEAL_FRACTION: f32 = 0.15;
+
+const BROOD_SAC_SPLIT_COUNT: u32 = 3;
+const ELITE_BROOD_SAC_SPLIT_COUNT: u32 = 5;
+
+const ELITE_MASS_MULTIPLIER: f32 = 2.0;
+const KNOCKBACK_DAMPING: f32 = 8.0;
+const KNOCKBACK_MIN_SPEED: f32 = 5.0;
+
 #[derive(Resource)]
 pub struct MaxHorrors(pub u32);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum HorrorType {
     SkitteringShadowling, FloatingEyeball, AmorphousFleshbeast, VoidBlinker, FleshWeaver, CrawlingTorment, FrenziedBehemoth,
+    BloatedMartyr, CarapaceWarden, MendingThrall, BroodSac,
+}
+
+impl HorrorType {
+    /// Relative weight used when horrors shove each other and when they are knocked back. Heavier types push lighter ones aside.
+    pub fn mass(&self) -> f32 {
+        match self {
+            HorrorType::CrawlingTorment => 0.5,
+            HorrorType::SkitteringShadowling => 1.0,
+            HorrorType::VoidBlinker => 1.0,
+            HorrorType::BloatedMartyr => 1.0,
+            HorrorType::MendingThrall => 1.0,
+            HorrorType::FloatingEyeball => 1.2,
+            HorrorType::FleshWeaver => 2.0,
+            HorrorType::BroodSac => 2.5,
+            HorrorType::CarapaceWarden => 4.0,
+            HorrorType::FrenziedBehemoth => 4.0,
+            HorrorType::AmorphousFleshbeast => 5.0,
+        }
+    }
+
+    /// Bosses are rewarded like elites, whether or not they rolled affixes.
+    pub fn is_boss(&self) -> bool { matches!(self, HorrorType::FrenziedBehemoth) }
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            HorrorType::SkitteringShadowling => "Skittering Shadowling",
+            HorrorType::FloatingEyeball => "Floating Eyeball",
+            HorrorType::AmorphousFleshbeast => "Amorphous Fleshbeast",
+            HorrorType::VoidBlinker => "Void Blinker",
+            HorrorType::FleshWeaver => "Flesh Weaver",
+            HorrorType::CrawlingTorment => "Crawling Torment",
+            HorrorType::FrenziedBehemoth => "Frenzied Behemoth",
+            HorrorType::BloatedMartyr => "Bloated Martyr",
+            HorrorType::CarapaceWarden => "Carapace Warden",
+            HorrorType::MendingThrall => "Mending Thrall",
+            HorrorType::BroodSac => "Brood Sac",
+        }
+    }
 }
 
 pub struct HorrorStats {
@@ -73,6 +141,10 @@ impl HorrorStats {
             HorrorType::FleshWeaver => HorrorStats { horror_type, health: (40.0 * difficulty_multiplier * 1.2).max(1.0) as i32, damage_on_collision: (8.0 * difficulty_multiplier.sqrt()).round() as i32, speed: 60.0 + 10.0 * (difficulty_multiplier - 1.0).max(0.0), size: FLESH_WEAVER_SIZE, sprite_path: "sprites/flesh_weaver_placeholder.png", projectile_range: None, projectile_fire_rate: None, projectile_speed: None, projectile_damage: None, xp_value: ECHOING_SOUL_VALUE + 20, item_drop_chance_override: Some(ITEM_DROP_CHANCE + 0.07), },
             HorrorType::CrawlingTorment => HorrorStats { horror_type, health: (5.0 * difficulty_multiplier).max(1.0) as i32, damage_on_collision: (5.0 * difficulty_multiplier.sqrt()).round() as i32, speed: 120.0 + 10.0 * (difficulty_multiplier - 1.0).max(0.0), size: CRAWLING_TORMENT_SIZE, sprite_path: "sprites/crawling_torment_placeholder.png", projectile_range: None, projectile_fire_rate: None, projectile_speed: None, projectile_damage: None, xp_value: ECHOING_SOUL_VALUE / 5, item_drop_chance_override: Some(MINION_ITEM_DROP_CHANCE), },
             HorrorType::FrenziedBehemoth => HorrorStats { horror_type, health: (70.0 * difficulty_multiplier * 1.3).max(1.0) as i32, damage_on_collision: (25.0 * difficulty_multiplier.sqrt()).round() as i32, speed: 80.0 + 15.0 * (difficulty_multiplier - 1.0).max(0.0), size: FRENZIED_BEHEMOTH_SIZE, sprite_path: "sprites/frenzied_behemoth_placeholder.png", projectile_range: None, projectile_fire_rate: None, projectile_speed: None, projectile_damage: None, xp_value: ECHOING_SOUL_VALUE + 25, item_drop_chance_override: Some(ITEM_DROP_CHANCE + 0.1), },
+            HorrorType::BloatedMartyr => HorrorStats { horror_type, health: (18.0 * difficulty_multiplier).max(1.0) as i32, damage_on_collision: (12.0 * difficulty_multiplier.sqrt()).round() as i32, speed: 125.0 + 15.0 * (difficulty_multiplier - 1.0).max(0.0), size: BLOATED_MARTYR_SIZE, sprite_path: "sprites/mindless_spawn.png", projectile_range: None, projectile_fire_rate: None, projectile_speed: None, projectile_damage: None, xp_value: ECHOING_SOUL_VALUE + 5, item_drop_chance_override: Some(ITEM_DROP_CHANCE), },
+            HorrorType::CarapaceWarden => HorrorStats { horror_type, health: (55.0 * difficulty_multiplier * 1.2).max(1.0) as i32, damage_on_collision: (15.0 * difficulty_multiplier.sqrt()).round() as i32, speed: 55.0 + 10.0 * (difficulty_multiplier - 1.0).max(0.0), size: CARAPACE_WARDEN_SIZE, sprite_path: "sprites/bulwark_of_flesh.png", projectile_range: None, projectile_fire_rate: None, projectile_speed: None, projectile_damage: None, xp_value: ECHOING_SOUL_VALUE + 20, item_drop_chance_override: Some(ITEM_DROP_CHANCE + 0.05), },
+            HorrorType::MendingThrall => HorrorStats { horror_type, health: (30.0 * difficulty_multiplier).max(1.0) as i32, damage_on_collision: (6.0 * difficulty_multiplier.sqrt()).round() as i32, speed: 75.0 + 10.0 * (difficulty_multiplier - 1.0).max(0.0), size: MENDING_THRALL_SIZE, sprite_path: "sprites/guardian_seed.png", projectile_range: None, projectile_fire_rate: None, projectile_speed: None, projectile_damage: None, xp_value: ECHOING_SOUL_VALUE + 20, item_drop_chance_override: Some(ITEM_DROP_CHANCE + 0.07), },
+            HorrorType::BroodSac => HorrorStats { horror_type, health: (45.0 * difficulty_multiplier).max(1.0) as i32, damage_on_collision: (12.0 * difficulty_multiplier.sqrt()).round() as i32, speed: 65.0 + 10.0 * (difficulty_multiplier - 1.0).max(0.0), size: BROOD_SAC_SIZE, sprite_path: "sprites/brood_tender.png", projectile_range: None, projectile_fire_rate: None, projectile_speed: None, projectile_damage: None, xp_value: ECHOING_SOUL_VALUE + 10, item_drop_chance_override: Some(ITEM_DROP_CHANCE + 0.03), },
         }
     }
 }
@@ -80,14 +152,41 @@ impl HorrorStats {
 #[derive(Component)]
 pub struct Horror {
     pub horror_type: HorrorType, pub size: Vec2, pub damage_on_collision: i32, pub speed: f32,
-    pub xp_value: u32, pub item_drop_chance: f64, pub is_elite: bool,
+    pub xp_value: u32, pub item_drop_chance: f64, pub is_elite: bool, pub max_health: i32,
+}
+
+impl Horror {
+    pub fn mass(&self) -> f32 { self.horror_type.mass() * if self.is_elite { ELITE_MASS_MULTIPLIER } else { 1.0 } }
+}
+
+/// Impulse from a hit, decaying over a few frames. The movement system rebuilds `Velocity` from the horror's intent every
+/// frame and then adds this on top, so the impulse has to live here between frames rather than in `Velocity` itself.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Knockback { pub velocity: Vec2, }
+impl Knockback {
+    pub fn from_hit(direction: Vec2, force: f32, mass: f32) -> Self { Self { velocity: direction * force / mass.max(0.1) } }
 }
 
 #[derive(Component)]
-pub struct RangedAttackerBehavior { pub shooting_range: f32, pub fire_timer: Timer, pub projectile_speed: f32, pub projectile_damage: i32, pub state: RangedAttackerState, pub reposition_target: Option<Vec2>, pub reposition_timer: Timer, }
+pub struct RangedAttackerBehavior { pub shooting_range: f32, pub fire_timer: Timer, pub projectile_speed: f32, pub projectile_damage: i32, pub attack_pattern: HorrorAttackPattern, pub volley_angle: f32, pub state: RangedAttackerState, pub reposition_target: Option<Vec2>, pub reposition_timer: Timer, }
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RangedAttackerState { Idle, Attacking, Repositioning, }
-impl Default for RangedAttackerBehavior { fn default() -> Self { Self { shooting_range: 300.0, fire_timer: Timer::from_seconds(2.0, TimerMode::Repeating), projectile_speed: 250.0, projectile_damage: 8, state: RangedAttackerState::Idle, reposition_target: None, reposition_timer: Timer::from_seconds(REPOSITION_DURATION_SECONDS, TimerMode::Once), } } }
+impl Default for RangedAttackerBehavior { fn default() -> Self { Self { shooting_range: 300.0, fire_timer: Timer::from_seconds(2.0, TimerMode::Repeating), projectile_speed: 250.0, projectile_damage: 8, attack_pattern: HorrorAttackPattern::Single, volley_angle: 0.0, state: RangedAttackerState::Idle, reposition_target: None, reposition_timer: Timer::from_seconds(REPOSITION_DURATION_SECONDS, TimerMode::Once), } } }
+
+/// How a ranged horror releases a volley. Any horror (or boss) carrying a `RangedAttackerBehavior` can use any pattern.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HorrorAttackPattern {
+    /// One straight shot at the survivor.
+    Single,
+    /// `count` shots fanned evenly across `arc_degrees`, centred on the survivor.
+    Spread { count: u32, arc_degrees: f32 },
+    /// `count` shots in a full circle; the ring turns by `rotation_per_volley_degrees` each volley.
+    Ring { count: u32, rotation_per_volley_degrees: f32 },
+    /// A slow orb that steers towards the survivor at up to `turn_rate` radians per second.
+    HomingOrb { turn_rate: f32, speed_multiplier: f32 },
+    /// Marks the survivor's position and erupts after `delay_secs`, damaging anything within `radius`.
+    GroundTelegraph { radius: f32, delay_secs: f32 },
+}
 
 #[derive(Component)]
 pub struct VoidBlinkerBehavior { pub state: VoidBlinkerState, pub action_timer: Timer, pub next_teleport_destination: Option<Vec2>, }
@@ -105,13 +204,51 @@ pub struct FrenziedBehemothBehavior { pub state: FrenziedBehemothState, pub char
 pub enum FrenziedBehemothState { Roaming, Telegraphing, Charging, Cooldown, }
 impl Default for FrenziedBehemothBehavior { fn default() -> Self { Self { state: FrenziedBehemothState::Roaming, charge_cooldown_timer: Timer::from_seconds(CHARGER_CHARGE_COOLDOWN_SECS, TimerMode::Once), telegraph_timer: Timer::from_seconds(CHARGER_TELEGRAPH_SECS, TimerMode::Once), charge_duration_timer: Timer::from_seconds(CHARGER_CHARGE_DURATION_SECS, TimerMode::Once), charge_target_pos: None, charge_direction: None, } } }
 
-#[derive(Component)] pub struct HorrorProjectile;
+/// Rushes the survivor and bursts once close enough, leaving no soul behind.
+#[derive(Component)]
+pub struct BloatedMartyrBehavior { pub state: BloatedMartyrState, pub fuse_timer: Timer, pub blast_radius: f32, }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BloatedMartyrState { Chasing, Primed, }
+impl Default for BloatedMartyrBehavior { fn default() -> Self { Self { state: BloatedMartyrState::Chasing, fuse_timer: Timer::from_seconds(MARTYR_FUSE_SECS, TimerMode::Once), blast_radius: MARTYR_BLAST_RADIUS, } } }
+
+/// Turns slowly to face the survivor; projectiles striking within the front arc are absorbed by its shell.
+#[derive(Component)]
+pub struct CarapaceWardenBehavior { pub facing: Vec2, pub turn_rate: f32, pub shield_arc_degrees: f32, }
+impl Default for CarapaceWardenBehavior { fn default() -> Self { Self { facing: Vec2::X, turn_rate: WARDEN_TURN_RATE, shield_arc_degrees: WARDEN_SHIELD_ARC_DEGREES, } } }
+impl CarapaceWardenBehavior {
+    pub fn blocks_projectile(&self, warden_pos: Vec2, projectile_pos: Vec2) -> bool {
+        let to_projectile = (projectile_pos - warden_pos).normalize_or_zero();
+        to_projectile != Vec2::ZERO && to_projectile.dot(self.facing) >= (self.shield_arc_degrees / 2.0).to_radians().cos()
+    }
+}
+
+/// Periodically restores a share of every nearby horror's health.
+#[derive(Component)]
+pub struct MendingThrallBehavior { pub heal_timer: Timer, pub heal_radius: f32, pub heal_fraction: f32, }
+impl Default for MendingThrallBehavior { fn default() -> Self { Self { heal_timer: Timer::from_seconds(THRALL_HEAL_COOLDOWN_SECS, TimerMode::Repeating), heal_radius: THRALL_HEAL_RADIUS, heal_fraction: THRALL_HEAL_FRACTION, } } }
+
+/// Bursts into `CrawlingTorment`s when slain.
+#[derive(Component)]
+pub struct BroodSacBehavior { pub split_count: u32, pub has_split: bool, }
+impl Default for BroodSacBehavior { fn default() -> Self { Self { split_count: BROOD_SAC_SPLIT_COUNT, has_split: false, } } }
+
+#[derive(Component)] pub struct HorrorProjectile { pub source_type: HorrorType, pub radius: f32, }
+/// Turns a horror projectile's velocity towards the survivor every frame, keeping its speed.
+#[derive(Component, Debug)] pub struct HomingProjectile { pub turn_rate: f32, }
+/// A marked patch of ground that erupts once its timer runs out.
+#[derive(Component, Debug)] pub struct HorrorGroundTelegraph { pub source_type: HorrorType, pub radius: f32, pub damage: i32, pub timer: Timer, }
+#[derive(Event, Debug, Clone, Copy)] pub struct HorrorKilledEvent { pub horror_type: HorrorType, pub is_elite: bool, pub position: Vec3, }
 const HORROR_PROJECTILE_SPRITE_SIZE: Vec2 = Vec2::new(15.0, 15.0);
 const HORROR_PROJECTILE_COLOR: Color = Color::rgb(0.3, 0.8, 0.4);
 const HORROR_PROJECTILE_LIFETIME: f32 = 3.5;
 const HORROR_PROJECTILE_Z_POS: f32 = 0.7;
+const HOMING_ORB_SPRITE_SIZE: Vec2 = Vec2::new(22.0, 22.0);
+const HOMING_ORB_COLOR: Color = Color::rgb(0.7, 0.3, 0.9);
+const HOMING_ORB_LIFETIME: f32 = 6.0;
+const GROUND_TELEGRAPH_COLOR: Color = Color::rgba(1.0, 0.2, 0.1, 0.15);
+const GROUND_TELEGRAPH_Z_POS: f32 = 0.1;
 
-fn spawn_horror_projectile( commands: &mut Commands, asset_server: &Res<AssetServer>, mut position: Vec3, direction: Vec2, speed: f32, damage: i32,) {
+pub fn spawn_horror_projectile( commands: &mut Commands, asset_server: &Res<AssetServer>, mut position: Vec3, direction: Vec2, speed: f32, damage: i32, source_type: HorrorType,) -> Entity {
     position.z = HORROR_PROJECTILE_Z_POS;
     commands.spawn((
         SpriteBundle {
@@ -121,37 +258,98 @@ fn spawn_horror_projectile( commands: &mut Commands, asset_server: &Res<AssetSer
             transform: Transform::from_translation(position).with_rotation(Quat::from_rotation_z(direction.y.atan2(direction.x))),
             ..default()
         },
-        HorrorProjectile, Velocity(direction * speed), Damage(damage),
+        HorrorProjectile { source_type, radius: HORROR_PROJECTILE_SPRITE_SIZE.x / 2.0 }, Velocity(direction * speed), Damage(damage),
         Lifetime { timer: Timer::from_seconds(HORROR_PROJECTILE_LIFETIME, TimerMode::Once)},
         Name::new("HorrorIchorBlast"),
+    )).id()
+}
+
+pub fn spawn_horror_ground_telegraph(commands: &mut Commands, asset_server: &Res<AssetServer>, target: Vec2, radius: f32, delay_secs: f32, damage: i32, source_type: HorrorType,) {
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load("sprites/aura_effect.png"),
+            sprite: Sprite { custom_size: Some(Vec2::splat(radius * 2.0)), color: GROUND_TELEGRAPH_COLOR, ..default() },
+            transform: Transform::from_translation(target.extend(GROUND_TELEGRAPH_Z_POS)),
+            ..default()
+        },
+        HorrorGroundTelegraph { source_type, radius, damage, timer: Timer::from_seconds(delay_secs, TimerMode::Once) },
+        Name::new("HorrorGroundTelegraph"),
     ));
 }
 
+/// One volley a ranged horror releases: what pattern, from where, at what, and how hard.
+pub struct HorrorVolley { pub pattern: HorrorAttackPattern, pub origin: Vec3, pub target: Vec2, pub speed: f32, pub damage: i32, pub source_type: HorrorType, }
+
+/// Releases `volley` at its target. `volley_angle` carries the ring's rotation between volleys.
+pub fn fire_horror_attack_pattern(commands: &mut Commands, asset_server: &Res<AssetServer>, volley: HorrorVolley, volley_angle: &mut f32) {
+    let HorrorVolley { pattern, origin, target, speed, damage, source_type } = volley;
+    let aim = (target - origin.truncate()).normalize_or_zero();
+    let aim = if aim == Vec2::ZERO { Vec2::X } else { aim };
+    match pattern {
+        HorrorAttackPattern::Single => { spawn_horror_projectile(commands, asset_server, origin, aim, speed, damage, source_type); }
+        HorrorAttackPattern::Spread { count, arc_degrees } => {
+            let count = count.max(1);
+            let step = if count > 1 { arc_degrees.to_radians() / (count - 1) as f32 } else { 0.0 };
+            let start = -arc_degrees.to_radians() / 2.0 * if count > 1 { 1.0 } else { 0.0 };
+            for i in 0..count {
+                let direction = Vec2::from_angle(start + step * i as f32).rotate(aim);
+                spawn_horror_projectile(commands, asset_server, origin, direction, speed, damage, source_type);
+            }
+        }
+        HorrorAttackPattern::Ring { count, rotation_per_volley_degrees } => {
+            let count = count.max(1);
+            let step = std::f32::consts::TAU / count as f32;
+            for i in 0..count {
+                let direction = Vec2::from_angle(*volley_angle + step * i as f32);
+                spawn_horror_projectile(commands, asset_server, origin, direction, speed, damage, source_type);
+            }
+            *volley_angle = (*volley_angle + rotation_per_volley_degrees.to_radians()) % std::f32::consts::TAU;
+        }
+        HorrorAttackPattern::HomingOrb { turn_rate, speed_multiplier } => {
+            let orb = spawn_horror_projectile(commands, asset_server, origin, aim, speed * speed_multiplier, damage, source_type);
+            commands.entity(orb).insert((
+                HomingProjectile { turn_rate },
+                HorrorProjectile { source_type, radius: HOMING_ORB_SPRITE_SIZE.x / 2.0 },
+                Sprite { custom_size: Some(HOMING_ORB_SPRITE_SIZE), color: HOMING_ORB_COLOR, ..default() },
+                asset_server.load::<Image>("sprites/void_ripper_projectile_placeholder.png"),
+                Lifetime { timer: Timer::from_seconds(HOMING_ORB_LIFETIME, TimerMode::Once) },
+                Name::new("HorrorHomingOrb"),
+            ));
+        }
+        HorrorAttackPattern::GroundTelegraph { radius, delay_secs } => { spawn_horror_ground_telegraph(commands, asset_server, target, radius, delay_secs, damage, source_type); }
+    }
+}
+
 #[derive(Resource)] pub struct HorrorSpawnTimer { pub timer: Timer, }
 impl Default for HorrorSpawnTimer { fn default() -> Self { Self { timer: Timer::from_seconds(2.0, TimerMode::Repeating), } } }
 
 pub struct HorrorPlugin;
-fn should_despawn_all_entities_on_session_end(next_state: Res<NextState<AppState>>) -> bool { match next_state.0 { Some(AppState::MainMenu) | Some(AppState::GameOver) => true, _ => false, } }
 
 impl Plugin for HorrorPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (
+        app.add_event::<HorrorKilledEvent>()
+            .add_systems(Update, (
                 horror_spawn_system,
-                horror_movement_system,
+                horror_movement_system.after(update_flow_field_system),
+                resolve_horror_crowd_collisions_system.after(horror_movement_system),
+                knockback_decay_system,
                 frozen_effect_tick_system, 
                 ranged_attacker_logic,
                 void_blinker_ai_system,
                 flesh_weaver_ai_system,
                 frenzied_behemoth_ai_system,
+                bloated_martyr_ai_system,
+                carapace_warden_ai_system,
+                mending_thrall_ai_system,
+                brood_sac_split_system,
+                homing_projectile_steering_system,
                 horror_projectile_collision_system,
+                horror_ground_telegraph_system,
                 horror_projectile_lifetime_system,
                 handle_horror_death_drops,
             ).chain().run_if(in_state(AppState::InGame)))
             .add_systems(PostUpdate, update_horror_count_system_in_game_state.run_if(in_state(AppState::InGame)))
-            .add_systems(OnExit(AppState::InGame), (
-                despawn_all_horrors.run_if(should_despawn_all_entities_on_session_end),
-                despawn_all_item_drops.run_if(should_despawn_all_entities_on_session_end)
-            ));
+            .add_systems(OnSessionEnd, (despawn_all_horrors, despawn_all_item_drops, despawn_all_horror_attacks));
     }
 }
 
@@ -161,31 +359,36 @@ pub fn despawn_all_horrors(mut commands: Commands, horror_query: Query<Entity, W
 fn despawn_all_item_drops(mut commands: Commands, item_drop_query: Query<Entity, With<ItemDrop>>) {
     for entity in item_drop_query.iter() { commands.entity(entity).despawn_recursive(); }
 }
+type HorrorAttackFilter = Or<(With<HorrorProjectile>, With<HorrorGroundTelegraph>)>;
+fn despawn_all_horror_attacks(mut commands: Commands, attack_query: Query<Entity, HorrorAttackFilter>) {
+    for entity in attack_query.iter() { commands.entity(entity).despawn_recursive(); }
+}
 
 fn spawn_horror_type(
     commands: &mut Commands, asset_server: &Res<AssetServer>, horror_type: HorrorType,
-    position: Vec3, difficulty_multiplier: f32, is_elite: bool,
+    position: Vec3, difficulty_multiplier: f32, is_elite: bool, modifiers: &RunModifiers,
 ) {
     let base_stats = HorrorStats::get_for_type(horror_type, difficulty_multiplier);
     let mut final_health = base_stats.health; 
-    let mut final_damage = base_stats.damage_on_collision;
-    let mut final_speed = base_stats.speed; 
+    let final_damage = base_stats.damage_on_collision;
+    let mut final_speed = base_stats.speed * modifiers.horror_speed_multiplier; 
     let mut final_size = base_stats.size;
     let mut final_xp = base_stats.xp_value; 
     let mut final_item_chance = base_stats.item_drop_chance_override.unwrap_or(0.0);
-    let mut final_name = format!("{:?}", base_stats.horror_type); 
+    let final_name = format!("{:?}", base_stats.horror_type); 
     let mut sprite_color = Color::WHITE;
 
+    // Elites are defined by their affixes; the flat bonus only keeps them from dying before they can show them off.
+    let affixes = if is_elite { roll_elite_affixes(horror_type, &mut rand::thread_rng()) } else { Vec::new() };
+    let is_hasted = affixes.contains(&EliteAffix::Hasted);
     if is_elite {
-        final_health = (final_health as f32 * 2.5).ceil() as i32;
-        final_damage = (final_damage as f32 * 1.8).ceil() as i32;
-        final_speed *= 1.15;
+        final_health = (final_health as f32 * ELITE_HEALTH_MULTIPLIER).ceil() as i32;
         final_size *= 1.25;
         final_xp = (final_xp as f32 * 2.0).ceil() as u32;
         final_item_chance = (final_item_chance + ELITE_ITEM_DROP_CHANCE_BONUS).min(1.0);
-        final_name = format!("[Elite] {}", final_name);
-        sprite_color = Color::rgb(1.0, 0.6, 0.6); 
+        if let Some(primary_affix) = affixes.first() { sprite_color = primary_affix.color(); }
     }
+    if is_hasted { final_speed *= HASTED_SPEED_MULTIPLIER; }
 
     let mut horror_entity_commands = commands.spawn((
         SpriteBundle {
@@ -195,7 +398,7 @@ fn spawn_horror_type(
         },
         Horror {
             horror_type: base_stats.horror_type, size: final_size, damage_on_collision: final_damage,
-            speed: final_speed, xp_value: final_xp, item_drop_chance: final_item_chance, is_elite,
+            speed: final_speed, xp_value: final_xp, item_drop_chance: final_item_chance, is_elite, max_health: final_health,
         },
         Health(final_health), Velocity(Vec2::ZERO), Name::new(final_name),
     ));
@@ -205,16 +408,25 @@ fn spawn_horror_type(
             let mut fire_rate = base_stats.projectile_fire_rate.unwrap_or(2.8);
             let mut proj_speed = base_stats.projectile_speed.unwrap_or(280.0);
             let mut proj_damage = base_stats.projectile_damage.unwrap_or(10);
+            let mut attack_pattern = HorrorAttackPattern::Single;
+            if is_hasted { fire_rate *= HASTED_FIRE_RATE_MULTIPLIER; }
             if is_elite { 
-                fire_rate *= 0.7;
                 proj_speed *= 1.2;
                 proj_damage = (proj_damage as f32 * 1.5) as i32;
+                attack_pattern = *[
+                    HorrorAttackPattern::Spread { count: 3, arc_degrees: 30.0 },
+                    HorrorAttackPattern::Ring { count: 8, rotation_per_volley_degrees: 22.5 },
+                    HorrorAttackPattern::HomingOrb { turn_rate: 1.5, speed_multiplier: 0.5 },
+                    HorrorAttackPattern::GroundTelegraph { radius: 70.0, delay_secs: 1.2 },
+                ].choose(&mut rand::thread_rng()).unwrap_or(&HorrorAttackPattern::Single);
             }
             horror_entity_commands.insert(RangedAttackerBehavior { 
                 shooting_range: base_stats.projectile_range.unwrap_or(350.0), 
                 fire_timer: Timer::from_seconds(fire_rate, TimerMode::Repeating), 
                 projectile_speed: proj_speed, 
                 projectile_damage: proj_damage, 
+                attack_pattern,
+                volley_angle: 0.0,
                 state: RangedAttackerState::Idle, 
                 reposition_target: None, 
                 reposition_timer: Timer::from_seconds(REPOSITION_DURATION_SECONDS, TimerMode::Once), 
@@ -223,15 +435,32 @@ fn spawn_horror_type(
         HorrorType::VoidBlinker => { horror_entity_commands.insert(VoidBlinkerBehavior::default()); }
         HorrorType::FleshWeaver => { horror_entity_commands.insert(FleshWeaverBehavior::default()); }
         HorrorType::FrenziedBehemoth => { horror_entity_commands.insert(FrenziedBehemothBehavior::default());}
+        HorrorType::BloatedMartyr => { horror_entity_commands.insert(BloatedMartyrBehavior::default()); }
+        HorrorType::CarapaceWarden => { horror_entity_commands.insert(CarapaceWardenBehavior::default()); }
+        HorrorType::MendingThrall => { horror_entity_commands.insert(MendingThrallBehavior::default()); }
+        HorrorType::BroodSac => {
+            let split_count = if is_elite { ELITE_BROOD_SAC_SPLIT_COUNT } else { BROOD_SAC_SPLIT_COUNT };
+            horror_entity_commands.insert(BroodSacBehavior { split_count, ..default() });
+        }
         _ => {}
     }
+    if is_elite { attach_elite_affixes(&mut horror_entity_commands, asset_server, affixes, horror_type, final_health, final_size); }
+}
+
+/// How many horrors the run allows and how hard new ones hit, from the cycle and the chosen omens.
+#[derive(SystemParam)]
+struct SpawnPressure<'w> {
+    max_horrors: Res<'w, MaxHorrors>,
+    game_state: Res<'w, GameState>,
+    run_modifiers: Res<'w, RunModifiers>,
 }
 
 fn horror_spawn_system(
     mut commands: Commands, time: Res<Time>, mut spawn_timer: ResMut<HorrorSpawnTimer>,
     asset_server: Res<AssetServer>, player_query: Query<&Transform, With<Survivor>>,
-    horror_query: Query<(), With<Horror>>, max_horrors: Res<MaxHorrors>, game_state: Res<GameState>,
+    horror_query: Query<(), With<Horror>>, pressure: SpawnPressure,
 ) {
+    let SpawnPressure { max_horrors, game_state, run_modifiers } = pressure;
     spawn_timer.timer.tick(time.delta());
     if !spawn_timer.timer.just_finished() || horror_query.iter().count() >= max_horrors.0 as usize { return; }
     let Ok(player_transform) = player_query.get_single() else { return; };
@@ -247,21 +476,22 @@ fn horror_spawn_system(
 
     let chosen_type = match game_state.cycle_number {
         1..=2 => HorrorType::SkitteringShadowling,
-        3..=4 => { if rng.gen_bool(0.3) { HorrorType::SkitteringShadowling } else if rng.gen_bool(0.3) { HorrorType::FloatingEyeball } else { HorrorType::VoidBlinker } }
-        5..=6 => { let roll = rng.gen_range(0..100); if roll < 20 { HorrorType::SkitteringShadowling } else if roll < 40 { HorrorType::FloatingEyeball } else if roll < 60 { HorrorType::VoidBlinker } else { HorrorType::FleshWeaver } }
-        _ => { let roll = rng.gen_range(0..100); if roll < 15 { HorrorType::SkitteringShadowling } else if roll < 30 { HorrorType::FloatingEyeball } else if roll < 45 { HorrorType::VoidBlinker } else if roll < 60 { HorrorType::FleshWeaver } else if roll < 80 { HorrorType::FrenziedBehemoth } else { HorrorType::AmorphousFleshbeast } }
+        3..=4 => { if rng.gen_bool(0.15) { HorrorType::BloatedMartyr } else if rng.gen_bool(0.3) { HorrorType::SkitteringShadowling } else if rng.gen_bool(0.3) { HorrorType::FloatingEyeball } else { HorrorType::VoidBlinker } }
+        5..=6 => { let roll = rng.gen_range(0..100); if roll < 15 { HorrorType::SkitteringShadowling } else if roll < 30 { HorrorType::FloatingEyeball } else if roll < 45 { HorrorType::VoidBlinker } else if roll < 60 { HorrorType::FleshWeaver } else if roll < 75 { HorrorType::BloatedMartyr } else if roll < 90 { HorrorType::CarapaceWarden } else { HorrorType::MendingThrall } }
+        _ => { let roll = rng.gen_range(0..100); if roll < 10 { HorrorType::SkitteringShadowling } else if roll < 20 { HorrorType::FloatingEyeball } else if roll < 30 { HorrorType::VoidBlinker } else if roll < 40 { HorrorType::FleshWeaver } else if roll < 52 { HorrorType::FrenziedBehemoth } else if roll < 62 { HorrorType::AmorphousFleshbeast } else if roll < 72 { HorrorType::BloatedMartyr } else if roll < 82 { HorrorType::CarapaceWarden } else if roll < 90 { HorrorType::MendingThrall } else { HorrorType::BroodSac } }
     };
-    let is_elite = rng.gen_bool(ELITE_SPAWN_CHANCE) &&
+    let is_elite = rng.gen_bool((ELITE_SPAWN_CHANCE * run_modifiers.elite_chance_multiplier).min(1.0)) &&
                    chosen_type != HorrorType::CrawlingTorment && 
                    chosen_type != HorrorType::FleshWeaver && 
+                   chosen_type != HorrorType::MendingThrall && 
                    chosen_type != HorrorType::FrenziedBehemoth; 
                    
-    spawn_horror_type(&mut commands, &asset_server, chosen_type, final_spawn_pos, difficulty_multiplier, is_elite);
+    spawn_horror_type(&mut commands, &asset_server, chosen_type, final_spawn_pos, difficulty_multiplier, is_elite, &run_modifiers);
 }
 
-fn horror_movement_system( mut query: Query<(&mut Transform, &mut Velocity, &Horror, Option<&RangedAttackerBehavior>, Option<&VoidBlinkerBehavior>, Option<&FleshWeaverBehavior>, Option<&FrenziedBehemothBehavior>, Option<&Frozen>)>, player_query: Query<&Transform, (With<Survivor>, Without<Horror>)>, time: Res<Time>,) {
+fn horror_movement_system( mut query: Query<(&mut Transform, &mut Velocity, &Horror, Option<&RangedAttackerBehavior>, Option<&VoidBlinkerBehavior>, Option<&FleshWeaverBehavior>, Option<&FrenziedBehemothBehavior>, Option<&BloatedMartyrBehavior>, Option<&Frozen>, Option<&Knockback>)>, player_query: Query<&Transform, (With<Survivor>, Without<Horror>)>, flow_field: Res<FlowField>, time: Res<Time>,) {
     let Ok(player_transform) = player_query.get_single() else { return; }; let player_pos = player_transform.translation.truncate();
-    for (mut transform, mut velocity, horror_data, ranged_opt, void_blinker_opt, flesh_weaver_opt, frenzied_behemoth_opt, frozen_opt) in query.iter_mut() {
+    for (mut transform, mut velocity, horror_data, ranged_opt, void_blinker_opt, flesh_weaver_opt, frenzied_behemoth_opt, martyr_opt, frozen_opt, knockback_opt) in query.iter_mut() {
         let mut current_speed_multiplier = 1.0; if let Some(frozen) = frozen_opt { current_speed_multiplier = frozen.speed_multiplier; }
         if current_speed_multiplier == 0.0 { velocity.0 = Vec2::ZERO; continue; } 
         let horror_pos = transform.translation.truncate(); let mut should_chase_player_normally = true;
@@ -269,13 +499,28 @@ fn horror_movement_system( mut query: Query<(&mut Transform, &mut Velocity, &Hor
         if should_chase_player_normally && ranged_opt.is_some() { if let Some(ranged_behavior) = ranged_opt { match ranged_behavior.state { RangedAttackerState::Attacking => { should_chase_player_normally = false; velocity.0 = Vec2::ZERO; } RangedAttackerState::Repositioning => { if let Some(target_pos) = ranged_behavior.reposition_target { let dir_to_target = (target_pos - horror_pos).normalize_or_zero(); if dir_to_target != Vec2::ZERO { velocity.0 = dir_to_target * horror_data.speed * REPOSITION_SPEED_MULTIPLIER * current_speed_multiplier; transform.rotation = Quat::from_rotation_z(dir_to_target.y.atan2(dir_to_target.x)); } else { velocity.0 = Vec2::ZERO; } should_chase_player_normally = false; } } RangedAttackerState::Idle => {} } } }
         if let Some(_summoner_behavior) = flesh_weaver_opt { let distance_to_player = player_pos.distance(horror_pos); if distance_to_player < 250.0 { let direction_away_from_player = (horror_pos - player_pos).normalize_or_zero(); if direction_away_from_player != Vec2::ZERO { velocity.0 = direction_away_from_player * horror_data.speed * 0.5 * current_speed_multiplier; transform.rotation = Quat::from_rotation_z(direction_away_from_player.y.atan2(direction_away_from_player.x)); } else { velocity.0 = Vec2::ZERO; } should_chase_player_normally = false; } else if distance_to_player > 400.0 { let direction_to_player = (player_pos - horror_pos).normalize_or_zero(); if direction_to_player != Vec2::ZERO { velocity.0 = direction_to_player * horror_data.speed * 0.5 * current_speed_multiplier; transform.rotation = Quat::from_rotation_z(direction_to_player.y.atan2(direction_to_player.x)); } else { velocity.0 = Vec2::ZERO; } should_chase_player_normally = false; } else { velocity.0 = Vec2::ZERO; should_chase_player_normally = false; } }
         if let Some(charger_behavior) = frenzied_behemoth_opt { match charger_behavior.state { FrenziedBehemothState::Telegraphing | FrenziedBehemothState::Cooldown => { should_chase_player_normally = false; velocity.0 = Vec2::ZERO; } FrenziedBehemothState::Charging => { if let Some(charge_dir) = charger_behavior.charge_direction { velocity.0 = charge_dir * horror_data.speed * CHARGER_CHARGE_SPEED_MULTIPLIER * current_speed_multiplier; } else { velocity.0 = Vec2::ZERO; } should_chase_player_normally = false; } FrenziedBehemothState::Roaming => {} } }
-        if should_chase_player_normally { let direction_to_player = (player_pos - horror_pos).normalize_or_zero(); if direction_to_player != Vec2::ZERO { velocity.0 = direction_to_player * horror_data.speed * current_speed_multiplier; transform.rotation = Quat::from_rotation_z(direction_to_player.y.atan2(direction_to_player.x)); } else { velocity.0 = Vec2::ZERO; } }
+        if martyr_opt.is_some_and(|martyr| martyr.state == BloatedMartyrState::Primed) { should_chase_player_normally = false; velocity.0 = Vec2::ZERO; }
+        if should_chase_player_normally {
+            let direction_to_player = (player_pos - horror_pos).normalize_or_zero();
+            let path_direction = flow_field.direction_at(horror_pos).unwrap_or(direction_to_player);
+            let steering = (path_direction + flow_field.separation_at(horror_pos) * SEPARATION_STEERING_WEIGHT).normalize_or_zero();
+            if steering != Vec2::ZERO { velocity.0 = steering * horror_data.speed * current_speed_multiplier; transform.rotation = Quat::from_rotation_z(steering.y.atan2(steering.x)); } else { velocity.0 = Vec2::ZERO; }
+        }
+        if let Some(knockback) = knockback_opt { velocity.0 += knockback.velocity; }
         transform.translation.x += velocity.0.x * time.delta_seconds(); transform.translation.y += velocity.0.y * time.delta_seconds();
     }
 }
 
+fn knockback_decay_system(mut commands: Commands, time: Res<Time>, mut knockback_query: Query<(Entity, &mut Knockback)>,) {
+    let decay = (-KNOCKBACK_DAMPING * time.delta_seconds()).exp();
+    for (entity, mut knockback) in knockback_query.iter_mut() {
+        knockback.velocity *= decay;
+        if knockback.velocity.length() < KNOCKBACK_MIN_SPEED { commands.entity(entity).remove::<Knockback>(); }
+    }
+}
+
 fn frozen_effect_tick_system( mut commands: Commands, time: Res<Time>, mut frozen_query: Query<(Entity, &mut Frozen)>,) { for (entity, mut frozen_effect) in frozen_query.iter_mut() { frozen_effect.timer.tick(time.delta()); if frozen_effect.timer.finished() { commands.entity(entity).remove::<Frozen>(); } } }
-fn ranged_attacker_logic(mut commands: Commands, time: Res<Time>, asset_server: Res<AssetServer>, mut attacker_query: Query<(&mut Transform, &mut RangedAttackerBehavior, &GlobalTransform, &Horror)>, player_query: Query<&Transform, (With<Survivor>, Without<Horror>)>, mut sound_event_writer: EventWriter<PlaySoundEvent>,) { let Ok(player_transform) = player_query.get_single() else { return; }; let player_position = player_transform.translation.truncate(); let mut rng = rand::thread_rng(); for (mut transform, mut behavior, attacker_gtransform, _horror_data) in attacker_query.iter_mut() { let attacker_position = attacker_gtransform.translation().truncate(); let distance_to_player = player_position.distance(attacker_position); match behavior.state { RangedAttackerState::Idle => { if distance_to_player <= behavior.shooting_range { behavior.state = RangedAttackerState::Attacking; } } RangedAttackerState::Attacking => { if distance_to_player > behavior.shooting_range * 1.1 { behavior.state = RangedAttackerState::Idle; } else { let dir = (player_position - attacker_position).normalize_or_zero(); if dir != Vec2::ZERO { transform.rotation = Quat::from_rotation_z(dir.y.atan2(dir.x)); } behavior.fire_timer.tick(time.delta()); if behavior.fire_timer.just_finished() { sound_event_writer.send(PlaySoundEvent(SoundEffect::HorrorProjectile)); spawn_horror_projectile( &mut commands, &asset_server, attacker_gtransform.translation(), dir, behavior.projectile_speed, behavior.projectile_damage, ); behavior.state = RangedAttackerState::Repositioning; behavior.reposition_timer.reset(); let perp_dir = Vec2::new(-dir.y, dir.x) * (if rng.gen_bool(0.5) { 1.0 } else { -1.0 }); let dist = rng.gen_range(50.0..150.0); behavior.reposition_target = Some(attacker_position + perp_dir * dist); } } } RangedAttackerState::Repositioning => { behavior.reposition_timer.tick(time.delta()); if behavior.reposition_timer.finished() || (behavior.reposition_target.is_some() && attacker_position.distance(behavior.reposition_target.unwrap()) < 10.0) { behavior.state = RangedAttackerState::Idle; behavior.reposition_target = None; } } } } }
+fn ranged_attacker_logic(mut commands: Commands, time: Res<Time>, asset_server: Res<AssetServer>, mut attacker_query: Query<(&mut Transform, &mut RangedAttackerBehavior, &GlobalTransform, &Horror)>, player_query: Query<&Transform, (With<Survivor>, Without<Horror>)>, mut sound_event_writer: EventWriter<PlaySoundAtEvent>,) { let Ok(player_transform) = player_query.get_single() else { return; }; let player_position = player_transform.translation.truncate(); let mut rng = rand::thread_rng(); for (mut transform, mut behavior, attacker_gtransform, horror_data) in attacker_query.iter_mut() { let attacker_position = attacker_gtransform.translation().truncate(); let distance_to_player = player_position.distance(attacker_position); match behavior.state { RangedAttackerState::Idle => { if distance_to_player <= behavior.shooting_range { behavior.state = RangedAttackerState::Attacking; } } RangedAttackerState::Attacking => { if distance_to_player > behavior.shooting_range * 1.1 { behavior.state = RangedAttackerState::Idle; } else { let dir = (player_position - attacker_position).normalize_or_zero(); if dir != Vec2::ZERO { transform.rotation = Quat::from_rotation_z(dir.y.atan2(dir.x)); } behavior.fire_timer.tick(time.delta()); if behavior.fire_timer.just_finished() { sound_event_writer.send(PlaySoundAtEvent(SoundEffect::HorrorProjectile, attacker_gtransform.translation())); let volley = HorrorVolley { pattern: behavior.attack_pattern, origin: attacker_gtransform.translation(), target: player_position, speed: behavior.projectile_speed, damage: behavior.projectile_damage, source_type: horror_data.horror_type }; fire_horror_attack_pattern(&mut commands, &asset_server, volley, &mut behavior.volley_angle); behavior.state = RangedAttackerState::Repositioning; behavior.reposition_timer.reset(); let perp_dir = Vec2::new(-dir.y, dir.x) * (if rng.gen_bool(0.5) { 1.0 } else { -1.0 }); let dist = rng.gen_range(50.0..150.0); behavior.reposition_target = Some(attacker_position + perp_dir * dist); } } } RangedAttackerState::Repositioning => { behavior.reposition_timer.tick(time.delta()); if behavior.reposition_timer.finished() || (behavior.reposition_target.is_some() && attacker_position.distance(behavior.reposition_target.unwrap()) < 10.0) { behavior.state = RangedAttackerState::Idle; behavior.reposition_target = None; } } } } }
 fn void_blinker_ai_system( _commands: Commands, time: Res<Time>, mut ripper_query: Query<(&mut Transform, &mut VoidBlinkerBehavior, &mut Sprite, &mut Visibility), (With<VoidBlinkerBehavior>, With<Horror>, Without<Survivor>)>, player_query: Query<&Transform, (With<Survivor>, Without<Horror>)>,) { let Ok(player_transform) = player_query.get_single() else { return; }; let player_pos = player_transform.translation.truncate(); let mut rng = rand::thread_rng(); for (mut transform, mut behavior, mut sprite, mut visibility) in ripper_query.iter_mut() { behavior.action_timer.tick(time.delta()); match behavior.state { VoidBlinkerState::Chasing => { if behavior.action_timer.finished() { behavior.state = VoidBlinkerState::PhasingOut; behavior.action_timer.set_duration(Duration::from_secs_f32(PHASE_RIPPER_PHASE_DURATION_SECS)); behavior.action_timer.reset(); let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0); let distance = rng.gen_range(PHASE_RIPPER_TELEPORT_RANGE_MIN..PHASE_RIPPER_TELEPORT_RANGE_MAX); behavior.next_teleport_destination = Some(player_pos + Vec2::new(angle.cos() * distance, angle.sin() * distance)); sprite.color.set_a(0.5); } } VoidBlinkerState::PhasingOut => { sprite.color.set_a(1.0 - behavior.action_timer.fraction()); if behavior.action_timer.just_finished() { *visibility = Visibility::Hidden; behavior.state = VoidBlinkerState::PhasedOut; behavior.action_timer.set_duration(Duration::from_millis(50)); behavior.action_timer.reset(); } } VoidBlinkerState::PhasedOut => { if behavior.action_timer.just_finished() { if let Some(destination) = behavior.next_teleport_destination.take() { transform.translation = destination.extend(transform.translation.z); } behavior.state = VoidBlinkerState::PhasingIn; behavior.action_timer.set_duration(Duration::from_secs_f32(PHASE_RIPPER_PHASE_DURATION_SECS)); behavior.action_timer.reset(); *visibility = Visibility::Visible; sprite.color.set_a(0.0); } } VoidBlinkerState::PhasingIn => { sprite.color.set_a(behavior.action_timer.fraction()); if behavior.action_timer.just_finished() { sprite.color.set_a(1.0); behavior.state = VoidBlinkerState::Cooldown; behavior.action_timer.set_duration(Duration::from_secs_f32(PHASE_RIPPER_TELEPORT_COOLDOWN_SECS)); behavior.action_timer.reset(); } } VoidBlinkerState::Cooldown => { if behavior.action_timer.finished() { behavior.state = VoidBlinkerState::Chasing; behavior.action_timer.set_duration(Duration::from_secs_f32(PHASE_RIPPER_TELEPORT_COOLDOWN_SECS)); behavior.action_timer.reset(); } } } } }
 fn flesh_weaver_ai_system( mut commands: Commands, time: Res<Time>, mut summoner_query: Query<(&Transform, &mut FleshWeaverBehavior), (With<Horror>, With<FleshWeaverBehavior>)>, asset_server: Res<AssetServer>, game_state: Res<GameState>,) { 
     let difficulty_multiplier = game_state.current_difficulty_multiplier;
@@ -296,95 +541,258 @@ fn flesh_weaver_ai_system( mut commands: Commands, time: Res<Time>, mut summoner
         } 
     } 
 }
-fn spawn_and_return_horror_entity( commands: &mut Commands, asset_server: &Res<AssetServer>, horror_type: HorrorType, position: Vec3, difficulty_multiplier: f32,) -> Entity {
+pub fn spawn_and_return_horror_entity( commands: &mut Commands, asset_server: &Res<AssetServer>, horror_type: HorrorType, position: Vec3, difficulty_multiplier: f32,) -> Entity {
     let stats = HorrorStats::get_for_type(horror_type, difficulty_multiplier);
     commands.spawn(( 
         SpriteBundle { texture: asset_server.load(stats.sprite_path), sprite: Sprite { custom_size: Some(stats.size), ..default() }, transform: Transform::from_translation(position), ..default() }, 
-        Horror { horror_type: stats.horror_type, size: stats.size, damage_on_collision: stats.damage_on_collision, speed: stats.speed, xp_value: stats.xp_value, item_drop_chance: stats.item_drop_chance_override.unwrap_or(0.0), is_elite: false }, 
+        Horror { horror_type: stats.horror_type, size: stats.size, damage_on_collision: stats.damage_on_collision, speed: stats.speed, xp_value: stats.xp_value, item_drop_chance: stats.item_drop_chance_override.unwrap_or(0.0), is_elite: false, max_health: stats.health }, 
         Health(stats.health), 
         Velocity(Vec2::ZERO), 
         Name::new(format!("{:?}", stats.horror_type)), 
     )).id() 
 }
+fn bloated_martyr_ai_system(mut commands: Commands, time: Res<Time>, asset_server: Res<AssetServer>, mut martyr_query: Query<(Entity, &Transform, &mut BloatedMartyrBehavior, &mut Sprite, &Health, &Horror)>, player_query: Query<&Transform, (With<Survivor>, Without<Horror>)>,) {
+    let Ok(player_transform) = player_query.get_single() else { return; };
+    let player_pos = player_transform.translation.truncate();
+    for (entity, transform, mut behavior, mut sprite, health, horror_data) in martyr_query.iter_mut() {
+        if health.0 <= 0 { continue; }
+        let martyr_pos = transform.translation.truncate();
+        match behavior.state {
+            BloatedMartyrState::Chasing => {
+                if martyr_pos.distance(player_pos) < MARTYR_PRIME_RANGE {
+                    behavior.state = BloatedMartyrState::Primed;
+                    behavior.fuse_timer.reset();
+                    let blast_damage = (horror_data.damage_on_collision as f32 * MARTYR_BLAST_DAMAGE_MULTIPLIER).round() as i32;
+                    spawn_horror_ground_telegraph(&mut commands, &asset_server, martyr_pos, behavior.blast_radius, MARTYR_FUSE_SECS, blast_damage, horror_data.horror_type);
+                }
+            }
+            BloatedMartyrState::Primed => {
+                behavior.fuse_timer.tick(time.delta());
+                // Swell and redden until the telegraph erupts beneath it.
+                let fuse = behavior.fuse_timer.fraction();
+                sprite.color = Color::rgb(1.0, 1.0 - fuse * 0.8, 1.0 - fuse * 0.8);
+                sprite.custom_size = Some(horror_data.size * (1.0 + fuse * 0.4));
+                if behavior.fuse_timer.just_finished() { commands.entity(entity).despawn_recursive(); }
+            }
+        }
+    }
+}
+
+fn carapace_warden_ai_system(time: Res<Time>, mut warden_query: Query<(&mut Transform, &mut CarapaceWardenBehavior), With<Horror>>, player_query: Query<&Transform, (With<Survivor>, Without<Horror>)>,) {
+    let Ok(player_transform) = player_query.get_single() else { return; };
+    let player_pos = player_transform.translation.truncate();
+    for (mut transform, mut behavior) in warden_query.iter_mut() {
+        let desired = (player_pos - transform.translation.truncate()).normalize_or_zero();
+        if desired == Vec2::ZERO { continue; }
+        let max_turn = behavior.turn_rate * time.delta_seconds();
+        let turn = behavior.facing.angle_between(desired).clamp(-max_turn, max_turn);
+        behavior.facing = Vec2::from_angle(turn).rotate(behavior.facing).normalize_or_zero();
+        // The shell, not the gait, decides which way the warden is seen to face.
+        transform.rotation = Quat::from_rotation_z(behavior.facing.y.atan2(behavior.facing.x));
+    }
+}
+
+fn mending_thrall_ai_system(time: Res<Time>, mut thrall_query: Query<(Entity, &Transform, &mut MendingThrallBehavior)>, mut horror_query: Query<(Entity, &Transform, &mut Health, &Horror), Without<MendingThrallBehavior>>, mut thrall_health_query: Query<(Entity, &Transform, &mut Health, &Horror), With<MendingThrallBehavior>>,) {
+    let mut pulses: Vec<(Entity, Vec2, f32, f32)> = Vec::new();
+    for (thrall_entity, thrall_transform, mut behavior) in thrall_query.iter_mut() {
+        behavior.heal_timer.tick(time.delta());
+        let is_alive = thrall_health_query.get(thrall_entity).is_ok_and(|(_, _, health, _)| health.0 > 0);
+        if behavior.heal_timer.just_finished() && is_alive { pulses.push((thrall_entity, thrall_transform.translation.truncate(), behavior.heal_radius, behavior.heal_fraction)); }
+    }
+    for (thrall_entity, thrall_pos, heal_radius, heal_fraction) in pulses {
+        // Thralls mend one another, but never themselves.
+        let targets = horror_query.iter_mut().chain(thrall_health_query.iter_mut().filter(|(entity, ..)| *entity != thrall_entity));
+        for (_, horror_transform, mut health, horror_data) in targets {
+            if health.0 <= 0 || health.0 >= horror_data.max_health || horror_transform.translation.truncate().distance(thrall_pos) > heal_radius { continue; }
+            let heal = ((horror_data.max_health as f32 * heal_fraction).ceil() as i32).max(1);
+            health.0 = (health.0 + heal).min(horror_data.max_health);
+        }
+    }
+}
+
+pub fn brood_sac_split_system(mut commands: Commands, asset_server: Res<AssetServer>, game_state: Res<GameState>, mut sac_query: Query<(&Transform, &Health, &mut BroodSacBehavior), Changed<Health>>,) {
+    let mut rng = rand::thread_rng();
+    for (sac_transform, health, mut behavior) in sac_query.iter_mut() {
+        if health.0 > 0 || behavior.has_split { continue; }
+        behavior.has_split = true;
+        for _ in 0..behavior.split_count {
+            let offset_angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
+            let offset_distance = rng.gen_range(15.0..40.0);
+            let spawn_pos = (sac_transform.translation.truncate() + Vec2::from_angle(offset_angle) * offset_distance).extend(0.5);
+            spawn_and_return_horror_entity(&mut commands, &asset_server, HorrorType::CrawlingTorment, spawn_pos, game_state.current_difficulty_multiplier);
+        }
+    }
+}
+
 fn frenzied_behemoth_ai_system(time: Res<Time>, mut charger_query: Query<(&Transform, &mut FrenziedBehemothBehavior, &mut Sprite, &Horror)>, player_query: Query<&Transform, With<Survivor>>,){ let Ok(player_transform) = player_query.get_single() else { return; }; let player_pos = player_transform.translation.truncate(); for (charger_transform, mut behavior, mut sprite, _horror_data) in charger_query.iter_mut() { let charger_pos = charger_transform.translation.truncate(); match behavior.state { FrenziedBehemothState::Roaming => { behavior.charge_cooldown_timer.tick(time.delta()); if behavior.charge_cooldown_timer.finished() { let distance_to_player = charger_pos.distance(player_pos); if distance_to_player < CHARGER_DETECTION_RANGE && distance_to_player > CHARGER_MIN_CHARGE_RANGE { behavior.state = FrenziedBehemothState::Telegraphing; behavior.telegraph_timer.reset(); behavior.charge_target_pos = Some(player_pos); sprite.color = Color::rgb(1.0, 0.5, 0.5); } } } FrenziedBehemothState::Telegraphing => { behavior.telegraph_timer.tick(time.delta()); if behavior.telegraph_timer.just_finished() { behavior.state = FrenziedBehemothState::Charging; behavior.charge_duration_timer.reset(); if let Some(target_pos) = behavior.charge_target_pos { behavior.charge_direction = Some((target_pos - charger_pos).normalize_or_zero()); } else { behavior.charge_direction = Some((player_pos - charger_pos).normalize_or_zero()); } sprite.color = Color::rgb(1.0, 0.2, 0.2); } } FrenziedBehemothState::Charging => { behavior.charge_duration_timer.tick(time.delta()); if behavior.charge_duration_timer.finished() { behavior.state = FrenziedBehemothState::Cooldown; behavior.charge_cooldown_timer.reset(); let telegraph_timer_duration_val = behavior.telegraph_timer.duration(); behavior.telegraph_timer.tick(telegraph_timer_duration_val); behavior.charge_direction = None; sprite.color = Color::WHITE; } } FrenziedBehemothState::Cooldown => { if behavior.charge_cooldown_timer.finished() { behavior.state = FrenziedBehemothState::Roaming; } } } } }
 
+/// The survivor as horror attacks see it: where it stands, what it can lose, and the shield that soaks damage first.
+#[derive(QueryData)]
+#[query_data(mutable)]
+struct SurvivorAttackTarget {
+    entity: Entity,
+    global_transform: &'static GlobalTransform,
+    health: &'static mut Health,
+    survivor: &'static mut Survivor,
+    shield: Option<&'static mut ActiveShield>,
+}
+
+/// Where a landed horror attack is reported: the hit sound, the camera shake and the run's damage-taken tally.
+#[derive(SystemParam)]
+struct SurvivorHitReports<'w> {
+    sound: EventWriter<'w, PlaySoundAtEvent>,
+    camera_shake: EventWriter<'w, CameraShakeEvent>,
+    run_stats: ResMut<'w, RunStatistics>,
+}
+
+/// Applies a horror attack to the survivor, draining any active shield first. Returns false while the survivor is invulnerable.
+fn damage_survivor_from_horror_attack(commands: &mut Commands, target: &mut SurvivorAttackTargetItem, damage: i32, source_type: HorrorType, reports: &mut SurvivorHitReports) -> bool {
+    if !target.survivor.invincibility_timer.finished() { return false; }
+    reports.sound.send(PlaySoundAtEvent(SoundEffect::SurvivorHit, target.global_transform.translation()));
+    reports.camera_shake.send(CameraShakeEvent { trauma: SURVIVOR_HIT_TRAUMA });
+    let mut damage_to_take = damage;
+
+    if let Some(shield) = target.shield.as_mut() {
+        if shield.amount > 0 {
+            let damage_absorbed = damage_to_take.min(shield.amount);
+            shield.amount -= damage_absorbed;
+            damage_to_take -= damage_absorbed;
+
+            if shield.amount <= 0 {
+                commands.entity(target.entity).remove::<ActiveShield>();
+            }
+        }
+    }
+
+    if damage_to_take > 0 {
+        target.health.0 -= damage_to_take;
+        reports.run_stats.record_damage_taken(source_type, damage_to_take);
+    }
+    target.survivor.grant_invulnerability(crate::survivor::SURVIVOR_HIT_INVINCIBILITY_SECS);
+    true
+}
+
 fn horror_projectile_collision_system(
     mut commands: Commands, 
-    projectile_query: Query<(Entity, &GlobalTransform, &Damage), With<HorrorProjectile>>, 
-    mut player_query: Query<(Entity, &GlobalTransform, &mut Health, &mut Survivor, Option<&mut ActiveShield>)>, 
-    mut sound_event_writer: EventWriter<PlaySoundEvent>,
+    projectile_query: Query<(Entity, &GlobalTransform, &Damage, &HorrorProjectile)>, 
+    mut player_query: Query<SurvivorAttackTarget>, 
+    mut reports: SurvivorHitReports,
 ) { 
-    if let Ok((player_entity, player_gtransform, mut player_health, mut player_component, mut opt_active_shield)) = player_query.get_single_mut() { 
-        for (projectile_entity, projectile_gtransform, projectile_damage) in projectile_query.iter() { 
-            let distance = projectile_gtransform.translation().truncate().distance(player_gtransform.translation().truncate()); 
-            let projectile_radius = HORROR_PROJECTILE_SPRITE_SIZE.x / 2.0; 
+    if let Ok(mut player) = player_query.get_single_mut() { 
+        for (projectile_entity, projectile_gtransform, projectile_damage, projectile_data) in projectile_query.iter() { 
+            let distance = projectile_gtransform.translation().truncate().distance(player.global_transform.translation().truncate()); 
             let player_radius = crate::survivor::SURVIVOR_SIZE.x / 2.0; 
             
-            if distance < projectile_radius + player_radius { 
-                if player_component.invincibility_timer.finished() { 
-                    sound_event_writer.send(PlaySoundEvent(SoundEffect::SurvivorHit));
-                    let mut damage_to_take = projectile_damage.0;
-
-                    if let Some(ref mut shield) = opt_active_shield {
-                        if shield.amount > 0 {
-                            let damage_absorbed = damage_to_take.min(shield.amount);
-                            shield.amount -= damage_absorbed;
-                            damage_to_take -= damage_absorbed;
-
-                            if shield.amount <= 0 {
-                                commands.entity(player_entity).remove::<ActiveShield>();
-                            }
-                        }
-                    }
-
-                    if damage_to_take > 0 {
-                        player_health.0 -= damage_to_take;
-                    }
-                    player_component.invincibility_timer.reset(); 
-                } 
+            if distance < projectile_data.radius + player_radius { 
+                damage_survivor_from_horror_attack(&mut commands, &mut player, projectile_damage.0, projectile_data.source_type, &mut reports);
                 commands.entity(projectile_entity).despawn_recursive(); 
             } 
         } 
     } 
 }
+
+fn homing_projectile_steering_system(time: Res<Time>, mut homing_query: Query<(&mut Transform, &mut Velocity, &HomingProjectile)>, player_query: Query<&Transform, (With<Survivor>, Without<HomingProjectile>)>,) {
+    let Ok(player_transform) = player_query.get_single() else { return; };
+    let player_pos = player_transform.translation.truncate();
+    for (mut transform, mut velocity, homing) in homing_query.iter_mut() {
+        let desired = (player_pos - transform.translation.truncate()).normalize_or_zero();
+        let current = velocity.0.normalize_or_zero();
+        if desired == Vec2::ZERO || current == Vec2::ZERO { continue; }
+        let max_turn = homing.turn_rate * time.delta_seconds();
+        let turn = current.angle_between(desired).clamp(-max_turn, max_turn);
+        velocity.0 = Vec2::from_angle(turn).rotate(velocity.0);
+        transform.rotation = Quat::from_rotation_z(velocity.0.y.atan2(velocity.0.x));
+    }
+}
+
+fn horror_ground_telegraph_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut telegraph_query: Query<(Entity, &Transform, &mut HorrorGroundTelegraph, &mut Sprite)>,
+    mut player_query: Query<SurvivorAttackTarget>,
+    mut reports: SurvivorHitReports,
+) {
+    let mut player = player_query.get_single_mut().ok();
+    for (telegraph_entity, telegraph_transform, mut telegraph, mut sprite) in telegraph_query.iter_mut() {
+        telegraph.timer.tick(time.delta());
+        // The warning circle darkens as the eruption nears.
+        sprite.color.set_a(GROUND_TELEGRAPH_COLOR.a() + telegraph.timer.fraction() * 0.45);
+        if !telegraph.timer.just_finished() { continue; }
+        commands.entity(telegraph_entity).despawn_recursive();
+        let Some(player) = player.as_mut() else { continue; };
+        let player_radius = crate::survivor::SURVIVOR_SIZE.x / 2.0;
+        if player.global_transform.translation().truncate().distance(telegraph_transform.translation.truncate()) < telegraph.radius + player_radius {
+            damage_survivor_from_horror_attack(&mut commands, player, telegraph.damage, telegraph.source_type, &mut reports);
+        }
+    }
+}
 fn horror_projectile_lifetime_system(mut commands: Commands, time: Res<Time>, mut query: Query<(Entity, &mut Lifetime), With<HorrorProjectile>>,) { for (entity, mut lifetime) in query.iter_mut() { lifetime.timer.tick(time.delta()); if lifetime.timer.just_finished() { commands.entity(entity).despawn_recursive(); } } }
 
+/// Run-wide bookkeeping fed by every horror kill.
+#[derive(SystemParam)]
+struct KillTally<'w> {
+    game_state: ResMut<'w, GameState>,
+    run_stats: ResMut<'w, RunStatistics>,
+    killed: EventWriter<'w, HorrorKilledEvent>,
+    run_modifiers: Res<'w, RunModifiers>,
+}
+
+impl KillTally<'_> {
+    fn record(&mut self, horror: &Horror, position: Vec3) {
+        self.game_state.score += (((horror.xp_value / 2) as f32) * self.run_modifiers.reward_multiplier).round() as u32;
+        self.run_stats.record_kill(horror.horror_type);
+        self.killed.send(HorrorKilledEvent { horror_type: horror.horror_type, is_elite: horror.is_elite, position });
+    }
+}
+
+/// The item library as this profile sees it: relics still locked behind achievements never drop.
+#[derive(SystemParam)]
+struct UnlockedItems<'w> {
+    library: Res<'w, ItemLibrary>,
+    locks: Res<'w, ContentLocks>,
+}
+
+impl UnlockedItems<'_> {
+    fn choose_drop(&self, rng: &mut impl Rng) -> Option<&ItemDefinition> {
+        let droppable: Vec<_> = self.library.items.iter().filter(|def| !self.locks.items.contains(&def.id)).collect();
+        droppable.choose(rng).copied()
+    }
+}
+
 fn handle_horror_death_drops(
     mut commands: Commands, 
     dead_horrors_query: Query<(Entity, &Transform, &Health, &Horror), Changed<Health>>, 
     asset_server: Res<AssetServer>, 
-    mut game_state: ResMut<GameState>, 
-    item_library: Res<ItemLibrary>, 
-    mut sound_event_writer: EventWriter<PlaySoundEvent>, 
+    mut kill_tally: KillTally,
+    items: UnlockedItems,
+    mut sound_event_writer: EventWriter<PlaySoundAtEvent>, 
     player_query: Query<(Entity, &Survivor)>,
 ) {
     let Ok((player_entity, player_data)) = player_query.get_single() else { return; };
     let mut rng = rand::thread_rng();
     for (entity, transform, health, horror_data) in dead_horrors_query.iter() {
         if health.0 <= 0 {
-            sound_event_writer.send(PlaySoundEvent(SoundEffect::HorrorDeath));
-            game_state.score += horror_data.xp_value / 2; 
+            sound_event_writer.send(PlaySoundAtEvent(SoundEffect::HorrorDeath, transform.translation));
+            kill_tally.record(horror_data, transform.translation);
             spawn_echoing_soul(&mut commands, &asset_server, transform.translation, horror_data.xp_value);
             
             if rng.gen_bool(horror_data.item_drop_chance) {
-                if !item_library.items.is_empty() {
-                    if let Some(item_to_drop_def) = item_library.items.choose(&mut rng) {
-                        commands.spawn((
-                            SpriteBundle {
-                                texture: asset_server.load("sprites/eldritch_relic_placeholder.png"), 
-                                sprite: Sprite { custom_size: Some(ITEM_DROP_SIZE), ..default() },
-                                transform: Transform::from_translation(transform.translation.truncate().extend(0.4)), 
-                                ..default()
-                            },
-                            ItemDrop { item_id: item_to_drop_def.id },
-                            Name::new(format!("ItemDrop_{}", item_to_drop_def.name)),
-                        ));
-                    } 
+                if let Some(item_to_drop_def) = items.choose_drop(&mut rng) {
+                    commands.spawn((
+                        SpriteBundle {
+                            texture: asset_server.load("sprites/eldritch_relic_placeholder.png"), 
+                            sprite: Sprite { custom_size: Some(ITEM_DROP_SIZE), ..default() },
+                            transform: Transform::from_translation(transform.translation.truncate().extend(0.4)), 
+                            ..default()
+                        },
+                        ItemDrop { item_id: item_to_drop_def.id },
+                        Name::new(format!("ItemDrop_{}", item_to_drop_def.name)),
+                    ));
                 } 
             } 
 
             for item_id in player_data.collected_item_ids.iter() {
-                if let Some(item_def) = item_library.get_item_definition(*item_id) {
+                if let Some(item_def) = items.library.get_item_definition(*item_id) {
                     for effect in &item_def.effects {
                         if let ItemEffect::OnHorrorKillTrigger { chance, effect: kill_effect_type } = effect {
                             if rng.gen_bool((*chance).into()) {
@@ -408,4 +816,38 @@ fn handle_horror_death_drops(
 
 fn update_horror_count_system_in_game_state(mut game_state: ResMut<crate::game::GameState>, horror_query: Query<(), With<Horror>>,) { 
     game_state.horror_count = horror_query.iter().count() as u32; 
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use crate::items::AutomaticWeaponLibrary;
+
+    #[test]
+    fn ground_telegraph_only_damages_the_survivor_once_its_windup_ends() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Events<PlaySoundAtEvent>>();
+        world.init_resource::<Events<CameraShakeEvent>>();
+        world.init_resource::<RunStatistics>();
+        world.init_resource::<AutomaticWeaponLibrary>();
+        let mut survivor = world.run_system_once(|library: Res<AutomaticWeaponLibrary>| Survivor::new_with_skills_and_items(Vec::new(), Vec::new(), None, &library));
+        survivor.invincibility_timer.tick(Duration::from_secs_f32(crate::survivor::SURVIVOR_HIT_INVINCIBILITY_SECS));
+        let player = world.spawn((survivor, Health(100), GlobalTransform::IDENTITY)).id();
+        let damage = 15;
+        let telegraph = world.spawn((
+            SpriteBundle { sprite: Sprite { color: GROUND_TELEGRAPH_COLOR, ..default() }, transform: Transform::from_xyz(30.0, 0.0, 0.0), ..default() },
+            HorrorGroundTelegraph { source_type: HorrorType::FloatingEyeball, radius: 70.0, damage, timer: Timer::from_seconds(1.2, TimerMode::Once) },
+        )).id();
+
+        world.resource_mut::<Time>().advance_by(Duration::from_secs_f32(0.5));
+        world.run_system_once(horror_ground_telegraph_system);
+        assert_eq!(world.get::<Health>(player).unwrap().0, 100);
+        assert!(world.get_entity(telegraph).is_some());
+
+        world.resource_mut::<Time>().advance_by(Duration::from_secs_f32(0.8));
+        world.run_system_once(horror_ground_telegraph_system);
+        assert_eq!(world.get::<Health>(player).unwrap().0, 100 - damage);
+        assert!(world.get_entity(telegraph).is_none());
+    }
+}
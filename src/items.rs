@@ -5,10 +5,11 @@ use crate::{
     components::{Health as ComponentHealth, Health},
     game::{AppState, ItemCollectedEvent},
     horror::Horror,
-    visual_effects::spawn_damage_text,
-    audio::{PlaySoundEvent, SoundEffect},
+    audio::{PlaySoundAtEvent, SoundEffect},
     skills::{SkillId, SkillLibrary, ActiveSkillInstance},
     weapons::{CircleOfWarding, SwarmOfNightmares},
+    run_statistics::{DamageDealtEvent, DamageSource},
+    elite_affixes::{AffixWard, apply_horror_damage},
 };
 
 // --- Standard Items (Relics) ---
@@ -34,6 +35,9 @@ pub enum ItemEffect {
     GrantSpecificSkill { skill_id: SkillId, },
     ActivateCircleOfWarding { base_damage: i32, base_radius: f32, base_tick_interval: f32 },
     ActivateSwarmOfNightmares { num_larvae: u32, base_damage: i32, base_orbit_radius: f32, base_rotation_speed: f32 },
+    CriticalStatBoost { crit_chance_increase: f32, crit_multiplier_increase: f32, },
+    /// Crits from weapons and skills may detonate, dealing a fraction of the crit's damage around the target.
+    OnCriticalHitExplode { chance: f32, explosion_damage_fraction: f32, explosion_radius: f32, explosion_color: Color, },
 }
 
 #[derive(Debug, Clone, Reflect)]
@@ -46,8 +50,8 @@ impl ItemLibrary { pub fn get_item_definition(&self, id: ItemId) -> Option<&Item
 #[derive(Component, Debug)] pub struct ItemDrop { pub item_id: ItemId, }
 pub const ITEM_DROP_SIZE: Vec2 = Vec2::new(24.0, 24.0);
 
-#[derive(Component, Reflect, Default, Debug)] #[reflect(Component)]
-pub struct ExplosionEffect { pub damage: i32, pub radius_sq: f32, pub timer: Timer, pub already_hit_entities: Vec<Entity>, }
+#[derive(Component, Reflect, Debug)] #[reflect(Component)]
+pub struct ExplosionEffect { pub damage: i32, pub radius_sq: f32, pub timer: Timer, pub already_hit_entities: Vec<Entity>, pub source: DamageSource, }
 #[derive(Component, Reflect, Default, Debug)] #[reflect(Component)]
 pub struct RetaliationNovaEffect { pub damage: i32, pub radius_sq: f32, pub timer: Timer, pub already_hit_entities: Vec<Entity>, }
 #[derive(Component, Reflect, Default, Debug)] #[reflect(Component)]
@@ -67,6 +71,10 @@ pub struct AutomaticWeaponDefinition {
     pub base_piercing: u32,
     pub additional_projectiles: u32,
     pub base_chains: u32, // New field for chain lightning
+    pub base_knockback: f32,
+    /// Replace the survivor's base crit chance and multiplier for this weapon; crit bonuses still apply on top.
+    pub crit_chance_override: Option<f32>,
+    pub crit_multiplier_override: Option<f32>,
 
     pub projectile_sprite_path: &'static str,
     pub projectile_size: Vec2,
@@ -74,16 +82,30 @@ pub struct AutomaticWeaponDefinition {
     pub projectile_lifetime_secs: f32,
 }
 
+/// A weapon that transforms into a stronger one once its catalyst relic is held.
+#[derive(Debug, Clone, Reflect)]
+pub struct WeaponEvolution {
+    pub base_weapon: AutomaticWeaponId,
+    pub catalyst_item: ItemId,
+    pub evolved_weapon: AutomaticWeaponId,
+}
+
 #[derive(Resource, Default, Reflect)]
 #[reflect(Resource)]
 pub struct AutomaticWeaponLibrary {
     pub weapons: Vec<AutomaticWeaponDefinition>,
+    pub evolutions: Vec<WeaponEvolution>,
 }
 
 impl AutomaticWeaponLibrary {
     pub fn get_weapon_definition(&self, id: AutomaticWeaponId) -> Option<&AutomaticWeaponDefinition> {
         self.weapons.iter().find(|def| def.id == id)
     }
+
+    pub fn available_evolution(&self, equipped_weapon: Option<AutomaticWeaponId>, collected_items: &[ItemId]) -> Option<&WeaponEvolution> {
+        let equipped_weapon = equipped_weapon?;
+        self.evolutions.iter().find(|evolution| evolution.base_weapon == equipped_weapon && collected_items.contains(&evolution.catalyst_item))
+    }
 }
 
 
@@ -92,7 +114,7 @@ impl Plugin for ItemsPlugin {
     fn build(&self, app: &mut App) {
         app .register_type::<ItemId>() .register_type::<SurvivorTemporaryBuff>() .register_type::<ItemEffect>() .register_type::<ItemLibrary>()
             .register_type::<ExplosionEffect>() .register_type::<RetaliationNovaEffect>() .register_type::<TemporaryHealthRegenBuff>()
-            .register_type::<AutomaticWeaponId>() .register_type::<AutomaticWeaponDefinition>() .register_type::<AutomaticWeaponLibrary>()
+            .register_type::<AutomaticWeaponId>() .register_type::<AutomaticWeaponDefinition>() .register_type::<WeaponEvolution>() .register_type::<AutomaticWeaponLibrary>()
             .init_resource::<ItemLibrary>()
             .init_resource::<AutomaticWeaponLibrary>()
             .add_systems(Startup, (populate_item_library, populate_automatic_weapon_library) )
@@ -110,6 +132,9 @@ fn populate_automatic_weapon_library(mut library: ResMut<AutomaticWeaponLibrary>
         base_piercing: 0,
         additional_projectiles: 0,
         base_chains: 0, // Standard projectile
+        base_knockback: 60.0,
+        crit_chance_override: None,
+        crit_multiplier_override: None,
         projectile_sprite_path: "sprites/ichor_blast_placeholder.png",
         projectile_size: Vec2::new(10.0, 10.0),
         projectile_color: Color::rgb(0.7, 0.5, 1.0),
@@ -125,6 +150,9 @@ fn populate_automatic_weapon_library(mut library: ResMut<AutomaticWeaponLibrary>
         base_piercing: 0,
         additional_projectiles: 0,
         base_chains: 0, // Standard projectile
+        base_knockback: 15.0,
+        crit_chance_override: Some(0.03),
+        crit_multiplier_override: None,
         projectile_sprite_path: "sprites/eldritch_gatling_projectile_placeholder.png",
         projectile_size: Vec2::new(8.0, 16.0),
         projectile_color: Color::rgb(0.3, 0.9, 0.4),
@@ -140,6 +168,9 @@ fn populate_automatic_weapon_library(mut library: ResMut<AutomaticWeaponLibrary>
         base_piercing: 1,
         additional_projectiles: 0,
         base_chains: 0, // Standard projectile, but with pierce
+        base_knockback: 220.0,
+        crit_chance_override: None,
+        crit_multiplier_override: Some(2.0),
         projectile_sprite_path: "sprites/void_cannon_projectile_placeholder.png",
         projectile_size: Vec2::new(18.0, 18.0),
         projectile_color: Color::rgb(0.4, 0.1, 0.7),
@@ -155,6 +186,9 @@ fn populate_automatic_weapon_library(mut library: ResMut<AutomaticWeaponLibrary>
         base_piercing: 0, // Lightning doesn't typically pierce, it chains
         additional_projectiles: 0, // Single bolt that chains
         base_chains: 1, // Starts by jumping to 1 other enemy
+        base_knockback: 0.0,
+        crit_chance_override: None,
+        crit_multiplier_override: None,
         projectile_sprite_path: "sprites/chain_lightning_bolt_placeholder.png", // New sprite
         projectile_size: Vec2::new(12.0, 24.0), // Placeholder size
         projectile_color: Color::rgb(0.8, 0.8, 1.0), // Lightning blue/white
@@ -162,6 +196,65 @@ fn populate_automatic_weapon_library(mut library: ResMut<AutomaticWeaponLibrary>
                                         // Or, if it chains, this lifetime might apply to the last segment.
                                         // We might handle chain segment lifetime differently.
     });
+
+    // Evolved forms; only reachable through treasure chests.
+    library.weapons.push(AutomaticWeaponDefinition {
+        id: AutomaticWeaponId(4),
+        name: "Primordial Deluge".to_string(),
+        base_damage: 16,
+        base_fire_rate_secs: 0.35,
+        base_projectile_speed: 650.0,
+        base_piercing: 1,
+        additional_projectiles: 2,
+        base_chains: 0,
+        base_knockback: 80.0,
+        crit_chance_override: None,
+        crit_multiplier_override: None,
+        projectile_sprite_path: "sprites/ichor_blast_placeholder.png",
+        projectile_size: Vec2::new(14.0, 14.0),
+        projectile_color: Color::rgb(0.9, 0.4, 1.0),
+        projectile_lifetime_secs: 2.0,
+    });
+
+    library.weapons.push(AutomaticWeaponDefinition {
+        id: AutomaticWeaponId(5),
+        name: "Eldritch Maelstrom".to_string(),
+        base_damage: 8,
+        base_fire_rate_secs: 0.1,
+        base_projectile_speed: 650.0,
+        base_piercing: 1,
+        additional_projectiles: 1,
+        base_chains: 0,
+        base_knockback: 20.0,
+        crit_chance_override: Some(0.03),
+        crit_multiplier_override: None,
+        projectile_sprite_path: "sprites/eldritch_gatling_projectile_placeholder.png",
+        projectile_size: Vec2::new(10.0, 18.0),
+        projectile_color: Color::rgb(0.2, 1.0, 0.7),
+        projectile_lifetime_secs: 1.5,
+    });
+
+    library.weapons.push(AutomaticWeaponDefinition {
+        id: AutomaticWeaponId(6),
+        name: "Singularity Cannon".to_string(),
+        base_damage: 55,
+        base_fire_rate_secs: 1.0,
+        base_projectile_speed: 420.0,
+        base_piercing: 4,
+        additional_projectiles: 0,
+        base_chains: 0,
+        base_knockback: 300.0,
+        crit_chance_override: None,
+        crit_multiplier_override: Some(2.5),
+        projectile_sprite_path: "sprites/void_cannon_projectile_placeholder.png",
+        projectile_size: Vec2::new(26.0, 26.0),
+        projectile_color: Color::rgb(0.25, 0.0, 0.5),
+        projectile_lifetime_secs: 3.0,
+    });
+
+    library.evolutions.push(WeaponEvolution { base_weapon: AutomaticWeaponId(0), catalyst_item: ItemId(3), evolved_weapon: AutomaticWeaponId(4) });
+    library.evolutions.push(WeaponEvolution { base_weapon: AutomaticWeaponId(1), catalyst_item: ItemId(12), evolved_weapon: AutomaticWeaponId(5) });
+    library.evolutions.push(WeaponEvolution { base_weapon: AutomaticWeaponId(2), catalyst_item: ItemId(6), evolved_weapon: AutomaticWeaponId(6) });
 }
 
 fn populate_item_library(mut library: ResMut<ItemLibrary>) {
@@ -177,6 +270,12 @@ fn populate_item_library(mut library: ResMut<ItemLibrary>) {
     library.items.push(ItemDefinition { id: ItemId(10), name: "Glyph-Etched Wardstone".to_string(), description: "Activates a Circle of Warding, damaging nearby foes.".to_string(), effects: vec![ItemEffect::ActivateCircleOfWarding { base_damage: 3, base_radius: 75.0, base_tick_interval: 0.5, }], });
     library.items.push(ItemDefinition { id: ItemId(11), name: "Broodmother's Oculus".to_string(), description: "Summons a Swarm of Nightmares to orbit and attack enemies.".to_string(), effects: vec![ItemEffect::ActivateSwarmOfNightmares { num_larvae: 2, base_damage: 5, base_orbit_radius: 80.0, base_rotation_speed: std::f32::consts::PI / 2.0, }], });
     library.items.push(ItemDefinition { id: ItemId(12), name: "Crystalline Conduit".to_string(), description: "Increases automatic weapon damage by +3 and projectile speed by +10%.".to_string(), effects: vec![ItemEffect::PassiveStatBoost { max_health_increase: None, speed_multiplier: None, damage_increase: Some(3), xp_gain_multiplier: None, pickup_radius_increase: None, auto_weapon_projectile_speed_multiplier_increase: Some(0.10) }], });
+    library.items.push(ItemDefinition { id: ItemId(15), name: "Eye of the Unblinking".to_string(), description: "+8% critical hit chance.".to_string(), effects: vec![ItemEffect::CriticalStatBoost { crit_chance_increase: 0.08, crit_multiplier_increase: 0.0 }], });
+    library.items.push(ItemDefinition { id: ItemId(16), name: "Serrated Fang of Dagon".to_string(), description: "Critical hits deal +50% more damage.".to_string(), effects: vec![ItemEffect::CriticalStatBoost { crit_chance_increase: 0.0, crit_multiplier_increase: 0.5 }], });
+    library.items.push(ItemDefinition { id: ItemId(17), name: "Shattered Star-Lens".to_string(), description: "Critical hits have a 35% chance to burst, dealing half their damage to nearby horrors.".to_string(), effects: vec![ItemEffect::OnCriticalHitExplode { chance: 0.35, explosion_damage_fraction: 0.5, explosion_radius: 70.0, explosion_color: Color::rgba(1.0, 0.85, 0.2, 0.6), }], });
+    // Unlocked through achievements; see `ContentLocks`.
+    library.items.push(ItemDefinition { id: ItemId(13), name: "Sigil of the Unscathed".to_string(), description: "Increases Max Health by 40 and Movement Speed by 10%.".to_string(), effects: vec![ItemEffect::PassiveStatBoost { max_health_increase: Some(40), speed_multiplier: Some(1.10), damage_increase: None, xp_gain_multiplier: None, pickup_radius_increase: None, auto_weapon_projectile_speed_multiplier_increase: None }], });
+    library.items.push(ItemDefinition { id: ItemId(14), name: "Storm-Touched Conduit".to_string(), description: "Automatic weapon damage +4, and projectiles often burst with crackling energy.".to_string(), effects: vec![ItemEffect::PassiveStatBoost { max_health_increase: None, speed_multiplier: None, damage_increase: Some(4), xp_gain_multiplier: None, pickup_radius_increase: None, auto_weapon_projectile_speed_multiplier_increase: None }, ItemEffect::OnAutomaticProjectileHitExplode { chance: 0.2, explosion_damage: 18, explosion_radius: 70.0, explosion_color: Color::rgba(0.6, 0.7, 1.0, 0.6) }], });
 }
 
 fn apply_collected_item_effects_system(
@@ -211,6 +310,10 @@ fn apply_collected_item_effects_system(
                                 if let Some(projectile_speed_inc) = auto_weapon_projectile_speed_multiplier_increase { player.auto_weapon_projectile_speed_multiplier *= 1.0 + projectile_speed_inc; }
                             }
                         }
+                        ItemEffect::CriticalStatBoost { crit_chance_increase, crit_multiplier_increase } if is_new_item => {
+                            player.crit_chance = (player.crit_chance + crit_chance_increase).min(1.0);
+                            player.crit_multiplier += *crit_multiplier_increase;
+                        }
                         ItemEffect::GrantSpecificSkill { skill_id } => {
                             if is_new_item {
                                 if let Some(_skill_to_grant_def) = skill_library.get_skill_definition(*skill_id) {
@@ -260,6 +363,6 @@ fn apply_collected_item_effects_system(
     }
 }
 
-fn explosion_effect_system( mut commands: Commands, time: Res<Time>, mut explosion_query: Query<(Entity, &mut ExplosionEffect, &GlobalTransform, &mut Sprite, &mut Transform)>, mut horror_query: Query<(Entity, &GlobalTransform, &mut Health), With<Horror>>, asset_server: Res<AssetServer>, mut sound_event_writer: EventWriter<PlaySoundEvent>,) { for (explosion_entity, mut explosion, explosion_g_transform, mut sprite, mut vis_transform) in explosion_query.iter_mut() { explosion.timer.tick(time.delta()); let progress = explosion.timer.fraction(); let current_radius = explosion.radius_sq.sqrt(); vis_transform.scale = Vec3::splat(current_radius * 2.0 * progress); sprite.color.set_a(1.0 - progress); if explosion.timer.fraction() < 0.5 && !explosion.already_hit_entities.contains(&explosion_entity) { let explosion_pos = explosion_g_transform.translation().truncate(); for (horror_entity, horror_gtransform, mut horror_health) in horror_query.iter_mut() { if explosion.already_hit_entities.contains(&horror_entity) { continue; } let horror_pos = horror_gtransform.translation().truncate(); if horror_pos.distance_squared(explosion_pos) < explosion.radius_sq { horror_health.0 -= explosion.damage; spawn_damage_text(&mut commands, &asset_server, horror_gtransform.translation(), explosion.damage, &time); sound_event_writer.send(PlaySoundEvent(SoundEffect::HorrorHit)); explosion.already_hit_entities.push(horror_entity); } } if !explosion.already_hit_entities.contains(&explosion_entity){ explosion.already_hit_entities.push(explosion_entity);}} if explosion.timer.finished() { commands.entity(explosion_entity).despawn_recursive(); } } }
-fn retaliation_nova_effect_system( mut commands: Commands, time: Res<Time>, mut nova_query: Query<(Entity, &mut RetaliationNovaEffect, &GlobalTransform, &mut Sprite, &mut Transform)>, mut horror_query: Query<(Entity, &GlobalTransform, &mut Health), With<Horror>>, asset_server: Res<AssetServer>, mut sound_event_writer: EventWriter<PlaySoundEvent>,) { for (nova_entity, mut nova, nova_g_transform, mut sprite, mut vis_transform) in nova_query.iter_mut() { nova.timer.tick(time.delta()); let progress = nova.timer.fraction(); let current_radius = nova.radius_sq.sqrt(); vis_transform.scale = Vec3::splat(current_radius * 2.0 * progress); sprite.color.set_a(1.0 - progress * progress); if nova.timer.fraction() < 0.3 && !nova.already_hit_entities.contains(&nova_entity){ let nova_pos = nova_g_transform.translation().truncate(); for (horror_entity, horror_gtransform, mut horror_health) in horror_query.iter_mut() { if nova.already_hit_entities.contains(&horror_entity) { continue; } let horror_pos = horror_gtransform.translation().truncate(); if horror_pos.distance_squared(nova_pos) < nova.radius_sq { horror_health.0 -= nova.damage; spawn_damage_text(&mut commands, &asset_server, horror_gtransform.translation(), nova.damage, &time); sound_event_writer.send(PlaySoundEvent(SoundEffect::HorrorHit)); nova.already_hit_entities.push(horror_entity); } } if !nova.already_hit_entities.contains(&nova_entity){nova.already_hit_entities.push(nova_entity);}} if nova.timer.finished() { commands.entity(nova_entity).despawn_recursive(); } } }
+fn explosion_effect_system( mut commands: Commands, time: Res<Time>, mut explosion_query: Query<(Entity, &mut ExplosionEffect, &GlobalTransform, &mut Sprite, &mut Transform)>, mut horror_query: Query<(Entity, &GlobalTransform, &mut Health, Option<&mut AffixWard>), With<Horror>>, mut sound_event_writer: EventWriter<PlaySoundAtEvent>, mut damage_event_writer: EventWriter<DamageDealtEvent>,) { for (explosion_entity, mut explosion, explosion_g_transform, mut sprite, mut vis_transform) in explosion_query.iter_mut() { explosion.timer.tick(time.delta()); let progress = explosion.timer.fraction(); let current_radius = explosion.radius_sq.sqrt(); vis_transform.scale = Vec3::splat(current_radius * 2.0 * progress); sprite.color.set_a(1.0 - progress); if explosion.timer.fraction() < 0.5 && !explosion.already_hit_entities.contains(&explosion_entity) { let explosion_pos = explosion_g_transform.translation().truncate(); for (horror_entity, horror_gtransform, mut horror_health, mut ward_opt) in horror_query.iter_mut() { if explosion.already_hit_entities.contains(&horror_entity) { continue; } let horror_pos = horror_gtransform.translation().truncate(); if horror_pos.distance_squared(explosion_pos) < explosion.radius_sq { let dealt = apply_horror_damage(&mut horror_health, ward_opt.as_deref_mut(), explosion.damage); if dealt > 0 { damage_event_writer.send(DamageDealtEvent { source: explosion.source, amount: dealt, target: horror_entity, position: horror_gtransform.translation(), is_crit: false }); } sound_event_writer.send(PlaySoundAtEvent(SoundEffect::HorrorHit, horror_gtransform.translation())); explosion.already_hit_entities.push(horror_entity); } } if !explosion.already_hit_entities.contains(&explosion_entity){ explosion.already_hit_entities.push(explosion_entity);}} if explosion.timer.finished() { commands.entity(explosion_entity).despawn_recursive(); } } }
+fn retaliation_nova_effect_system( mut commands: Commands, time: Res<Time>, mut nova_query: Query<(Entity, &mut RetaliationNovaEffect, &GlobalTransform, &mut Sprite, &mut Transform)>, mut horror_query: Query<(Entity, &GlobalTransform, &mut Health, Option<&mut AffixWard>), With<Horror>>, mut sound_event_writer: EventWriter<PlaySoundAtEvent>, mut damage_event_writer: EventWriter<DamageDealtEvent>,) { for (nova_entity, mut nova, nova_g_transform, mut sprite, mut vis_transform) in nova_query.iter_mut() { nova.timer.tick(time.delta()); let progress = nova.timer.fraction(); let current_radius = nova.radius_sq.sqrt(); vis_transform.scale = Vec3::splat(current_radius * 2.0 * progress); sprite.color.set_a(1.0 - progress * progress); if nova.timer.fraction() < 0.3 && !nova.already_hit_entities.contains(&nova_entity){ let nova_pos = nova_g_transform.translation().truncate(); for (horror_entity, horror_gtransform, mut horror_health, mut ward_opt) in horror_query.iter_mut() { if nova.already_hit_entities.contains(&horror_entity) { continue; } let horror_pos = horror_gtransform.translation().truncate(); if horror_pos.distance_squared(nova_pos) < nova.radius_sq { let dealt = apply_horror_damage(&mut horror_health, ward_opt.as_deref_mut(), nova.damage); if dealt > 0 { damage_event_writer.send(DamageDealtEvent { source: DamageSource::RetaliationNova, amount: dealt, target: horror_entity, position: horror_gtransform.translation(), is_crit: false }); } sound_event_writer.send(PlaySoundAtEvent(SoundEffect::HorrorHit, horror_gtransform.translation())); nova.already_hit_entities.push(horror_entity); } } if !nova.already_hit_entities.contains(&nova_entity){nova.already_hit_entities.push(nova_entity);}} if nova.timer.finished() { commands.entity(nova_entity).despawn_recursive(); } } }
 fn temporary_health_regen_buff_system( mut commands: Commands, time: Res<Time>, mut buff_query: Query<(Entity, &mut TemporaryHealthRegenBuff, &Survivor, &mut ComponentHealth)>,) { for (entity, mut buff, survivor_stats, mut health_component) in buff_query.iter_mut() { buff.duration_timer.tick(time.delta()); if buff.duration_timer.finished() { commands.entity(entity).remove::<TemporaryHealthRegenBuff>(); } else { let regen_amount = buff.regen_per_second * time.delta().as_secs_f32(); health_component.0 = (health_component.0 as f32 + regen_amount).round() as i32; health_component.0 = health_component.0.min(survivor_stats.max_health); } } }
\ No newline at end of file
@@ -3,6 +3,7 @@ use crate::{
     survivor::Survivor, // Changed
     horror::Horror,     // Changed
     game::AppState, 
+    camera_systems::{CameraZoomPunchEvent, LEVEL_UP_PUNCH_ZOOM},
 };
 
 const LEVEL_UP_WAVE_DURATION_SECONDS: f32 = 0.75; 
@@ -33,9 +34,11 @@ fn spawn_level_up_wave_effect(
     player_query: Query<&Transform, With<Survivor>>, // Changed
     time: Res<Time>, 
     asset_server: Res<AssetServer>,
+    mut zoom_punch_writer: EventWriter<CameraZoomPunchEvent>,
 ) {
     if let Ok(player_transform) = player_query.get_single() {
         let player_position = player_transform.translation.truncate();
+        zoom_punch_writer.send(CameraZoomPunchEvent { amount: LEVEL_UP_PUNCH_ZOOM });
         
         commands.spawn((
             LevelUpWaveEffect {
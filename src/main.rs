@@ -18,6 +18,22 @@ mod background;
 mod debug_menu;
 mod skills;
 mod items;
+mod pathfinding;
+mod run_statistics;
+mod achievements;
+mod archetypes;
+mod omens;
+mod treasure_chests;
+mod pickups;
+mod xp_curve;
+mod merchant;
+mod elite_affixes;
+mod health_bars;
+mod dodge;
+mod minimap;
+mod music;
+mod damage_numbers;
+mod critical_hits;
 // mod glyphs; // Commented out
 
 use survivor::SurvivorPlugin;
@@ -31,6 +47,22 @@ use audio::GameAudioPlugin;
 use camera_systems::{CameraSystemsPlugin, MainCamera};
 use background::BackgroundPlugin;
 use skills::SkillsPlugin;
+use pathfinding::PathfindingPlugin;
+use run_statistics::RunStatisticsPlugin;
+use achievements::AchievementsPlugin;
+use archetypes::ArchetypesPlugin;
+use omens::OmensPlugin;
+use treasure_chests::TreasureChestsPlugin;
+use pickups::PickupsPlugin;
+use xp_curve::XpCurvePlugin;
+use merchant::MerchantPlugin;
+use elite_affixes::EliteAffixesPlugin;
+use health_bars::HealthBarsPlugin;
+use dodge::DodgePlugin;
+use minimap::MinimapPlugin;
+use music::AdaptiveMusicPlugin;
+use damage_numbers::DamageNumbersPlugin;
+use critical_hits::CriticalHitsPlugin;
 use items::{ItemsPlugin, AutomaticWeaponLibrary, AutomaticWeaponDefinition, AutomaticWeaponId};
 // use glyphs::GlyphsPlugin; // Commented out
 
@@ -62,9 +94,12 @@ fn main() {
             BackgroundPlugin,
             SkillsPlugin,
             ItemsPlugin,
+            PathfindingPlugin,
+            RunStatisticsPlugin,
             // GlyphsPlugin, // Commented out
             HanabiPlugin, // Add the HanabiPlugin here
         ))
+        .add_plugins((AchievementsPlugin, ArchetypesPlugin, OmensPlugin, TreasureChestsPlugin, PickupsPlugin, XpCurvePlugin, MerchantPlugin, EliteAffixesPlugin, HealthBarsPlugin, DodgePlugin, MinimapPlugin, AdaptiveMusicPlugin, DamageNumbersPlugin, CriticalHitsPlugin))
         .add_systems(Startup, setup_global_camera)
         .run();
 }
@@ -0,0 +1,389 @@
+use bevy::{prelude::*, ecs::system::SystemParam};
+use rand::{Rng, seq::SliceRandom};
+use crate::{
+    game::{AppState, OnSessionEnd, GameState, UpgradeChosenEvent, ItemCollectedEvent},
+    survivor::{Survivor, MAX_ACTIVE_SKILLS},
+    horror::HorrorKilledEvent,
+    items::{ItemId, ItemLibrary},
+    skills::{SkillId, SkillLibrary, ActiveSkillInstance},
+    upgrades::{UpgradePool, UpgradeCard, SurvivorUpgradeContext},
+    weapons::{CircleOfWarding, SwarmOfNightmares},
+    achievements::ContentLocks,
+    audio::{PlaySoundEvent, PlaySoundAtEvent, SoundEffect},
+};
+
+const GOLD_DROP_CHANCE: f64 = 0.08;
+const GOLD_BASE_VALUE: u32 = 3;
+const ELITE_GOLD_MULTIPLIER: u32 = 5;
+const GOLD_COIN_SIZE: Vec2 = Vec2::new(12.0, 12.0);
+const GOLD_COLLECTION_RADIUS: f32 = 32.0;
+
+/// The merchant turns up every this many cycles.
+const MERCHANT_CYCLE_INTERVAL: u32 = 3;
+const MERCHANT_SIZE: Vec2 = Vec2::new(48.0, 60.0);
+const MERCHANT_SPAWN_DISTANCE: f32 = 260.0;
+const MERCHANT_INTERACT_RADIUS: f32 = 55.0;
+const MERCHANT_STAY_SECS: f32 = 45.0;
+
+const SHOP_OFFER_COUNT: usize = 4;
+const RELIC_BASE_PRICE: u32 = 40;
+const SKILL_BASE_PRICE: u32 = 50;
+const WEAPON_LEVEL_BASE_PRICE: u32 = 25;
+const REROLL_BASE_PRICE: u32 = 10;
+/// Prices grow by this fraction per cycle survived.
+const PRICE_GROWTH_PER_CYCLE: f32 = 0.2;
+const SHOP_KEYS: [KeyCode; SHOP_OFFER_COUNT] = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4];
+
+#[derive(Component)]
+pub struct GoldCoin { pub value: u32, }
+
+#[derive(Component)]
+pub struct WanderingMerchant {
+    pub departure_timer: Timer,
+    /// Set when the shop closes so it doesn't reopen until the survivor steps away.
+    pub awaiting_departure_of_survivor: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum ShopOfferKind {
+    Relic(ItemId),
+    Skill(SkillId),
+    WeaponLevel(UpgradeCard),
+}
+
+#[derive(Debug, Clone)]
+pub struct ShopOffer { pub kind: ShopOfferKind, pub price: u32, pub sold: bool, }
+
+/// Stock of the current merchant visit. Kept while the merchant stays, so leaving and returning doesn't restock.
+#[derive(Resource, Default)]
+pub struct ShopStock { pub offers: Vec<ShopOffer>, pub reroll_count: u32, }
+
+#[derive(Resource)]
+struct MerchantSchedule { next_visit_cycle: u32, }
+impl Default for MerchantSchedule { fn default() -> Self { Self { next_visit_cycle: MERCHANT_CYCLE_INTERVAL } } }
+
+#[derive(Component)] struct ShopUI;
+#[derive(Component)]
+enum ShopText { Offer(usize), Gold, Reroll, }
+
+/// Everything a shop restock draws from.
+#[derive(SystemParam)]
+struct ShopCatalog<'w> {
+    upgrade_pool: Res<'w, UpgradePool>,
+    item_library: Res<'w, ItemLibrary>,
+    skill_library: Res<'w, SkillLibrary>,
+    content_locks: Res<'w, ContentLocks>,
+}
+
+impl ShopCatalog<'_> {
+    fn roll_offers(&self, survivor: &Survivor, opt_circle: Option<&CircleOfWarding>, opt_swarm: Option<&SwarmOfNightmares>, cycle_number: u32) -> Vec<ShopOffer> {
+        let context = SurvivorUpgradeContext {
+            is_circle_of_warding_active: opt_circle.is_some_and(|circle| circle.is_active),
+            is_swarm_of_nightmares_active: opt_swarm.is_some_and(|swarm| swarm.is_active),
+            equipped_skills: &survivor.equipped_skills,
+            current_weapon_id: survivor.equipped_weapon_id,
+            choice_limit: None,
+        };
+        roll_shop_offers(survivor, &context, &self.upgrade_pool, &self.item_library, &self.skill_library, &self.content_locks, cycle_number)
+    }
+}
+
+/// Where a purchase is delivered.
+#[derive(SystemParam)]
+struct ShopPurchaseWriters<'w> {
+    upgrade_chosen: EventWriter<'w, UpgradeChosenEvent>,
+    item_collected: EventWriter<'w, ItemCollectedEvent>,
+    sound: EventWriter<'w, PlaySoundEvent>,
+}
+
+pub struct MerchantPlugin;
+
+impl Plugin for MerchantPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<ShopStock>()
+            .init_resource::<MerchantSchedule>()
+            .add_systems(OnExit(AppState::MainMenu), reset_merchant_schedule)
+            .add_systems(Update, (
+                spawn_gold_on_horror_death_system,
+                gold_collection_system,
+                merchant_arrival_system,
+                merchant_departure_system,
+                merchant_interaction_system,
+            ).chain().run_if(in_state(AppState::InGame)))
+            .add_systems(OnEnter(AppState::Shop), setup_shop_ui)
+            .add_systems(Update, (
+                shop_input_system,
+                update_shop_ui,
+            ).chain().run_if(in_state(AppState::Shop)))
+            .add_systems(OnExit(AppState::Shop), despawn_shop_ui)
+            .add_systems(OnSessionEnd, despawn_gold_and_merchant);
+    }
+}
+
+pub fn scaled_price(base_price: u32, cycle_number: u32) -> u32 {
+    (base_price as f32 * (1.0 + cycle_number.saturating_sub(1) as f32 * PRICE_GROWTH_PER_CYCLE)).round() as u32
+}
+
+fn reroll_price(stock: &ShopStock, cycle_number: u32) -> u32 { scaled_price(REROLL_BASE_PRICE, cycle_number) * (stock.reroll_count + 1) }
+
+fn reset_merchant_schedule(mut schedule: ResMut<MerchantSchedule>, mut stock: ResMut<ShopStock>) {
+    *schedule = MerchantSchedule::default();
+    *stock = ShopStock::default();
+}
+
+fn spawn_gold_on_horror_death_system(mut commands: Commands, asset_server: Res<AssetServer>, game_state: Res<GameState>, mut kill_events: EventReader<HorrorKilledEvent>) {
+    let mut rng = rand::thread_rng();
+    for event in kill_events.read() {
+        if !event.is_elite && !rng.gen_bool(GOLD_DROP_CHANCE) { continue; }
+        let base_value = GOLD_BASE_VALUE + game_state.cycle_number;
+        let value = if event.is_elite { base_value * ELITE_GOLD_MULTIPLIER } else { base_value };
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load("sprites/gold_coin_placeholder.png"),
+                sprite: Sprite { custom_size: Some(GOLD_COIN_SIZE), color: Color::GOLD, ..default() },
+                transform: Transform::from_translation(event.position.truncate().extend(0.35)),
+                ..default()
+            },
+            GoldCoin { value },
+            Name::new("GoldCoin"),
+        ));
+    }
+}
+
+fn gold_collection_system(
+    mut commands: Commands,
+    survivor_query: Query<&Transform, With<Survivor>>,
+    coin_query: Query<(Entity, &Transform, &GoldCoin)>,
+    mut game_state: ResMut<GameState>,
+    mut sound_event_writer: EventWriter<PlaySoundAtEvent>,
+) {
+    let Ok(survivor_transform) = survivor_query.get_single() else { return; };
+    let survivor_pos = survivor_transform.translation.truncate();
+    for (coin_entity, coin_transform, coin) in coin_query.iter() {
+        if coin_transform.translation.truncate().distance(survivor_pos) < GOLD_COLLECTION_RADIUS {
+            game_state.gold += coin.value;
+            sound_event_writer.send(PlaySoundAtEvent(SoundEffect::SoulCollect, coin_transform.translation));
+            commands.entity(coin_entity).despawn_recursive();
+        }
+    }
+}
+
+fn merchant_arrival_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_state: Res<GameState>,
+    mut schedule: ResMut<MerchantSchedule>,
+    mut stock: ResMut<ShopStock>,
+    survivor_query: Query<&Transform, With<Survivor>>,
+    merchant_query: Query<(), With<WanderingMerchant>>,
+) {
+    let cycle = game_state.cycle_number;
+    if cycle < schedule.next_visit_cycle || !merchant_query.is_empty() { return; }
+    let Ok(survivor_transform) = survivor_query.get_single() else { return; };
+    schedule.next_visit_cycle = cycle + MERCHANT_CYCLE_INTERVAL;
+    // Fresh visit, fresh stock; it is rolled when the shop first opens.
+    *stock = ShopStock::default();
+
+    let angle = rand::thread_rng().gen_range(0.0..std::f32::consts::TAU);
+    let position = survivor_transform.translation.truncate() + Vec2::from_angle(angle) * MERCHANT_SPAWN_DISTANCE;
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load("sprites/wandering_merchant_placeholder.png"),
+            sprite: Sprite { custom_size: Some(MERCHANT_SIZE), ..default() },
+            transform: Transform::from_translation(position.extend(0.45)),
+            ..default()
+        },
+        WanderingMerchant { departure_timer: Timer::from_seconds(MERCHANT_STAY_SECS, TimerMode::Once), awaiting_departure_of_survivor: false },
+        Name::new("WanderingMerchant"),
+    ));
+}
+
+fn merchant_departure_system(mut commands: Commands, time: Res<Time>, mut merchant_query: Query<(Entity, &mut WanderingMerchant)>) {
+    for (entity, mut merchant) in merchant_query.iter_mut() {
+        merchant.departure_timer.tick(time.delta());
+        if merchant.departure_timer.finished() { commands.entity(entity).despawn_recursive(); }
+    }
+}
+
+fn merchant_interaction_system(
+    mut merchant_query: Query<(&Transform, &mut WanderingMerchant)>,
+    survivor_query: Query<&Transform, With<Survivor>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut sound_event_writer: EventWriter<PlaySoundEvent>,
+) {
+    if next_app_state.0.is_some() { return; }
+    let Ok(survivor_transform) = survivor_query.get_single() else { return; };
+    let survivor_pos = survivor_transform.translation.truncate();
+    for (merchant_transform, mut merchant) in merchant_query.iter_mut() {
+        let in_range = merchant_transform.translation.truncate().distance(survivor_pos) < MERCHANT_INTERACT_RADIUS;
+        if !in_range { merchant.awaiting_departure_of_survivor = false; continue; }
+        if merchant.awaiting_departure_of_survivor { continue; }
+        merchant.awaiting_departure_of_survivor = true;
+        sound_event_writer.send(PlaySoundEvent(SoundEffect::OmenAccepted));
+        next_app_state.set(AppState::Shop);
+        return;
+    }
+}
+
+fn roll_shop_offers(
+    survivor: &Survivor,
+    context: &SurvivorUpgradeContext,
+    upgrade_pool: &UpgradePool,
+    item_library: &ItemLibrary,
+    skill_library: &SkillLibrary,
+    content_locks: &ContentLocks,
+    cycle_number: u32,
+) -> Vec<ShopOffer> {
+    let mut rng = rand::thread_rng();
+    let mut candidates: Vec<ShopOffer> = Vec::new();
+    candidates.extend(item_library.items.iter()
+        .filter(|def| !content_locks.items.contains(&def.id) && !survivor.collected_item_ids.contains(&def.id))
+        .map(|def| ShopOffer { kind: ShopOfferKind::Relic(def.id), price: scaled_price(RELIC_BASE_PRICE, cycle_number), sold: false }));
+    if survivor.equipped_skills.len() < MAX_ACTIVE_SKILLS {
+        candidates.extend(skill_library.skills.iter()
+            .filter(|def| !survivor.equipped_skills.iter().any(|skill| skill.definition_id == def.id))
+            .map(|def| ShopOffer { kind: ShopOfferKind::Skill(def.id), price: scaled_price(SKILL_BASE_PRICE, cycle_number), sold: false }));
+    }
+    candidates.extend(upgrade_pool.eligible_upgrades(context).into_iter()
+        .filter(|card| card.upgrade_type.is_weapon_level_up())
+        .map(|card| ShopOffer { kind: ShopOfferKind::WeaponLevel(card.clone()), price: scaled_price(WEAPON_LEVEL_BASE_PRICE, cycle_number), sold: false }));
+    candidates.shuffle(&mut rng);
+    candidates.truncate(SHOP_OFFER_COUNT);
+    candidates
+}
+
+fn offer_label(offer: &ShopOffer, item_library: &ItemLibrary, skill_library: &SkillLibrary) -> String {
+    let (name, description) = match &offer.kind {
+        ShopOfferKind::Relic(item_id) => item_library.get_item_definition(*item_id).map_or(("Unknown Relic".to_string(), String::new()), |def| (format!("Relic: {}", def.name), def.description.clone())),
+        ShopOfferKind::Skill(skill_id) => skill_library.get_skill_definition(*skill_id).map_or(("Unknown Skill".to_string(), String::new()), |def| (format!("Skill: {}", def.name), def.description.clone())),
+        ShopOfferKind::WeaponLevel(card) => (card.name.clone(), card.description.clone()),
+    };
+    if offer.sold { format!("{} - SOLD", name) } else { format!("{} ({} gold)\n{}", name, offer.price, description) }
+}
+
+fn setup_shop_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut stock: ResMut<ShopStock>,
+    game_state: Res<GameState>,
+    survivor_query: Query<(&Survivor, Option<&CircleOfWarding>, Option<&SwarmOfNightmares>)>,
+    catalog: ShopCatalog,
+) {
+    if stock.offers.is_empty() {
+        if let Ok((survivor, opt_circle, opt_swarm)) = survivor_query.get_single() {
+            stock.offers = catalog.roll_offers(survivor, opt_circle, opt_swarm, game_state.cycle_number);
+        }
+    }
+
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands.spawn((
+        NodeBundle { style: Style { width: Val::Percent(100.0), height: Val::Percent(100.0), position_type: PositionType::Absolute, justify_content: JustifyContent::Center, align_items: AlignItems::Center, flex_direction: FlexDirection::Column, row_gap: Val::Px(16.0), ..default() }, background_color: Color::rgba(0.06, 0.05, 0.08, 0.92).into(), z_index: ZIndex::Global(10), ..default() },
+        ShopUI,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("The Wandering Merchant", TextStyle { font: font.clone(), font_size: 52.0, color: Color::GOLD }).with_text_justify(JustifyText::Center));
+        parent.spawn(TextBundle::from_section("\"Everything has a price, survivor. Everything.\"", TextStyle { font: font.clone(), font_size: 20.0, color: Color::rgba(0.75, 0.75, 0.75, 1.0) }).with_text_justify(JustifyText::Center));
+        parent.spawn((TextBundle::from_section("", TextStyle { font: font.clone(), font_size: 28.0, color: Color::GOLD }), ShopText::Gold));
+        for index in 0..SHOP_OFFER_COUNT {
+            parent.spawn((TextBundle::from_section("", TextStyle { font: font.clone(), font_size: 20.0, color: Color::WHITE }).with_text_justify(JustifyText::Center), ShopText::Offer(index)));
+        }
+        parent.spawn((TextBundle::from_section("", TextStyle { font: font.clone(), font_size: 22.0, color: Color::rgb(0.7, 0.8, 1.0) }), ShopText::Reroll));
+        parent.spawn(TextBundle::from_section("1-4 to buy  |  R to reroll  |  ESC to leave", TextStyle { font, font_size: 22.0, color: Color::rgba(0.8, 0.8, 0.8, 1.0) }).with_text_justify(JustifyText::Center));
+    });
+}
+
+fn shop_input_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut stock: ResMut<ShopStock>,
+    mut game_state: ResMut<GameState>,
+    mut survivor_query: Query<(&mut Survivor, Option<&CircleOfWarding>, Option<&SwarmOfNightmares>)>,
+    catalog: ShopCatalog,
+    mut writers: ShopPurchaseWriters,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) { next_app_state.set(AppState::InGame); return; }
+    let Ok((mut survivor, opt_circle, opt_swarm)) = survivor_query.get_single_mut() else { return; };
+
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        let price = reroll_price(&stock, game_state.cycle_number);
+        if game_state.gold < price { return; }
+        game_state.gold -= price;
+        stock.reroll_count += 1;
+        stock.offers = catalog.roll_offers(&survivor, opt_circle, opt_swarm, game_state.cycle_number);
+        writers.sound.send(PlaySoundEvent(SoundEffect::RitualCast));
+        return;
+    }
+
+    let Some(index) = SHOP_KEYS.iter().position(|key| keyboard_input.just_pressed(*key)) else { return; };
+    let Some(offer) = stock.offers.get_mut(index) else { return; };
+    if offer.sold || game_state.gold < offer.price { return; }
+    // A skill bought after the slots filled up would be wasted.
+    if matches!(offer.kind, ShopOfferKind::Skill(_)) && survivor.equipped_skills.len() >= MAX_ACTIVE_SKILLS { return; }
+
+    game_state.gold -= offer.price;
+    offer.sold = true;
+    match &offer.kind {
+        ShopOfferKind::Relic(item_id) => { writers.item_collected.send(ItemCollectedEvent(*item_id)); }
+        ShopOfferKind::Skill(skill_id) => { survivor.equipped_skills.push(ActiveSkillInstance::new(*skill_id)); }
+        ShopOfferKind::WeaponLevel(card) => { writers.upgrade_chosen.send(UpgradeChosenEvent(card.clone())); }
+    }
+    writers.sound.send(PlaySoundEvent(SoundEffect::OmenAccepted));
+}
+
+fn update_shop_ui(
+    stock: Res<ShopStock>,
+    game_state: Res<GameState>,
+    item_library: Res<ItemLibrary>,
+    skill_library: Res<SkillLibrary>,
+    mut text_query: Query<(&ShopText, &mut Text)>,
+) {
+    for (shop_text, mut text) in text_query.iter_mut() {
+        match shop_text {
+            ShopText::Offer(index) => {
+                let Some(offer) = stock.offers.get(*index) else { text.sections[0].value = String::new(); continue; };
+                text.sections[0].value = format!("[{}] {}", index + 1, offer_label(offer, &item_library, &skill_library));
+                text.sections[0].style.color = if offer.sold { Color::rgba(0.5, 0.5, 0.5, 1.0) } else if game_state.gold >= offer.price { Color::WHITE } else { Color::rgb(1.0, 0.45, 0.45) };
+            }
+            ShopText::Gold => { text.sections[0].value = format!("Gold: {}", game_state.gold); }
+            ShopText::Reroll => { text.sections[0].value = format!("Reroll: {} gold", reroll_price(&stock, game_state.cycle_number)); }
+        }
+    }
+}
+
+fn despawn_shop_ui(mut commands: Commands, query: Query<Entity, With<ShopUI>>) { for entity in query.iter() { commands.entity(entity).despawn_recursive(); } }
+
+fn despawn_gold_and_merchant(mut commands: Commands, gold_query: Query<Entity, With<GoldCoin>>, merchant_query: Query<Entity, With<WanderingMerchant>>) {
+    for entity in gold_query.iter().chain(merchant_query.iter()) { commands.entity(entity).despawn_recursive(); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaled_price_starts_at_base_in_the_first_cycle() {
+        assert_eq!(scaled_price(40, 0), 40);
+        assert_eq!(scaled_price(40, 1), 40);
+    }
+
+    #[test]
+    fn scaled_price_grows_linearly_per_cycle() {
+        assert_eq!(scaled_price(40, 2), 48);
+        assert_eq!(scaled_price(40, 6), 80);
+        assert_eq!(scaled_price(25, 3), 35);
+    }
+
+    #[test]
+    fn scaled_price_rounds_to_the_nearest_gold() {
+        assert_eq!(scaled_price(10, 2), 12);
+        assert_eq!(scaled_price(13, 2), 16);
+    }
+
+    #[test]
+    fn reroll_price_multiplies_by_rerolls_so_far() {
+        let mut stock = ShopStock::default();
+        assert_eq!(reroll_price(&stock, 1), REROLL_BASE_PRICE);
+        stock.reroll_count = 2;
+        assert_eq!(reroll_price(&stock, 1), REROLL_BASE_PRICE * 3);
+    }
+}
@@ -0,0 +1,325 @@
+use bevy::{prelude::*, utils::HashMap};
+use crate::{
+    game::{AppState, OnSessionEnd, SCREEN_WIDTH},
+    camera_systems::{MainCamera, visible_world_rect},
+    survivor::Survivor,
+    horror::{Horror, FrenziedBehemothBehavior, FrenziedBehemothState},
+    items::ItemDrop,
+    treasure_chests::TreasureChest,
+};
+
+const MINIMAP_SIZE: f32 = 160.0;
+const MINIMAP_MARGIN_RIGHT: f32 = 20.0;
+const MINIMAP_MARGIN_BOTTOM: f32 = 50.0;
+const MINIMAP_BACKGROUND_COLOR: Color = Color::rgba(0.02, 0.0, 0.05, 0.6);
+const MINIMAP_BORDER_COLOR: Color = Color::rgba(0.5, 0.4, 0.7, 0.8);
+const MINIMAP_SURVIVOR_DOT_SIZE: f32 = 6.0;
+
+const DENSITY_GRID_CELLS: usize = 16;
+const DENSITY_CELL_SIZE: f32 = MINIMAP_SIZE / DENSITY_GRID_CELLS as f32;
+/// Horrors in a single cell needed for it to be drawn at full strength.
+const DENSITY_SATURATION_COUNT: f32 = 4.0;
+const DENSITY_MAX_ALPHA: f32 = 0.75;
+
+const INDICATOR_SCREEN_MARGIN: f32 = 28.0;
+const INDICATOR_Z: f32 = 10.0;
+const INDICATOR_ARM_SIZE: Vec2 = Vec2::new(15.0, 4.0);
+const INDICATOR_PULSE_SPEED: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrackedKind { ItemDrop, Chest, Elite, Behemoth, ChargingBehemoth, }
+
+impl TrackedKind {
+    fn color(&self) -> Color {
+        match self {
+            TrackedKind::ItemDrop => Color::rgb(0.3, 0.9, 1.0),
+            TrackedKind::Chest => Color::GOLD,
+            TrackedKind::Elite => Color::rgb(1.0, 0.55, 0.1),
+            TrackedKind::Behemoth | TrackedKind::ChargingBehemoth => Color::rgb(1.0, 0.1, 0.15),
+        }
+    }
+
+    fn minimap_marker_size(&self) -> f32 {
+        match self {
+            TrackedKind::ItemDrop => 4.0,
+            TrackedKind::Chest | TrackedKind::Elite => 6.0,
+            TrackedKind::Behemoth | TrackedKind::ChargingBehemoth => 8.0,
+        }
+    }
+}
+
+/// `half_extent` is how far the map reaches from the survivor: one visible width, read from the camera each frame so
+/// the map widens with the cycle zoom. Horrors spawn up to a screen width away, so at the default zoom it just covers them.
+#[derive(Component)] struct MinimapUI { half_extent: f32, }
+#[derive(Component)] struct MinimapDensityCell { index: usize, }
+#[derive(Component)] struct MinimapViewFrame;
+#[derive(Component)] struct MinimapMarker;
+
+/// An edge-of-screen arrow pointing at something worth turning around for.
+#[derive(Component)]
+struct OffscreenIndicator { target: Entity, kind: TrackedKind, }
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame), setup_minimap_ui)
+            .add_systems(Update, (
+                update_minimap_view_frame_system,
+                update_minimap_density_system,
+                update_minimap_markers_system,
+            ).chain().run_if(in_state(AppState::InGame)))
+            // PostUpdate, so the arrows are placed against where the camera actually ended up this frame.
+            .add_systems(PostUpdate, update_offscreen_indicators_system.before(bevy::transform::TransformSystem::TransformPropagate).run_if(in_state(AppState::InGame)))
+            .add_systems(OnExit(AppState::InGame), despawn_minimap_ui)
+            .add_systems(OnSessionEnd, despawn_offscreen_indicators);
+    }
+}
+
+fn setup_minimap_ui(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(MINIMAP_MARGIN_RIGHT),
+                bottom: Val::Px(MINIMAP_MARGIN_BOTTOM),
+                width: Val::Px(MINIMAP_SIZE),
+                height: Val::Px(MINIMAP_SIZE),
+                border: UiRect::all(Val::Px(1.0)),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            background_color: MINIMAP_BACKGROUND_COLOR.into(),
+            border_color: MINIMAP_BORDER_COLOR.into(),
+            z_index: ZIndex::Global(2),
+            ..default()
+        },
+        MinimapUI { half_extent: SCREEN_WIDTH },
+        Name::new("Minimap"),
+    )).with_children(|minimap| {
+        for index in 0..DENSITY_GRID_CELLS * DENSITY_GRID_CELLS {
+            minimap.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px((index % DENSITY_GRID_CELLS) as f32 * DENSITY_CELL_SIZE),
+                        top: Val::Px((index / DENSITY_GRID_CELLS) as f32 * DENSITY_CELL_SIZE),
+                        width: Val::Px(DENSITY_CELL_SIZE),
+                        height: Val::Px(DENSITY_CELL_SIZE),
+                        ..default()
+                    },
+                    background_color: Color::NONE.into(),
+                    ..default()
+                },
+                MinimapDensityCell { index },
+            ));
+        }
+        minimap.spawn((
+            NodeBundle {
+                style: Style { position_type: PositionType::Absolute, border: UiRect::all(Val::Px(1.0)), ..default() },
+                border_color: Color::rgba(1.0, 1.0, 1.0, 0.35).into(),
+                ..default()
+            },
+            MinimapViewFrame,
+        ));
+        minimap.spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px((MINIMAP_SIZE - MINIMAP_SURVIVOR_DOT_SIZE) / 2.0),
+                top: Val::Px((MINIMAP_SIZE - MINIMAP_SURVIVOR_DOT_SIZE) / 2.0),
+                width: Val::Px(MINIMAP_SURVIVOR_DOT_SIZE),
+                height: Val::Px(MINIMAP_SURVIVOR_DOT_SIZE),
+                ..default()
+            },
+            background_color: Color::WHITE.into(),
+            ..default()
+        });
+    });
+}
+
+/// World offset from the survivor to a pixel position inside the minimap, pinned to the rim when out of range.
+fn minimap_position(offset: Vec2, half_extent: f32) -> Vec2 {
+    let clamped = offset.clamp(Vec2::splat(-half_extent), Vec2::splat(half_extent));
+    let scale = MINIMAP_SIZE / (2.0 * half_extent);
+    Vec2::new(MINIMAP_SIZE / 2.0 + clamped.x * scale, MINIMAP_SIZE / 2.0 - clamped.y * scale)
+}
+
+fn horror_kind(horror: &Horror, behemoth: Option<&FrenziedBehemothBehavior>) -> Option<TrackedKind> {
+    // The behemoth is the closest thing the horde has to a boss, so it always gets the loudest marker.
+    match behemoth {
+        Some(behemoth) if behemoth.state == FrenziedBehemothState::Telegraphing => Some(TrackedKind::ChargingBehemoth),
+        Some(_) => Some(TrackedKind::Behemoth),
+        None if horror.is_elite => Some(TrackedKind::Elite),
+        None => None,
+    }
+}
+
+fn update_minimap_view_frame_system(
+    survivor_query: Query<&Transform, With<Survivor>>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+    mut minimap_query: Query<&mut MinimapUI>,
+    mut view_frame_query: Query<&mut Style, With<MinimapViewFrame>>,
+) {
+    let (Ok(survivor_transform), Ok((camera_transform, projection)), Ok(mut minimap)) = (survivor_query.get_single(), camera_query.get_single(), minimap_query.get_single_mut()) else { return; };
+    let visible = visible_world_rect(camera_transform, projection);
+    minimap.half_extent = visible.width().max(1.0);
+    let Ok(mut frame_style) = view_frame_query.get_single_mut() else { return; };
+    let frame_size = visible.size() * MINIMAP_SIZE / (2.0 * minimap.half_extent);
+    let frame_center = minimap_position(visible.center() - survivor_transform.translation.truncate(), minimap.half_extent);
+    frame_style.left = Val::Px(frame_center.x - frame_size.x / 2.0);
+    frame_style.top = Val::Px(frame_center.y - frame_size.y / 2.0);
+    frame_style.width = Val::Px(frame_size.x);
+    frame_style.height = Val::Px(frame_size.y);
+}
+
+/// Shades the grid by how many ordinary horrors stand in each cell. Elites and behemoths get markers instead.
+fn update_minimap_density_system(
+    survivor_query: Query<&Transform, With<Survivor>>,
+    horror_query: Query<(&Transform, &Horror, Option<&FrenziedBehemothBehavior>)>,
+    minimap_query: Query<&MinimapUI>,
+    mut cell_query: Query<(&MinimapDensityCell, &mut BackgroundColor)>,
+) {
+    let (Ok(survivor_transform), Ok(minimap)) = (survivor_query.get_single(), minimap_query.get_single()) else { return; };
+    let survivor_pos = survivor_transform.translation.truncate();
+    let mut density = [0u32; DENSITY_GRID_CELLS * DENSITY_GRID_CELLS];
+    for (transform, horror, behemoth) in horror_query.iter() {
+        if horror_kind(horror, behemoth).is_some() { continue; }
+        let offset = transform.translation.truncate() - survivor_pos;
+        if offset.x.abs() >= minimap.half_extent || offset.y.abs() >= minimap.half_extent { continue; }
+        let cell = (minimap_position(offset, minimap.half_extent) / DENSITY_CELL_SIZE).floor();
+        let (column, row) = ((cell.x as usize).min(DENSITY_GRID_CELLS - 1), (cell.y as usize).min(DENSITY_GRID_CELLS - 1));
+        density[row * DENSITY_GRID_CELLS + column] += 1;
+    }
+    for (cell, mut background) in cell_query.iter_mut() {
+        let strength = (density[cell.index] as f32 / DENSITY_SATURATION_COUNT).min(1.0);
+        *background = Color::rgba(0.85, 0.1, 0.2, strength * DENSITY_MAX_ALPHA).into();
+    }
+}
+
+fn update_minimap_markers_system(
+    mut commands: Commands,
+    survivor_query: Query<&Transform, With<Survivor>>,
+    horror_query: Query<(&Transform, &Horror, Option<&FrenziedBehemothBehavior>)>,
+    item_drop_query: Query<&Transform, With<ItemDrop>>,
+    chest_query: Query<&Transform, With<TreasureChest>>,
+    minimap_query: Query<(Entity, &MinimapUI)>,
+    mut marker_query: Query<(&mut Style, &mut BackgroundColor), With<MinimapMarker>>,
+) {
+    let Ok(survivor_transform) = survivor_query.get_single() else { return; };
+    let Ok((minimap_entity, minimap)) = minimap_query.get_single() else { return; };
+    let survivor_pos = survivor_transform.translation.truncate();
+
+    let mut markers: Vec<(Vec2, TrackedKind)> = Vec::new();
+    for transform in item_drop_query.iter() { markers.push((transform.translation.truncate() - survivor_pos, TrackedKind::ItemDrop)); }
+    for transform in chest_query.iter() { markers.push((transform.translation.truncate() - survivor_pos, TrackedKind::Chest)); }
+    for (transform, horror, behemoth) in horror_query.iter() {
+        if let Some(kind) = horror_kind(horror, behemoth) { markers.push((transform.translation.truncate() - survivor_pos, kind)); }
+    }
+    // Later markers are drawn on top, so the biggest threats go last.
+    markers.sort_by_key(|(_, kind)| *kind as u8);
+
+    let mut pending_markers = markers.iter();
+    for (mut style, mut background) in marker_query.iter_mut() {
+        match pending_markers.next() {
+            Some((offset, kind)) => {
+                apply_marker_style(&mut style, *offset, *kind, minimap.half_extent);
+                *background = kind.color().into();
+            }
+            None => style.display = Display::None,
+        }
+    }
+    let extra_markers: Vec<_> = pending_markers.collect();
+    if extra_markers.is_empty() { return; }
+    let half_extent = minimap.half_extent;
+    commands.entity(minimap_entity).with_children(|minimap| {
+        for (offset, kind) in extra_markers {
+            let mut style = Style { position_type: PositionType::Absolute, ..default() };
+            apply_marker_style(&mut style, *offset, *kind, half_extent);
+            minimap.spawn((NodeBundle { style, background_color: kind.color().into(), ..default() }, MinimapMarker));
+        }
+    });
+}
+
+fn apply_marker_style(style: &mut Style, offset: Vec2, kind: TrackedKind, half_extent: f32) {
+    let size = kind.minimap_marker_size();
+    let position = minimap_position(offset, half_extent);
+    style.display = Display::Flex;
+    style.left = Val::Px(position.x - size / 2.0);
+    style.top = Val::Px(position.y - size / 2.0);
+    style.width = Val::Px(size);
+    style.height = Val::Px(size);
+}
+
+fn update_offscreen_indicators_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+    // Targets are read through `GlobalTransform` so they don't clash with the indicators' own `Transform`.
+    horror_query: Query<(Entity, &GlobalTransform, &Horror, Option<&FrenziedBehemothBehavior>)>,
+    item_drop_query: Query<(Entity, &GlobalTransform), With<ItemDrop>>,
+    mut indicator_query: Query<(Entity, &OffscreenIndicator, &mut Transform), Without<MainCamera>>,
+) {
+    let Ok((camera_transform, projection)) = camera_query.get_single() else { return; };
+    let visible = visible_world_rect(camera_transform, projection);
+    let camera_pos = visible.center();
+    let half_extent = (visible.half_size() - Vec2::splat(INDICATOR_SCREEN_MARGIN)).max(Vec2::ONE);
+
+    // Only the elites, item drops and behemoths winding up a charge are worth an arrow; everything else is on the minimap.
+    let mut wanted: HashMap<Entity, (Vec2, TrackedKind)> = HashMap::new();
+    for (entity, transform, horror, behemoth) in horror_query.iter() {
+        if let Some(kind @ (TrackedKind::Elite | TrackedKind::ChargingBehemoth)) = horror_kind(horror, behemoth) {
+            wanted.insert(entity, (transform.translation().truncate(), kind));
+        }
+    }
+    for (entity, transform) in item_drop_query.iter() { wanted.insert(entity, (transform.translation().truncate(), TrackedKind::ItemDrop)); }
+    wanted.retain(|_, (position, _)| {
+        let offset = *position - camera_pos;
+        offset.x.abs() > half_extent.x || offset.y.abs() > half_extent.y
+    });
+
+    let pulse = 1.0 + 0.2 * (time.elapsed_seconds() * INDICATOR_PULSE_SPEED).sin();
+    for (indicator_entity, indicator, mut transform) in indicator_query.iter_mut() {
+        match wanted.remove(&indicator.target) {
+            Some((position, kind)) if kind == indicator.kind => {
+                *transform = indicator_transform(camera_pos, half_extent, position, kind, pulse);
+            }
+            _ => commands.entity(indicator_entity).despawn_recursive(),
+        }
+    }
+    for (target, (position, kind)) in wanted {
+        spawn_offscreen_indicator(&mut commands, target, kind, indicator_transform(camera_pos, half_extent, position, kind, pulse));
+    }
+}
+
+/// Pins the arrow to the screen edge along the line from the camera to the target, pointing outwards.
+fn indicator_transform(camera_pos: Vec2, half_extent: Vec2, target_pos: Vec2, kind: TrackedKind, pulse: f32) -> Transform {
+    let direction = target_pos - camera_pos;
+    let edge_scale = (half_extent.x / direction.x.abs().max(f32::EPSILON)).min(half_extent.y / direction.y.abs().max(f32::EPSILON));
+    let edge_pos = camera_pos + direction * edge_scale;
+    let scale = if kind == TrackedKind::ChargingBehemoth { pulse } else { 1.0 };
+    Transform::from_translation(edge_pos.extend(INDICATOR_Z))
+        .with_rotation(Quat::from_rotation_z(direction.to_angle()))
+        .with_scale(Vec3::splat(scale))
+}
+
+fn spawn_offscreen_indicator(commands: &mut Commands, target: Entity, kind: TrackedKind, transform: Transform) {
+    // A ">" chevron built from two bars, so it batches with the other untextured sprites.
+    let arm_angle = (INDICATOR_ARM_SIZE.y * 2.0).atan2(INDICATOR_ARM_SIZE.x);
+    let arm_offset = Vec2::from_angle(arm_angle) * INDICATOR_ARM_SIZE.x / 2.0;
+    commands.spawn((
+        SpatialBundle::from_transform(transform),
+        OffscreenIndicator { target, kind },
+        Name::new("OffscreenIndicator"),
+    )).with_children(|indicator| {
+        for side in [1.0, -1.0] {
+            indicator.spawn(SpriteBundle {
+                sprite: Sprite { custom_size: Some(INDICATOR_ARM_SIZE), color: kind.color(), ..default() },
+                transform: Transform::from_xyz(-arm_offset.x, side * arm_offset.y, 0.0).with_rotation(Quat::from_rotation_z(-side * arm_angle)),
+                ..default()
+            });
+        }
+    });
+}
+
+fn despawn_minimap_ui(mut commands: Commands, query: Query<Entity, With<MinimapUI>>) { for entity in query.iter() { commands.entity(entity).despawn_recursive(); } }
+fn despawn_offscreen_indicators(mut commands: Commands, query: Query<Entity, With<OffscreenIndicator>>) { for entity in query.iter() { commands.entity(entity).despawn_recursive(); } }
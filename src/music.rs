@@ -0,0 +1,179 @@
+use bevy::{prelude::*, asset::io::file::FileAssetReader, audio::{PlaybackMode, Volume}};
+use crate::{
+    game::{AppState, OnSessionEnd, SCREEN_WIDTH},
+    components::Health,
+    survivor::Survivor,
+    horror::{Horror, HorrorType},
+    audio::{AudioMixer, MixerVoice},
+};
+
+/// The old single track, looped on its own when the dedicated stems aren't shipped.
+const FALLBACK_LOOP_PATH: &str = "audio/cyclopean_ruins_ambience_placeholder.ogg";
+
+/// How quickly stems chase their target volume, per second. Slow enough that a single kill doesn't make the music lurch.
+const STEM_FADE_RATE: f32 = 1.2;
+const PRESSURE_RADIUS: f32 = 380.0;
+/// Horrors inside `PRESSURE_RADIUS` for the pulse layer to reach full volume.
+const PRESSURE_SATURATION_COUNT: f32 = 25.0;
+const BOSS_PRESENCE_RADIUS: f32 = SCREEN_WIDTH;
+const ELITE_PRESENCE: f32 = 0.5;
+/// Below this fraction of max endurance the heartbeat starts to creep in.
+const LOW_HEALTH_FRACTION: f32 = 0.4;
+
+/// How long the stems stay pulled down under the level-up cue.
+const CUE_DUCK_SECS: f32 = 1.5;
+const CUE_DUCK_VOLUME: f32 = 0.35;
+
+/// One looping stem, driven only by its volume. Each stem starts looping as soon as its own asset has loaded, so the
+/// layers are not beat-aligned; they are textures rather than parts of one arrangement. Layering only happens when every
+/// stem file is present under `assets/`; otherwise the ambience plays alone from `FALLBACK_LOOP_PATH`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicLayer {
+    /// Always playing; the bed everything else sits on.
+    Ambience,
+    /// Follows how crowded it is around the survivor.
+    Pulse,
+    /// Comes in while a behemoth or elite is close.
+    Dread,
+    /// Creeps in as the survivor runs low on endurance.
+    Heartbeat,
+}
+
+impl MusicLayer {
+    pub const ALL: [MusicLayer; 4] = [MusicLayer::Ambience, MusicLayer::Pulse, MusicLayer::Dread, MusicLayer::Heartbeat];
+
+    fn path(&self) -> &'static str {
+        match self {
+            MusicLayer::Ambience => "audio/music/ambience_stem.ogg",
+            MusicLayer::Pulse => "audio/music/pulse_stem.ogg",
+            MusicLayer::Dread => "audio/music/dread_stem.ogg",
+            MusicLayer::Heartbeat => "audio/music/heartbeat_stem.ogg",
+        }
+    }
+
+    fn max_volume(&self) -> f32 {
+        match self {
+            MusicLayer::Ambience => 0.3,
+            MusicLayer::Pulse => 0.35,
+            MusicLayer::Dread => 0.35,
+            MusicLayer::Heartbeat => 0.4,
+        }
+    }
+
+    fn target_level(&self, intensity: &MusicIntensity) -> f32 {
+        match self {
+            MusicLayer::Ambience => 1.0,
+            MusicLayer::Pulse => intensity.horror_pressure,
+            MusicLayer::Dread => intensity.boss_presence,
+            MusicLayer::Heartbeat => intensity.low_health,
+        }
+    }
+}
+
+/// Live tension readings, each 0..1. Measured while in game; held while paused so the music doesn't drop out on level-up.
+#[derive(Resource, Default, Debug)]
+pub struct MusicIntensity { pub horror_pressure: f32, pub boss_presence: f32, pub low_health: f32, }
+
+#[derive(Resource)]
+struct MusicHandles { stems: Vec<(MusicLayer, Handle<AudioSource>)>, }
+
+#[derive(Component)]
+struct MusicStem { layer: MusicLayer, level: f32, }
+
+/// Pulls the stems down so the `Revelation` cue cuts through. Game over needs no duck: the stems stop with the
+/// session and `MadnessConsumes` plays on its own.
+#[derive(Resource, Default)]
+struct MusicDuck { remaining_secs: f32, }
+
+pub struct AdaptiveMusicPlugin;
+
+impl Plugin for AdaptiveMusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicIntensity>()
+            .init_resource::<MusicDuck>()
+            .add_systems(Startup, setup_music_handles)
+            .add_systems(OnEnter(AppState::InGame), start_music_stems)
+            .add_systems(Update, measure_music_intensity_system.run_if(in_state(AppState::InGame)))
+            .add_systems(Update, crossfade_music_stems_system.after(measure_music_intensity_system))
+            .add_systems(OnEnter(AppState::LevelUp), duck_music_for_level_up)
+            .add_systems(OnSessionEnd, stop_music_stems);
+    }
+}
+
+fn setup_music_handles(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let assets_root = FileAssetReader::get_base_path().join("assets");
+    let stems = if MusicLayer::ALL.iter().all(|layer| assets_root.join(layer.path()).is_file()) {
+        MusicLayer::ALL.iter().map(|layer| (*layer, asset_server.load(layer.path()))).collect()
+    } else {
+        info!("Music stems not found under {}, looping the single ambience track instead.", assets_root.display());
+        vec![(MusicLayer::Ambience, asset_server.load(FALLBACK_LOOP_PATH))]
+    };
+    commands.insert_resource(MusicHandles { stems });
+}
+
+fn start_music_stems(mut commands: Commands, music_handles: Res<MusicHandles>, stem_query: Query<(), With<MusicStem>>) {
+    if !stem_query.is_empty() { return; }
+    for (layer, handle) in music_handles.stems.iter() {
+        commands.spawn((
+            AudioBundle {
+                source: handle.clone(),
+                // Everything starts silent and fades in, so a fresh run eases into the ambience.
+                settings: PlaybackSettings { mode: PlaybackMode::Loop, volume: Volume::new(0.0), ..default() },
+            },
+            MusicStem { layer: *layer, level: 0.0 },
+            MixerVoice::music(0.0),
+            Name::new(format!("MusicStem{:?}", layer)),
+        ));
+    }
+}
+
+fn stop_music_stems(mut commands: Commands, stem_query: Query<Entity, With<MusicStem>>, mut intensity: ResMut<MusicIntensity>) {
+    for entity in stem_query.iter() { commands.entity(entity).despawn_recursive(); }
+    *intensity = MusicIntensity::default();
+}
+
+fn measure_music_intensity_system(
+    survivor_query: Query<(&Transform, &Survivor, &Health)>,
+    horror_query: Query<(&Transform, &Horror), Without<Survivor>>,
+    mut intensity: ResMut<MusicIntensity>,
+) {
+    let Ok((survivor_transform, survivor, health)) = survivor_query.get_single() else { return; };
+    let survivor_pos = survivor_transform.translation.truncate();
+    let mut nearby_horrors = 0;
+    let mut boss_presence: f32 = 0.0;
+    for (transform, horror) in horror_query.iter() {
+        let distance = transform.translation.truncate().distance(survivor_pos);
+        if distance < PRESSURE_RADIUS { nearby_horrors += 1; }
+        if distance < BOSS_PRESENCE_RADIUS {
+            if horror.horror_type == HorrorType::FrenziedBehemoth { boss_presence = 1.0; } else if horror.is_elite { boss_presence = boss_presence.max(ELITE_PRESENCE); }
+        }
+    }
+    intensity.horror_pressure = (nearby_horrors as f32 / PRESSURE_SATURATION_COUNT).min(1.0);
+    intensity.boss_presence = boss_presence;
+    let health_fraction = health.0 as f32 / survivor.max_health.max(1) as f32;
+    intensity.low_health = ((LOW_HEALTH_FRACTION - health_fraction) / LOW_HEALTH_FRACTION).clamp(0.0, 1.0);
+}
+
+/// Runs on real time, so stems keep settling while the game is paused behind a menu.
+fn crossfade_music_stems_system(
+    real_time: Res<Time<Real>>,
+    intensity: Res<MusicIntensity>,
+    mixer: Res<AudioMixer>,
+    mut duck: ResMut<MusicDuck>,
+    mut stem_query: Query<(&mut MusicStem, &mut MixerVoice, &AudioSink)>,
+) {
+    let delta = real_time.delta_seconds();
+    duck.remaining_secs = (duck.remaining_secs - delta).max(0.0);
+    let duck_volume = if duck.remaining_secs > 0.0 { CUE_DUCK_VOLUME } else { 1.0 };
+    let blend = 1.0 - (-STEM_FADE_RATE * delta).exp();
+    for (mut stem, mut voice, sink) in stem_query.iter_mut() {
+        let target = stem.layer.target_level(&intensity) * duck_volume;
+        stem.level += (target - stem.level) * blend;
+        // Kept on the voice too, so the mixer re-applying the music bus lands on the same volume.
+        voice.base_volume = stem.level * stem.layer.max_volume();
+        sink.set_volume(voice.base_volume * mixer.bus_volume(voice.bus));
+    }
+}
+
+/// The cue itself is the `Revelation` sound sent when the level is gained; the music only makes room for it.
+fn duck_music_for_level_up(mut duck: ResMut<MusicDuck>) { duck.remaining_secs = CUE_DUCK_SECS; }
@@ -0,0 +1,236 @@
+use bevy::prelude::*;
+use crate::{
+    game::{AppState, GameState},
+    achievements::PlayerProfile,
+    run_statistics::RunStatistics,
+    audio::{PlaySoundEvent, SoundEffect},
+};
+
+const OMEN_TOGGLE_KEYS: [KeyCode; 5] = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4, KeyCode::Digit5];
+const LORE_PER_CYCLE: f32 = 5.0;
+const KILLS_PER_LORE: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Default)]
+pub struct OmenId(pub u32);
+
+/// A single rule change an omen makes. Systems never look at omens directly, only at the folded `RunModifiers`.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub enum OmenEffect {
+    HorrorSpeedMultiplier(f32),
+    EliteChanceMultiplier(f64),
+    HealthRegenMultiplier(f32),
+    UpgradeChoiceLimit(usize),
+    CycleSpeedMultiplier(f32),
+}
+
+#[derive(Debug, Clone, Reflect)]
+pub struct OmenDefinition { pub id: OmenId, pub name: String, pub description: String, pub effects: Vec<OmenEffect>, pub reward_multiplier: f32, }
+
+#[derive(Resource, Default, Reflect)] #[reflect(Resource)]
+pub struct OmenLibrary { pub omens: Vec<OmenDefinition>, }
+impl OmenLibrary { pub fn get_omen_definition(&self, id: OmenId) -> Option<&OmenDefinition> { self.omens.iter().find(|def| def.id == id) } }
+
+/// Omens the player opted into. Kept between runs so veterans don't have to re-pick them every time.
+#[derive(Resource, Default, Debug)]
+pub struct ActiveOmens { pub omen_ids: Vec<OmenId>, }
+
+/// The combined effect of every active omen, rebuilt whenever the selection changes.
+#[derive(Resource, Debug, Clone)]
+pub struct RunModifiers {
+    pub horror_speed_multiplier: f32,
+    pub elite_chance_multiplier: f64,
+    pub health_regen_multiplier: f32,
+    pub upgrade_choice_limit: Option<usize>,
+    pub cycle_speed_multiplier: f32,
+    pub reward_multiplier: f32,
+}
+
+impl Default for RunModifiers {
+    fn default() -> Self { Self { horror_speed_multiplier: 1.0, elite_chance_multiplier: 1.0, health_regen_multiplier: 1.0, upgrade_choice_limit: None, cycle_speed_multiplier: 1.0, reward_multiplier: 1.0 } }
+}
+
+impl RunModifiers {
+    pub fn from_omens(active_omens: &ActiveOmens, library: &OmenLibrary) -> Self {
+        let mut modifiers = Self::default();
+        for omen in active_omens.omen_ids.iter().filter_map(|id| library.get_omen_definition(*id)) {
+            modifiers.reward_multiplier *= omen.reward_multiplier;
+            for effect in &omen.effects {
+                match effect {
+                    OmenEffect::HorrorSpeedMultiplier(multiplier) => modifiers.horror_speed_multiplier *= multiplier,
+                    OmenEffect::EliteChanceMultiplier(multiplier) => modifiers.elite_chance_multiplier *= multiplier,
+                    OmenEffect::HealthRegenMultiplier(multiplier) => modifiers.health_regen_multiplier *= multiplier,
+                    OmenEffect::UpgradeChoiceLimit(limit) => modifiers.upgrade_choice_limit = Some(modifiers.upgrade_choice_limit.map_or(*limit, |current| current.min(*limit))),
+                    OmenEffect::CycleSpeedMultiplier(multiplier) => modifiers.cycle_speed_multiplier *= multiplier,
+                }
+            }
+        }
+        modifiers
+    }
+
+    /// Meta-currency earned by a finished run, scaled by the omens that were active.
+    pub fn forbidden_lore_for_run(&self, game_state: &GameState, run_stats: &RunStatistics) -> u64 {
+        let total_kills: u32 = run_stats.kills_by_horror_type.values().sum();
+        let base_lore = game_state.cycle_number as f32 * LORE_PER_CYCLE + total_kills as f32 / KILLS_PER_LORE;
+        (base_lore * self.reward_multiplier).round() as u64
+    }
+}
+
+#[derive(Component)] struct OmenSelectUI;
+#[derive(Component)] struct OmenRow(OmenId);
+#[derive(Component)] struct OmenMultiplierText;
+
+pub struct OmensPlugin;
+
+impl Plugin for OmensPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .register_type::<OmenLibrary>()
+            .init_resource::<OmenLibrary>()
+            .init_resource::<ActiveOmens>()
+            .init_resource::<RunModifiers>()
+            .add_systems(Startup, populate_omen_library)
+            .add_systems(OnEnter(AppState::OmenSelect), setup_omen_select_ui)
+            .add_systems(Update, (
+                omen_select_input_system,
+                update_omen_select_ui,
+            ).chain().run_if(in_state(AppState::OmenSelect)))
+            .add_systems(OnExit(AppState::OmenSelect), despawn_omen_select_ui)
+            .add_systems(OnEnter(AppState::GameOver), award_forbidden_lore_system);
+    }
+}
+
+fn populate_omen_library(mut library: ResMut<OmenLibrary>) {
+    library.omens.push(OmenDefinition { id: OmenId(1), name: "Omen of the Hunt".to_string(), description: "Horrors move 20% faster.".to_string(), effects: vec![OmenEffect::HorrorSpeedMultiplier(1.2)], reward_multiplier: 1.25, });
+    library.omens.push(OmenDefinition { id: OmenId(2), name: "Omen of the Crowned".to_string(), description: "Elites appear three times as often.".to_string(), effects: vec![OmenEffect::EliteChanceMultiplier(3.0)], reward_multiplier: 1.3, });
+    library.omens.push(OmenDefinition { id: OmenId(3), name: "Omen of the Open Wound".to_string(), description: "Your endurance never regenerates.".to_string(), effects: vec![OmenEffect::HealthRegenMultiplier(0.0)], reward_multiplier: 1.2, });
+    library.omens.push(OmenDefinition { id: OmenId(4), name: "Omen of the Narrow Path".to_string(), description: "Level-ups offer only two upgrades.".to_string(), effects: vec![OmenEffect::UpgradeChoiceLimit(2)], reward_multiplier: 1.2, });
+    library.omens.push(OmenDefinition { id: OmenId(5), name: "Omen of the Hastening Dark".to_string(), description: "Cycles turn 25% sooner.".to_string(), effects: vec![OmenEffect::CycleSpeedMultiplier(1.25)], reward_multiplier: 1.25, });
+}
+
+fn setup_omen_select_ui(mut commands: Commands, asset_server: Res<AssetServer>, library: Res<OmenLibrary>) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands.spawn((
+        NodeBundle { style: Style { width: Val::Percent(100.0), height: Val::Percent(100.0), justify_content: JustifyContent::Center, align_items: AlignItems::Center, flex_direction: FlexDirection::Column, row_gap: Val::Px(14.0), ..default() }, ..default() },
+        OmenSelectUI,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("Heed the Omens", TextStyle { font: font.clone(), font_size: 60.0, color: Color::WHITE }).with_text_justify(JustifyText::Center));
+        parent.spawn(TextBundle::from_section("Each omen makes the night crueler, and its rewards richer.", TextStyle { font: font.clone(), font_size: 22.0, color: Color::rgba(0.8, 0.8, 0.8, 1.0) }).with_text_justify(JustifyText::Center));
+        for (index, omen) in library.omens.iter().enumerate().take(OMEN_TOGGLE_KEYS.len()) {
+            parent.spawn((
+                TextBundle::from_section(format!("[{}] {} - {} (x{:.2})", index + 1, omen.name, omen.description, omen.reward_multiplier), TextStyle { font: font.clone(), font_size: 24.0, color: Color::rgba(0.6, 0.6, 0.6, 1.0) }),
+                OmenRow(omen.id),
+            ));
+        }
+        parent.spawn((TextBundle::from_section("", TextStyle { font: font.clone(), font_size: 28.0, color: Color::rgb(0.9, 0.8, 0.5) }).with_text_justify(JustifyText::Center), OmenMultiplierText));
+        parent.spawn(TextBundle::from_section("1-5 to toggle  |  SPACE to descend  |  ESC to return", TextStyle { font, font_size: 24.0, color: Color::rgba(0.8, 0.8, 0.8, 1.0) }).with_text_justify(JustifyText::Center));
+    });
+}
+
+fn omen_select_input_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    library: Res<OmenLibrary>,
+    mut active_omens: ResMut<ActiveOmens>,
+    mut run_modifiers: ResMut<RunModifiers>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut sound_event_writer: EventWriter<PlaySoundEvent>,
+) {
+    for (key, omen) in OMEN_TOGGLE_KEYS.iter().zip(library.omens.iter()) {
+        if !keyboard_input.just_pressed(*key) { continue; }
+        if let Some(position) = active_omens.omen_ids.iter().position(|id| *id == omen.id) { active_omens.omen_ids.remove(position); } else { active_omens.omen_ids.push(omen.id); }
+        *run_modifiers = RunModifiers::from_omens(&active_omens, &library);
+    }
+    if keyboard_input.just_pressed(KeyCode::Escape) { next_app_state.set(AppState::CharacterSelect); return; }
+    if keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Enter]) {
+        *run_modifiers = RunModifiers::from_omens(&active_omens, &library);
+        sound_event_writer.send(PlaySoundEvent(SoundEffect::OmenAccepted));
+        next_app_state.set(AppState::InGame);
+    }
+}
+
+fn update_omen_select_ui(
+    active_omens: Res<ActiveOmens>,
+    run_modifiers: Res<RunModifiers>,
+    mut row_query: Query<(&OmenRow, &mut Text), Without<OmenMultiplierText>>,
+    mut multiplier_text_query: Query<&mut Text, With<OmenMultiplierText>>,
+) {
+    for (row, mut text) in row_query.iter_mut() {
+        text.sections[0].style.color = if active_omens.omen_ids.contains(&row.0) { Color::rgb(1.0, 0.45, 0.45) } else { Color::rgba(0.6, 0.6, 0.6, 1.0) };
+    }
+    for mut text in multiplier_text_query.iter_mut() {
+        text.sections[0].value = format!("Score and Forbidden Lore x{:.2}", run_modifiers.reward_multiplier);
+    }
+}
+
+fn despawn_omen_select_ui(mut commands: Commands, query: Query<Entity, With<OmenSelectUI>>) { for entity in query.iter() { commands.entity(entity).despawn_recursive(); } }
+
+fn award_forbidden_lore_system(run_modifiers: Res<RunModifiers>, game_state: Res<GameState>, run_stats: Res<RunStatistics>, mut profile: ResMut<PlayerProfile>) {
+    profile.forbidden_lore += run_modifiers.forbidden_lore_for_run(&game_state, &run_stats);
+    profile.save();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn omen(id: u32, effects: Vec<OmenEffect>, reward_multiplier: f32) -> OmenDefinition {
+        OmenDefinition { id: OmenId(id), name: format!("Omen {}", id), description: String::new(), effects, reward_multiplier }
+    }
+
+    fn library() -> OmenLibrary {
+        OmenLibrary { omens: vec![
+            omen(1, vec![OmenEffect::HorrorSpeedMultiplier(1.2)], 1.25),
+            omen(2, vec![OmenEffect::EliteChanceMultiplier(3.0), OmenEffect::CycleSpeedMultiplier(1.5)], 1.5),
+            omen(3, vec![OmenEffect::UpgradeChoiceLimit(3)], 1.1),
+            omen(4, vec![OmenEffect::UpgradeChoiceLimit(2), OmenEffect::HealthRegenMultiplier(0.0)], 1.2),
+        ] }
+    }
+
+    #[test]
+    fn no_omens_leave_the_run_untouched() {
+        let modifiers = RunModifiers::from_omens(&ActiveOmens::default(), &library());
+        assert_eq!(modifiers.horror_speed_multiplier, 1.0);
+        assert_eq!(modifiers.elite_chance_multiplier, 1.0);
+        assert_eq!(modifiers.health_regen_multiplier, 1.0);
+        assert_eq!(modifiers.upgrade_choice_limit, None);
+        assert_eq!(modifiers.cycle_speed_multiplier, 1.0);
+        assert_eq!(modifiers.reward_multiplier, 1.0);
+    }
+
+    #[test]
+    fn effects_and_rewards_multiply_across_omens() {
+        let active = ActiveOmens { omen_ids: vec![OmenId(1), OmenId(2)] };
+        let modifiers = RunModifiers::from_omens(&active, &library());
+        assert!((modifiers.horror_speed_multiplier - 1.2).abs() < 1e-6);
+        assert!((modifiers.elite_chance_multiplier - 3.0).abs() < 1e-9);
+        assert!((modifiers.cycle_speed_multiplier - 1.5).abs() < 1e-6);
+        assert!((modifiers.reward_multiplier - 1.25 * 1.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn the_strictest_upgrade_choice_limit_wins() {
+        let active = ActiveOmens { omen_ids: vec![OmenId(3), OmenId(4)] };
+        let modifiers = RunModifiers::from_omens(&active, &library());
+        assert_eq!(modifiers.upgrade_choice_limit, Some(2));
+        assert_eq!(modifiers.health_regen_multiplier, 0.0);
+        let reversed = RunModifiers::from_omens(&ActiveOmens { omen_ids: vec![OmenId(4), OmenId(3)] }, &library());
+        assert_eq!(reversed.upgrade_choice_limit, Some(2));
+    }
+
+    #[test]
+    fn unknown_omen_ids_are_ignored() {
+        let active = ActiveOmens { omen_ids: vec![OmenId(99), OmenId(1)] };
+        let modifiers = RunModifiers::from_omens(&active, &library());
+        assert!((modifiers.reward_multiplier - 1.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn forbidden_lore_scales_with_the_reward_multiplier() {
+        let mut run_stats = RunStatistics::default();
+        run_stats.kills_by_horror_type.insert(crate::horror::HorrorType::SkitteringShadowling, 40);
+        let game_state = GameState { cycle_number: 3, ..default() };
+        let plain = RunModifiers::default();
+        assert_eq!(plain.forbidden_lore_for_run(&game_state, &run_stats), 19);
+        let cursed = RunModifiers { reward_multiplier: 2.0, ..plain };
+        assert_eq!(cursed.forbidden_lore_for_run(&game_state, &run_stats), 38);
+    }
+}
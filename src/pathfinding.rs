@@ -0,0 +1,392 @@
+// src/pathfinding.rs
+use bevy::prelude::*;
+use std::{cmp::Reverse, collections::{BinaryHeap, HashMap}};
+use crate::{
+    camera_systems::MainCamera,
+    survivor::Survivor,
+    horror::Horror,
+    game::AppState,
+};
+
+pub const FLOW_FIELD_CELL_SIZE: f32 = 48.0;
+// Horrors spawn up to one screen width away, so the grid has to reach at least that far from the camera.
+const FLOW_FIELD_HALF_EXTENT_CELLS: i32 = 30;
+const FLOW_FIELD_REBUILD_INTERVAL_SECS: f32 = 0.15;
+
+const STRAIGHT_STEP_COST: u32 = 10;
+const DIAGONAL_STEP_COST: u32 = 14;
+const CROWD_COST_PER_HORROR: u32 = 4;
+const MAX_CROWD_COST: u32 = 40;
+
+pub const SEPARATION_STEERING_WEIGHT: f32 = 0.6;
+
+// Must be at least the diameter of the largest horror so overlaps are always found in the 3x3 neighbourhood.
+const CROWD_GRID_CELL_SIZE: f32 = 80.0;
+const CROWD_BODY_RADIUS_FACTOR: f32 = 0.45;
+const CROWD_PUSH_STIFFNESS: f32 = 0.5;
+
+/// Static blocker that horrors path around. The flow field treats every cell touched by `radius` as impassable.
+/// Read through `GlobalTransform`, since the ruins that carry it are children of the scrolling background tiles.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Obstacle { pub radius: f32, }
+
+const NEIGHBOUR_OFFSETS: [IVec2; 8] = [
+    IVec2::new(1, 0), IVec2::new(-1, 0), IVec2::new(0, 1), IVec2::new(0, -1),
+    IVec2::new(1, 1), IVec2::new(1, -1), IVec2::new(-1, 1), IVec2::new(-1, -1),
+];
+
+/// Grid of "best direction towards the survivor" vectors, centred on the camera.
+/// Step costs are raised by how many horrors occupy a cell so crowds fan out around each other.
+#[derive(Resource)]
+pub struct FlowField {
+    pub origin: Vec2,
+    pub width: i32,
+    pub height: i32,
+    pub cell_size: f32,
+    blocked: Vec<bool>,
+    crowd: Vec<u16>,
+    integrated_costs: Vec<u32>,
+    directions: Vec<Vec2>,
+    target_cell: Option<IVec2>,
+    rebuild_timer: Timer,
+}
+
+impl Default for FlowField {
+    fn default() -> Self {
+        let side = FLOW_FIELD_HALF_EXTENT_CELLS * 2;
+        let cell_count = (side * side) as usize;
+        Self {
+            origin: Vec2::ZERO,
+            width: side,
+            height: side,
+            cell_size: FLOW_FIELD_CELL_SIZE,
+            blocked: vec![false; cell_count],
+            crowd: vec![0; cell_count],
+            integrated_costs: vec![u32::MAX; cell_count],
+            directions: vec![Vec2::ZERO; cell_count],
+            target_cell: None,
+            rebuild_timer: Timer::from_seconds(FLOW_FIELD_REBUILD_INTERVAL_SECS, TimerMode::Repeating),
+        }
+    }
+}
+
+impl FlowField {
+    pub fn cell_of(&self, position: Vec2) -> Option<IVec2> {
+        let local = (position - self.origin) / self.cell_size;
+        let cell = IVec2::new(local.x.floor() as i32, local.y.floor() as i32);
+        if self.contains(cell) { Some(cell) } else { None }
+    }
+
+    pub fn cell_center(&self, cell: IVec2) -> Vec2 {
+        self.origin + (cell.as_vec2() + Vec2::splat(0.5)) * self.cell_size
+    }
+
+    fn contains(&self, cell: IVec2) -> bool {
+        cell.x >= 0 && cell.y >= 0 && cell.x < self.width && cell.y < self.height
+    }
+
+    fn index(&self, cell: IVec2) -> usize { (cell.y * self.width + cell.x) as usize }
+
+    /// Direction a horror at `position` should walk. `None` outside the grid or in the survivor's own cell,
+    /// where callers should fall back to seeking the survivor directly.
+    pub fn direction_at(&self, position: Vec2) -> Option<Vec2> {
+        let cell = self.cell_of(position)?;
+        let direction = self.directions[self.index(cell)];
+        if direction == Vec2::ZERO { None } else { Some(direction) }
+    }
+
+    /// Steering away from crowded neighbouring cells, and away from the centre of the horror's own cell when it is shared.
+    pub fn separation_at(&self, position: Vec2) -> Vec2 {
+        let Some(cell) = self.cell_of(position) else { return Vec2::ZERO; };
+        let own_crowd = self.crowd[self.index(cell)] as f32;
+        let mut steering = Vec2::ZERO;
+        if own_crowd > 1.0 {
+            steering += (position - self.cell_center(cell)) / self.cell_size * (own_crowd - 1.0);
+        }
+        for offset in NEIGHBOUR_OFFSETS {
+            let neighbour = cell + offset;
+            if !self.contains(neighbour) { continue; }
+            let neighbour_crowd = self.crowd[self.index(neighbour)] as f32;
+            if neighbour_crowd > 0.0 {
+                steering -= offset.as_vec2().normalize() * neighbour_crowd / 8.0;
+            }
+        }
+        steering.clamp_length_max(1.0)
+    }
+
+    fn recenter(&mut self, center: Vec2) {
+        let half_span = Vec2::new(self.width as f32, self.height as f32) * 0.5 * self.cell_size;
+        self.origin = ((center - half_span) / self.cell_size).floor() * self.cell_size;
+        self.blocked.iter_mut().for_each(|b| *b = false);
+        self.crowd.iter_mut().for_each(|c| *c = 0);
+    }
+
+    fn mark_obstacle(&mut self, position: Vec2, radius: f32) {
+        let min = ((position - Vec2::splat(radius) - self.origin) / self.cell_size).floor();
+        let max = ((position + Vec2::splat(radius) - self.origin) / self.cell_size).floor();
+        for y in (min.y as i32)..=(max.y as i32) {
+            for x in (min.x as i32)..=(max.x as i32) {
+                let cell = IVec2::new(x, y);
+                if !self.contains(cell) { continue; }
+                let closest = position.clamp(self.origin + cell.as_vec2() * self.cell_size, self.origin + (cell.as_vec2() + Vec2::ONE) * self.cell_size);
+                if closest.distance_squared(position) <= radius * radius {
+                    let idx = self.index(cell);
+                    self.blocked[idx] = true;
+                }
+            }
+        }
+    }
+
+    fn add_crowd(&mut self, position: Vec2) {
+        if let Some(cell) = self.cell_of(position) {
+            let idx = self.index(cell);
+            self.crowd[idx] = self.crowd[idx].saturating_add(1);
+        }
+    }
+
+    fn integrate_towards(&mut self, target: Vec2) {
+        self.integrated_costs.iter_mut().for_each(|c| *c = u32::MAX);
+        self.directions.iter_mut().for_each(|d| *d = Vec2::ZERO);
+        self.target_cell = self.cell_of(target);
+        let Some(target_cell) = self.target_cell else { return; };
+
+        let target_idx = self.index(target_cell);
+        self.integrated_costs[target_idx] = 0;
+        let mut open = BinaryHeap::new();
+        open.push(Reverse((0u32, target_idx)));
+
+        while let Some(Reverse((cost, idx))) = open.pop() {
+            if cost > self.integrated_costs[idx] { continue; }
+            let cell = IVec2::new(idx as i32 % self.width, idx as i32 / self.width);
+            for offset in NEIGHBOUR_OFFSETS {
+                let neighbour = cell + offset;
+                if !self.contains(neighbour) || !self.can_step(cell, offset) { continue; }
+                let neighbour_idx = self.index(neighbour);
+                let step_cost = if offset.x != 0 && offset.y != 0 { DIAGONAL_STEP_COST } else { STRAIGHT_STEP_COST };
+                let crowd_cost = (self.crowd[neighbour_idx] as u32 * CROWD_COST_PER_HORROR).min(MAX_CROWD_COST);
+                let new_cost = cost + step_cost + crowd_cost;
+                if new_cost < self.integrated_costs[neighbour_idx] {
+                    self.integrated_costs[neighbour_idx] = new_cost;
+                    open.push(Reverse((new_cost, neighbour_idx)));
+                }
+            }
+        }
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let cell = IVec2::new(x, y);
+                let idx = self.index(cell);
+                if idx == target_idx || self.integrated_costs[idx] == u32::MAX { continue; }
+                let mut best: Option<(u32, IVec2)> = None;
+                for offset in NEIGHBOUR_OFFSETS {
+                    let neighbour = cell + offset;
+                    if !self.contains(neighbour) || !self.can_step(cell, offset) { continue; }
+                    let neighbour_cost = self.integrated_costs[self.index(neighbour)];
+                    if best.is_none_or(|(best_cost, _)| neighbour_cost < best_cost) { best = Some((neighbour_cost, offset)); }
+                }
+                if let Some((_, offset)) = best { self.directions[idx] = offset.as_vec2().normalize(); }
+            }
+        }
+    }
+
+    /// Blocked cells can't be entered, and diagonal steps may not cut the corner of a blocked cell.
+    fn can_step(&self, from: IVec2, offset: IVec2) -> bool {
+        let to = from + offset;
+        if self.blocked[self.index(to)] { return false; }
+        if offset.x != 0 && offset.y != 0 {
+            let side_a = IVec2::new(from.x + offset.x, from.y);
+            let side_b = IVec2::new(from.x, from.y + offset.y);
+            if self.blocked[self.index(side_a)] || self.blocked[self.index(side_b)] { return false; }
+        }
+        true
+    }
+}
+
+pub struct PathfindingPlugin;
+
+impl Plugin for PathfindingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlowField>()
+            .add_systems(Update, update_flow_field_system.run_if(in_state(AppState::InGame)));
+    }
+}
+
+pub fn update_flow_field_system(
+    time: Res<Time>,
+    mut flow_field: ResMut<FlowField>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    survivor_query: Query<&Transform, With<Survivor>>,
+    obstacle_query: Query<(&GlobalTransform, &Obstacle)>,
+    horror_query: Query<&Transform, With<Horror>>,
+) {
+    flow_field.rebuild_timer.tick(time.delta());
+    if !flow_field.rebuild_timer.just_finished() { return; }
+    let Ok(survivor_transform) = survivor_query.get_single() else { return; };
+    let survivor_pos = survivor_transform.translation.truncate();
+    let center = camera_query.get_single().map_or(survivor_pos, |t| t.translation.truncate());
+
+    flow_field.recenter(center);
+    for (obstacle_transform, obstacle) in obstacle_query.iter() {
+        flow_field.mark_obstacle(obstacle_transform.translation().truncate(), obstacle.radius);
+    }
+    for horror_transform in horror_query.iter() {
+        flow_field.add_crowd(horror_transform.translation.truncate());
+    }
+    flow_field.integrate_towards(survivor_pos);
+}
+
+/// Soft-body pass run after horror movement: overlapping horrors are pushed apart, split by mass, and pushed out of obstacles.
+/// Only part of each overlap is resolved per frame so dense crowds compress a little instead of jittering.
+pub fn resolve_horror_crowd_collisions_system(
+    mut horror_query: Query<(&mut Transform, &Horror, &Visibility)>,
+    obstacle_query: Query<(&GlobalTransform, &Obstacle)>,
+    mut spatial_grid: Local<HashMap<IVec2, Vec<usize>>>,
+    mut bodies: Local<Vec<(Vec2, f32, f32)>>,
+    mut displacements: Local<Vec<Vec2>>,
+) {
+    bodies.clear();
+    for (transform, horror, visibility) in horror_query.iter() {
+        if *visibility == Visibility::Hidden { bodies.push((transform.translation.truncate(), 0.0, horror.mass())); continue; }
+        bodies.push((transform.translation.truncate(), horror.size.min_element() * CROWD_BODY_RADIUS_FACTOR, horror.mass()));
+    }
+
+    spatial_grid.values_mut().for_each(|bucket| bucket.clear());
+    for (index, (position, radius, _)) in bodies.iter().enumerate() {
+        if *radius <= 0.0 { continue; }
+        spatial_grid.entry(crowd_cell_of(*position)).or_default().push(index);
+    }
+
+    displacements.clear();
+    displacements.resize(bodies.len(), Vec2::ZERO);
+    for (index, &(position, radius, mass)) in bodies.iter().enumerate() {
+        if radius <= 0.0 { continue; }
+        let cell = crowd_cell_of(position);
+        for y in -1..=1 {
+            for x in -1..=1 {
+                let Some(bucket) = spatial_grid.get(&(cell + IVec2::new(x, y))) else { continue; };
+                for &other_index in bucket.iter().filter(|&&other_index| other_index > index) {
+                    let (other_position, other_radius, other_mass) = bodies[other_index];
+                    let offset = position - other_position;
+                    let overlap = radius + other_radius - offset.length();
+                    if overlap <= 0.0 { continue; }
+                    // Perfectly stacked horrors get an arbitrary but deterministic separating axis.
+                    let normal = offset.try_normalize().unwrap_or_else(|| Vec2::from_angle(index as f32));
+                    let push = normal * overlap * CROWD_PUSH_STIFFNESS;
+                    let total_mass = mass + other_mass;
+                    displacements[index] += push * (other_mass / total_mass);
+                    displacements[other_index] -= push * (mass / total_mass);
+                }
+            }
+        }
+    }
+
+    for (index, (mut transform, _, _)) in horror_query.iter_mut().enumerate() {
+        let (position, radius, _) = bodies[index];
+        let mut new_position = position + displacements[index];
+        if radius > 0.0 {
+            for (obstacle_transform, obstacle) in obstacle_query.iter() {
+                let obstacle_pos = obstacle_transform.translation().truncate();
+                let offset = new_position - obstacle_pos;
+                let min_distance = obstacle.radius + radius;
+                if offset.length_squared() < min_distance * min_distance {
+                    new_position = obstacle_pos + offset.try_normalize().unwrap_or(Vec2::X) * min_distance;
+                }
+            }
+        }
+        transform.translation.x = new_position.x;
+        transform.translation.y = new_position.y;
+    }
+}
+
+fn crowd_cell_of(position: Vec2) -> IVec2 {
+    (position / CROWD_GRID_CELL_SIZE).floor().as_ivec2()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field_centred_on_origin() -> FlowField {
+        let mut field = FlowField::default();
+        field.recenter(Vec2::ZERO);
+        field
+    }
+
+    #[test]
+    fn cell_lookup_round_trips_through_cell_centre() {
+        let field = field_centred_on_origin();
+        let cell = field.cell_of(Vec2::new(100.0, -250.0)).unwrap();
+        assert_eq!(field.cell_of(field.cell_center(cell)), Some(cell));
+        let half_span = FLOW_FIELD_HALF_EXTENT_CELLS as f32 * FLOW_FIELD_CELL_SIZE;
+        assert_eq!(field.cell_of(Vec2::new(half_span + 1.0, 0.0)), None);
+        assert_eq!(field.cell_of(Vec2::new(0.0, -half_span - 1.0)), None);
+    }
+
+    #[test]
+    fn open_field_points_straight_at_target() {
+        let mut field = field_centred_on_origin();
+        field.integrate_towards(Vec2::ZERO);
+        assert_eq!(field.direction_at(Vec2::new(FLOW_FIELD_CELL_SIZE * 5.5, 1.0)), Some(Vec2::NEG_X));
+        assert_eq!(field.direction_at(Vec2::new(1.0, -FLOW_FIELD_CELL_SIZE * 5.5)), Some(Vec2::Y));
+        assert_eq!(field.direction_at(Vec2::new(1.0, 1.0)), None, "the target's own cell has no direction");
+        assert_eq!(field.direction_at(Vec2::splat(10_000.0)), None);
+    }
+
+    #[test]
+    fn step_costs_accumulate_outwards_from_target() {
+        let mut field = field_centred_on_origin();
+        field.integrate_towards(Vec2::splat(1.0));
+        let target = field.cell_of(Vec2::splat(1.0)).unwrap();
+        assert_eq!(field.integrated_costs[field.index(target)], 0);
+        assert_eq!(field.integrated_costs[field.index(target + IVec2::new(3, 0))], 3 * STRAIGHT_STEP_COST);
+        assert_eq!(field.integrated_costs[field.index(target + IVec2::new(2, 2))], 2 * DIAGONAL_STEP_COST);
+    }
+
+    #[test]
+    fn obstacles_block_their_cells_and_are_routed_around() {
+        let mut field = field_centred_on_origin();
+        // A wall one cell thick, five cells tall, directly between the target and a horror east of it.
+        for y in -2..=2 {
+            field.mark_obstacle(Vec2::new(FLOW_FIELD_CELL_SIZE * 2.5, FLOW_FIELD_CELL_SIZE * (y as f32 + 0.5)), 1.0);
+        }
+        field.integrate_towards(Vec2::splat(1.0));
+        let wall_cell = field.cell_of(Vec2::new(FLOW_FIELD_CELL_SIZE * 2.5, 1.0)).unwrap();
+        assert!(field.blocked[field.index(wall_cell)]);
+        assert_eq!(field.integrated_costs[field.index(wall_cell)], u32::MAX);
+
+        let behind_wall = Vec2::new(FLOW_FIELD_CELL_SIZE * 3.5, 1.0);
+        let direction = field.direction_at(behind_wall).unwrap();
+        assert!(direction.y.abs() > 0.0, "expected a detour around the wall, got {direction:?}");
+        let behind_cell = field.cell_of(behind_wall).unwrap();
+        assert!(field.integrated_costs[field.index(behind_cell)] > 3 * STRAIGHT_STEP_COST);
+    }
+
+    #[test]
+    fn diagonal_steps_do_not_cut_blocked_corners() {
+        let mut field = field_centred_on_origin();
+        let from = IVec2::new(10, 10);
+        field.mark_obstacle(field.cell_center(from + IVec2::X), 1.0);
+        assert!(!field.can_step(from, IVec2::new(1, 1)));
+        assert!(!field.can_step(from, IVec2::new(1, -1)));
+        assert!(field.can_step(from, IVec2::new(-1, 1)));
+        assert!(!field.can_step(from, IVec2::X));
+    }
+
+    #[test]
+    fn recentering_clears_obstacles_and_crowds() {
+        let mut field = field_centred_on_origin();
+        field.mark_obstacle(Vec2::new(100.0, 100.0), 60.0);
+        field.add_crowd(Vec2::new(-100.0, 0.0));
+        field.recenter(Vec2::ZERO);
+        assert!(field.blocked.iter().all(|blocked| !blocked));
+        assert!(field.crowd.iter().all(|crowd| *crowd == 0));
+    }
+
+    #[test]
+    fn separation_pushes_away_from_crowded_neighbours() {
+        let mut field = field_centred_on_origin();
+        let cell = field.cell_of(Vec2::splat(1.0)).unwrap();
+        for _ in 0..4 { field.add_crowd(field.cell_center(cell + IVec2::X)); }
+        assert!(field.separation_at(field.cell_center(cell)).x < 0.0);
+    }
+}
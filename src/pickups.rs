@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+use rand::{Rng, seq::SliceRandom};
+use crate::{
+    game::{AppState, OnSessionEnd},
+    camera_systems::{MainCamera, visible_world_rect},
+    survivor::Survivor,
+    components::Health,
+    horror::{Horror, HorrorKilledEvent},
+    echoing_soul::{EchoingSoul, VoidMagnetized},
+    audio::{PlaySoundAtEvent, SoundEffect},
+    run_statistics::{DamageDealtEvent, DamageSource},
+};
+
+const PICKUP_DROP_CHANCE: f64 = 0.04;
+const PICKUP_COLLECTION_RADIUS: f32 = 36.0;
+const FLESH_CHUNK_HEAL_AMOUNT: i32 = 30;
+const WARDING_ORB_INVULNERABILITY_SECS: f32 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PickupKind {
+    /// Restores endurance.
+    FleshChunk,
+    /// Pulls every echoing soul on the map to the survivor.
+    VoidMagnet,
+    /// Slays every horror on screen.
+    ElderSign,
+    /// Brief invulnerability.
+    WardingOrb,
+}
+
+#[derive(Debug, Clone)]
+pub struct PickupDefinition {
+    pub kind: PickupKind,
+    pub name: String,
+    pub sprite_path: String,
+    pub size: Vec2,
+    pub color: Color,
+    pub spawn_weight: u32,
+    pub sound: SoundEffect,
+}
+
+#[derive(Resource, Default)]
+pub struct PickupLibrary { pub pickups: Vec<PickupDefinition>, }
+impl PickupLibrary { pub fn get_pickup_definition(&self, kind: PickupKind) -> Option<&PickupDefinition> { self.pickups.iter().find(|def| def.kind == kind) } }
+
+#[derive(Component, Debug)]
+pub struct Pickup { pub kind: PickupKind, }
+
+/// Sent when an Elder Sign is collected.
+#[derive(Event, Debug, Clone, Copy)]
+struct ElderSignInvokedEvent;
+
+pub struct PickupsPlugin;
+
+impl Plugin for PickupsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<ElderSignInvokedEvent>()
+            .init_resource::<PickupLibrary>()
+            .add_systems(Startup, populate_pickup_library)
+            .add_systems(Update, (
+                spawn_pickups_on_horror_death_system,
+                pickup_collection_system,
+                elder_sign_system,
+            ).chain().run_if(in_state(AppState::InGame)))
+            .add_systems(OnSessionEnd, despawn_all_pickups);
+    }
+}
+
+fn populate_pickup_library(mut library: ResMut<PickupLibrary>) {
+    library.pickups.push(PickupDefinition { kind: PickupKind::FleshChunk, name: "Flesh Chunk".to_string(), sprite_path: "sprites/flesh_chunk_placeholder.png".to_string(), size: Vec2::new(20.0, 20.0), color: Color::rgb(0.9, 0.3, 0.3), spawn_weight: 50, sound: SoundEffect::FleshConsumed, });
+    library.pickups.push(PickupDefinition { kind: PickupKind::VoidMagnet, name: "Void Magnet".to_string(), sprite_path: "sprites/void_magnet_placeholder.png".to_string(), size: Vec2::new(22.0, 22.0), color: Color::rgb(0.5, 0.3, 1.0), spawn_weight: 25, sound: SoundEffect::VoidMagnet, });
+    library.pickups.push(PickupDefinition { kind: PickupKind::WardingOrb, name: "Warding Orb".to_string(), sprite_path: "sprites/warding_orb_placeholder.png".to_string(), size: Vec2::new(22.0, 22.0), color: Color::rgb(0.6, 0.9, 1.0), spawn_weight: 15, sound: SoundEffect::WardingOrb, });
+    library.pickups.push(PickupDefinition { kind: PickupKind::ElderSign, name: "Elder Sign".to_string(), sprite_path: "sprites/elder_sign_placeholder.png".to_string(), size: Vec2::new(26.0, 26.0), color: Color::rgb(1.0, 0.9, 0.4), spawn_weight: 10, sound: SoundEffect::ElderSign, });
+}
+
+pub fn spawn_pickup(commands: &mut Commands, asset_server: &Res<AssetServer>, definition: &PickupDefinition, position: Vec3) {
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load(definition.sprite_path.clone()),
+            sprite: Sprite { custom_size: Some(definition.size), color: definition.color, ..default() },
+            transform: Transform::from_translation(position.truncate().extend(0.4)),
+            ..default()
+        },
+        Pickup { kind: definition.kind },
+        Name::new(format!("Pickup_{}", definition.name)),
+    ));
+}
+
+fn spawn_pickups_on_horror_death_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    library: Res<PickupLibrary>,
+    mut kill_events: EventReader<HorrorKilledEvent>,
+) {
+    let mut rng = rand::thread_rng();
+    for event in kill_events.read() {
+        if !rng.gen_bool(PICKUP_DROP_CHANCE) { continue; }
+        if let Ok(definition) = library.pickups.choose_weighted(&mut rng, |def| def.spawn_weight) {
+            spawn_pickup(&mut commands, &asset_server, definition, event.position);
+        }
+    }
+}
+
+fn pickup_collection_system(
+    mut commands: Commands,
+    library: Res<PickupLibrary>,
+    pickup_query: Query<(Entity, &Transform, &Pickup)>,
+    mut survivor_query: Query<(&Transform, &mut Survivor, &mut Health)>,
+    soul_query: Query<Entity, (With<EchoingSoul>, Without<VoidMagnetized>)>,
+    mut sound_event_writer: EventWriter<PlaySoundAtEvent>,
+    mut elder_sign_writer: EventWriter<ElderSignInvokedEvent>,
+) {
+    let Ok((survivor_transform, mut survivor, mut survivor_health)) = survivor_query.get_single_mut() else { return; };
+    let survivor_pos = survivor_transform.translation.truncate();
+    for (pickup_entity, pickup_transform, pickup) in pickup_query.iter() {
+        if pickup_transform.translation.truncate().distance(survivor_pos) >= PICKUP_COLLECTION_RADIUS { continue; }
+        commands.entity(pickup_entity).despawn_recursive();
+        if let Some(definition) = library.get_pickup_definition(pickup.kind) { sound_event_writer.send(PlaySoundAtEvent(definition.sound, pickup_transform.translation)); }
+
+        match pickup.kind {
+            PickupKind::FleshChunk => { survivor_health.0 = (survivor_health.0 + FLESH_CHUNK_HEAL_AMOUNT).min(survivor.max_health); }
+            PickupKind::VoidMagnet => { for soul_entity in soul_query.iter() { commands.entity(soul_entity).try_insert(VoidMagnetized); } }
+            PickupKind::WardingOrb => { survivor.grant_invulnerability(WARDING_ORB_INVULNERABILITY_SECS); }
+            PickupKind::ElderSign => { elder_sign_writer.send(ElderSignInvokedEvent); }
+        }
+    }
+}
+
+/// Kills every horror on screen outright. Elite wards don't apply: the sign is a wipe, not a hit.
+fn elder_sign_system(
+    mut events: EventReader<ElderSignInvokedEvent>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+    mut horror_query: Query<(Entity, &Transform, &mut Health), With<Horror>>,
+    mut damage_event_writer: EventWriter<DamageDealtEvent>,
+) {
+    if events.read().count() == 0 { return; }
+    let Ok((camera_transform, projection)) = camera_query.get_single() else { return; };
+    let visible = visible_world_rect(camera_transform, projection);
+    for (horror_entity, horror_transform, mut horror_health) in horror_query.iter_mut() {
+        if horror_health.0 <= 0 || !visible.contains(horror_transform.translation.truncate()) { continue; }
+        damage_event_writer.send(DamageDealtEvent { source: DamageSource::ElderSign, amount: horror_health.0, target: horror_entity, position: horror_transform.translation, is_crit: false });
+        horror_health.0 = 0;
+    }
+}
+
+fn despawn_all_pickups(mut commands: Commands, query: Query<Entity, With<Pickup>>) { for entity in query.iter() { commands.entity(entity).despawn_recursive(); } }
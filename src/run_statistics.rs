@@ -0,0 +1,263 @@
+use bevy::prelude::*;
+use serde::Serialize;
+use std::{collections::HashMap, path::PathBuf, time::{SystemTime, UNIX_EPOCH}};
+use crate::{
+    game::{AppState, GameState, UpgradeChosenEvent},
+    horror::HorrorType,
+    items::{AutomaticWeaponId, AutomaticWeaponLibrary},
+    skills::{SkillId, SkillLibrary},
+    upgrades::UpgradeId,
+};
+
+const RUN_REPORT_DIRECTORY: &str = "run_reports";
+const STATS_PANEL_MAX_ROWS: usize = 8;
+
+/// Everything that can deal damage to a horror, used to attribute damage in the post-run report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum DamageSource {
+    AutomaticWeapon(AutomaticWeaponId),
+    Skill(SkillId),
+    CircleOfWarding,
+    SwarmOfNightmares,
+    ItemExplosion,
+    RetaliationNova,
+    ElderSign,
+    Dodge,
+}
+
+/// Sent for every hit on a horror. Feeds the post-run damage report and the floating damage numbers.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DamageDealtEvent { pub source: DamageSource, pub amount: i32, pub target: Entity, pub position: Vec3, pub is_crit: bool, }
+
+#[derive(Debug, Clone)]
+pub struct UpgradePick { pub elapsed_secs: f32, pub upgrade_id: UpgradeId, pub name: String, }
+
+/// Per-run accounting shown on the game over screen. Reset when a new run starts.
+#[derive(Resource, Default, Debug)]
+pub struct RunStatistics {
+    pub damage_by_source: HashMap<DamageSource, i64>,
+    pub kills_by_horror_type: HashMap<HorrorType, u32>,
+    pub damage_taken_by_horror_type: HashMap<HorrorType, i64>,
+    pub experience_collected: u64,
+    pub upgrade_picks: Vec<UpgradePick>,
+}
+
+impl RunStatistics {
+    pub fn record_kill(&mut self, horror_type: HorrorType) { *self.kills_by_horror_type.entry(horror_type).or_insert(0) += 1; }
+    pub fn record_damage_taken(&mut self, horror_type: HorrorType, amount: i32) { *self.damage_taken_by_horror_type.entry(horror_type).or_insert(0) += amount.max(0) as i64; }
+    pub fn record_experience(&mut self, amount: u32) { self.experience_collected += amount as u64; }
+
+    pub fn build_report(&self, game_state: &GameState, weapon_library: &AutomaticWeaponLibrary, skill_library: &SkillLibrary) -> RunStatisticsReport {
+        let total_damage_dealt: i64 = self.damage_by_source.values().sum();
+        let mut damage_by_source: Vec<SourceDamageEntry> = self.damage_by_source.iter().map(|(source, damage)| SourceDamageEntry {
+            source: damage_source_name(*source, weapon_library, skill_library),
+            damage: *damage,
+            share_percent: if total_damage_dealt > 0 { *damage as f32 / total_damage_dealt as f32 * 100.0 } else { 0.0 },
+        }).collect();
+        damage_by_source.sort_by(|a, b| b.damage.cmp(&a.damage).then_with(|| a.source.cmp(&b.source)));
+
+        let mut kills_by_horror_type: Vec<HorrorTypeEntry> = self.kills_by_horror_type.iter().map(|(horror_type, kills)| HorrorTypeEntry { horror_type: format!("{:?}", horror_type), value: *kills as i64 }).collect();
+        kills_by_horror_type.sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.horror_type.cmp(&b.horror_type)));
+        let mut damage_taken_by_horror_type: Vec<HorrorTypeEntry> = self.damage_taken_by_horror_type.iter().map(|(horror_type, damage)| HorrorTypeEntry { horror_type: format!("{:?}", horror_type), value: *damage }).collect();
+        damage_taken_by_horror_type.sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.horror_type.cmp(&b.horror_type)));
+
+        RunStatisticsReport {
+            score: game_state.score,
+            cycle_reached: game_state.cycle_number,
+            survival_time_secs: game_state.game_timer.elapsed_secs(),
+            experience_collected: self.experience_collected,
+            total_damage_dealt,
+            total_kills: self.kills_by_horror_type.values().sum(),
+            damage_by_source,
+            kills_by_horror_type,
+            damage_taken_by_horror_type,
+            upgrade_picks: self.upgrade_picks.iter().enumerate().map(|(index, pick)| UpgradePickEntry { order: index as u32 + 1, elapsed_secs: pick.elapsed_secs, upgrade_id: pick.upgrade_id.0, name: pick.name.clone() }).collect(),
+        }
+    }
+}
+
+fn damage_source_name(source: DamageSource, weapon_library: &AutomaticWeaponLibrary, skill_library: &SkillLibrary) -> String {
+    match source {
+        DamageSource::AutomaticWeapon(weapon_id) => weapon_library.get_weapon_definition(weapon_id).map_or_else(|| format!("Weapon #{}", weapon_id.0), |def| def.name.clone()),
+        DamageSource::Skill(skill_id) => skill_library.get_skill_definition(skill_id).map_or_else(|| format!("Skill #{}", skill_id.0), |def| def.name.clone()),
+        DamageSource::CircleOfWarding => "Circle of Warding".to_string(),
+        DamageSource::SwarmOfNightmares => "Swarm of Nightmares".to_string(),
+        DamageSource::ItemExplosion => "Relic Explosions".to_string(),
+        DamageSource::RetaliationNova => "Retaliation Novas".to_string(),
+        DamageSource::ElderSign => "Elder Signs".to_string(),
+        DamageSource::Dodge => "Dodge".to_string(),
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SourceDamageEntry { pub source: String, pub damage: i64, pub share_percent: f32, }
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HorrorTypeEntry { pub horror_type: String, pub value: i64, }
+
+#[derive(Serialize, Debug, Clone)]
+pub struct UpgradePickEntry { pub order: u32, pub elapsed_secs: f32, pub upgrade_id: u32, pub name: String, }
+
+/// Serializable snapshot of a finished run, written out as JSON for balance review.
+#[derive(Serialize, Debug, Clone)]
+pub struct RunStatisticsReport {
+    pub score: u32,
+    pub cycle_reached: u32,
+    pub survival_time_secs: f32,
+    pub experience_collected: u64,
+    pub total_damage_dealt: i64,
+    pub total_kills: u32,
+    pub damage_by_source: Vec<SourceDamageEntry>,
+    pub kills_by_horror_type: Vec<HorrorTypeEntry>,
+    pub damage_taken_by_horror_type: Vec<HorrorTypeEntry>,
+    pub upgrade_picks: Vec<UpgradePickEntry>,
+}
+
+pub fn export_report(report: &RunStatisticsReport) -> std::io::Result<PathBuf> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    std::fs::create_dir_all(RUN_REPORT_DIRECTORY)?;
+    let path = PathBuf::from(RUN_REPORT_DIRECTORY).join(format!("run_{}.json", timestamp));
+    let json = serde_json::to_string_pretty(report).map_err(std::io::Error::other)?;
+    std::fs::write(&path, json)?;
+    Ok(path)
+}
+
+#[derive(Component)] pub struct RunStatisticsExportText;
+
+pub struct RunStatisticsPlugin;
+
+impl Plugin for RunStatisticsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<DamageDealtEvent>()
+            .init_resource::<RunStatistics>()
+            .add_systems(OnExit(AppState::MainMenu), reset_run_statistics_system)
+            .add_systems(Update, (
+                accumulate_damage_dealt_system,
+                record_upgrade_picks_system,
+            ))
+            .add_systems(Update, export_run_statistics_system.run_if(in_state(AppState::GameOver)));
+    }
+}
+
+fn reset_run_statistics_system(mut run_stats: ResMut<RunStatistics>) { *run_stats = RunStatistics::default(); }
+
+fn accumulate_damage_dealt_system(mut events: EventReader<DamageDealtEvent>, mut run_stats: ResMut<RunStatistics>) {
+    for event in events.read() {
+        *run_stats.damage_by_source.entry(event.source).or_insert(0) += event.amount.max(0) as i64;
+    }
+}
+
+fn record_upgrade_picks_system(mut events: EventReader<UpgradeChosenEvent>, mut run_stats: ResMut<RunStatistics>, game_state: Res<GameState>) {
+    for event in events.read() {
+        run_stats.upgrade_picks.push(UpgradePick { elapsed_secs: game_state.game_timer.elapsed_secs(), upgrade_id: event.0.id, name: event.0.name.clone() });
+    }
+}
+
+fn export_run_statistics_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    run_stats: Res<RunStatistics>,
+    game_state: Res<GameState>,
+    weapon_library: Res<AutomaticWeaponLibrary>,
+    skill_library: Res<SkillLibrary>,
+    mut status_text_query: Query<&mut Text, With<RunStatisticsExportText>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyE) { return; }
+    let report = run_stats.build_report(&game_state, &weapon_library, &skill_library);
+    let status = match export_report(&report) {
+        Ok(path) => { info!("Run statistics exported to {}", path.display()); format!("Saved to {}", path.display()) }
+        Err(err) => { warn!("Failed to export run statistics: {}", err); format!("Export failed: {}", err) }
+    };
+    for mut text in status_text_query.iter_mut() { text.sections[0].value = status.clone(); }
+}
+
+/// Builds the statistics block of the game over screen.
+pub fn spawn_run_statistics_panel(parent: &mut ChildBuilder, report: &RunStatisticsReport, asset_server: &AssetServer) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let heading_style = TextStyle { font: font.clone(), font_size: 24.0, color: Color::rgb(0.9, 0.8, 0.5) };
+    let row_style = TextStyle { font: font.clone(), font_size: 17.0, color: Color::rgba(0.85, 0.85, 0.85, 1.0) };
+
+    let minutes = (report.survival_time_secs / 60.0) as u32;
+    let seconds = (report.survival_time_secs % 60.0) as u32;
+    parent.spawn(TextBundle::from_section(
+        format!("Survived {:02}:{:02}  |  Cycle {}  |  {} horrors slain  |  {} damage dealt  |  {} echoes gathered", minutes, seconds, report.cycle_reached, report.total_kills, report.total_damage_dealt, report.experience_collected),
+        TextStyle { font: font.clone(), font_size: 22.0, color: Color::WHITE },
+    ).with_text_justify(JustifyText::Center));
+
+    let damage_rows: Vec<String> = report.damage_by_source.iter().map(|entry| format!("{}: {} ({:.1}%)", entry.source, entry.damage, entry.share_percent)).collect();
+    let kill_rows: Vec<String> = report.kills_by_horror_type.iter().map(|entry| format!("{}: {}", entry.horror_type, entry.value)).collect();
+    let taken_rows: Vec<String> = report.damage_taken_by_horror_type.iter().map(|entry| format!("{}: {}", entry.horror_type, entry.value)).collect();
+    let pick_rows: Vec<String> = report.upgrade_picks.iter().map(|pick| format!("{}. {}", pick.order, pick.name)).collect();
+
+    parent.spawn(NodeBundle {
+        style: Style { flex_direction: FlexDirection::Row, column_gap: Val::Px(30.0), padding: UiRect::all(Val::Px(12.0)), ..default() },
+        background_color: Color::rgba(0.0, 0.0, 0.0, 0.4).into(),
+        ..default()
+    }).with_children(|columns| {
+        for (title, rows) in [("Damage by Source", damage_rows), ("Horrors Slain", kill_rows), ("Damage Taken", taken_rows), ("Upgrade Order", pick_rows)] {
+            columns.spawn(NodeBundle { style: Style { flex_direction: FlexDirection::Column, row_gap: Val::Px(4.0), ..default() }, ..default() }).with_children(|column| {
+                column.spawn(TextBundle::from_section(title, heading_style.clone()));
+                if rows.is_empty() { column.spawn(TextBundle::from_section("-", row_style.clone())); }
+                for row in rows.iter().take(STATS_PANEL_MAX_ROWS) { column.spawn(TextBundle::from_section(row.clone(), row_style.clone())); }
+                if rows.len() > STATS_PANEL_MAX_ROWS { column.spawn(TextBundle::from_section(format!("... and {} more", rows.len() - STATS_PANEL_MAX_ROWS), row_style.clone())); }
+            });
+        }
+    });
+
+    parent.spawn((TextBundle::from_section("Export Statistics as JSON (E)", TextStyle { font, font_size: 20.0, color: Color::rgba(0.7, 0.7, 0.7, 1.0) }).with_text_justify(JustifyText::Center), RunStatisticsExportText));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(stats: &RunStatistics) -> RunStatisticsReport {
+        let game_state = GameState { score: 420, cycle_number: 6, ..default() };
+        stats.build_report(&game_state, &AutomaticWeaponLibrary::default(), &SkillLibrary::default())
+    }
+
+    #[test]
+    fn damage_sources_are_named_sorted_and_share_the_total() {
+        let mut stats = RunStatistics::default();
+        stats.damage_by_source.insert(DamageSource::AutomaticWeapon(AutomaticWeaponId(7)), 300);
+        stats.damage_by_source.insert(DamageSource::CircleOfWarding, 600);
+        stats.damage_by_source.insert(DamageSource::Skill(SkillId(12)), 100);
+
+        let report = build(&stats);
+        assert_eq!(report.total_damage_dealt, 1000);
+        let rows: Vec<(&str, i64)> = report.damage_by_source.iter().map(|entry| (entry.source.as_str(), entry.damage)).collect();
+        assert_eq!(rows, vec![("Circle of Warding", 600), ("Weapon #7", 300), ("Skill #12", 100)]);
+        assert!((report.damage_by_source[0].share_percent - 60.0).abs() < 1e-4);
+        assert!((report.damage_by_source.iter().map(|entry| entry.share_percent).sum::<f32>() - 100.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn kills_damage_taken_and_picks_are_tallied_in_order() {
+        let mut stats = RunStatistics::default();
+        stats.record_kill(HorrorType::FloatingEyeball);
+        for _ in 0..3 { stats.record_kill(HorrorType::CrawlingTorment); }
+        stats.record_damage_taken(HorrorType::FrenziedBehemoth, 40);
+        stats.record_damage_taken(HorrorType::FrenziedBehemoth, -5);
+        stats.record_experience(250);
+        stats.upgrade_picks.push(UpgradePick { elapsed_secs: 30.0, upgrade_id: UpgradeId(4), name: "First".to_string() });
+        stats.upgrade_picks.push(UpgradePick { elapsed_secs: 75.0, upgrade_id: UpgradeId(9), name: "Second".to_string() });
+
+        let report = build(&stats);
+        assert_eq!((report.score, report.cycle_reached, report.experience_collected), (420, 6, 250));
+        assert_eq!(report.total_kills, 4);
+        assert_eq!(report.kills_by_horror_type[0].horror_type, "CrawlingTorment");
+        assert_eq!(report.kills_by_horror_type[0].value, 3);
+        assert_eq!(report.damage_taken_by_horror_type[0].value, 40);
+        let picks: Vec<(u32, u32)> = report.upgrade_picks.iter().map(|pick| (pick.order, pick.upgrade_id)).collect();
+        assert_eq!(picks, vec![(1, 4), (2, 9)]);
+    }
+
+    #[test]
+    fn a_run_without_damage_reports_zero_shares() {
+        let mut stats = RunStatistics::default();
+        stats.damage_by_source.insert(DamageSource::RetaliationNova, 0);
+        let report = build(&stats);
+        assert_eq!(report.total_damage_dealt, 0);
+        assert_eq!(report.damage_by_source[0].share_percent, 0.0);
+    }
+}
@@ -1,13 +1,16 @@
 // src/skills.rs
-use bevy::prelude::*;
+use bevy::{prelude::*, sprite::Anchor, ecs::system::SystemParam};
 use std::time::Duration;
 use crate::{
-    survivor::{Survivor, SURVIVOR_SIZE},
+    survivor::{Survivor, SURVIVOR_SIZE, MAX_ACTIVE_SKILLS},
     game::AppState,
     components::{Velocity, Damage, Lifetime, Health},
-    horror::Horror,
-    visual_effects::spawn_damage_text,
-    audio::{PlaySoundEvent, SoundEffect},
+    horror::{Horror, Knockback},
+    elite_affixes::{AffixWard, HorrorHitTarget, HorrorHitTargetItem, try_mirror_reflect, apply_horror_damage},
+    visual_effects::{HitFlash, HitstopEvent, HorrorHitWriters, HEAVY_HIT_KNOCKBACK_THRESHOLD, HEAVY_HIT_HITSTOP_SECS},
+    audio::{PlaySoundAtEvent, SoundEffect},
+    run_statistics::{DamageDealtEvent, DamageSource},
+    critical_hits::CritStats,
     // We might need ExplosionEffect or a similar component here later
     // For now, let's define the impact properties on ActiveSkillInstance
 };
@@ -24,6 +27,7 @@ pub enum SkillEffectType {
         color: Color,
         lifetime_secs: f32,
         piercing: u32,
+        knockback: f32,
     },
     AreaOfEffect {
         base_damage_per_tick: i32,
@@ -56,6 +60,51 @@ pub enum SkillEffectType {
         amount: i32,
         duration_secs: f32,
     },
+    Dash {
+        distance: f32,
+        duration_secs: f32,
+        invulnerability_secs: f32,
+    },
+    ChanneledBeam {
+        damage_per_tick: i32,
+        length: f32,
+        width: f32,
+        tick_interval_secs: f32,
+        duration_secs: f32,
+        color: Color,
+    },
+    Vortex {
+        damage_per_tick: i32,
+        radius: f32,
+        pull_speed: f32,
+        tick_interval_secs: f32,
+        duration_secs: f32,
+        color: Color,
+    },
+    SummonCompanion {
+        damage: i32,
+        attack_range: f32,
+        attack_interval_secs: f32,
+        projectile_speed: f32,
+        move_speed: f32,
+        duration_secs: f32,
+        color: Color,
+    },
+}
+
+/// How a skill picks where it lands when the skill key is released.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub enum SkillTargeting {
+    /// Fired from the survivor along the aim direction.
+    Line,
+    /// A spread of fragments fanning out along the aim direction.
+    Cone { angle_degrees: f32, fragments: u32 },
+    /// Placed at the cursor, clamped to `max_range` from the survivor.
+    GroundTarget { max_range: f32 },
+    /// Aimed at the closest horror within `max_range`, falling back to the aim direction.
+    NearestEnemy { max_range: f32 },
+    /// Centred on the survivor.
+    SelfCentered,
 }
 
 #[derive(Debug, Clone, Reflect)]
@@ -65,6 +114,137 @@ pub struct SkillDefinition {
     pub description: String,
     pub base_cooldown: Duration,
     pub effect: SkillEffectType,
+    pub targeting: SkillTargeting,
+    /// Replace the survivor's base crit chance and multiplier for this skill; crit bonuses still apply on top.
+    pub crit_chance_override: Option<f32>,
+    pub crit_multiplier_override: Option<f32>,
+}
+
+impl SkillDefinition {
+    /// Reach shown by the range indicator while the skill key is held. Zero for skills centred on the survivor.
+    pub fn targeting_range(&self, instance: &ActiveSkillInstance) -> f32 {
+        match self.targeting {
+            SkillTargeting::GroundTarget { max_range } | SkillTargeting::NearestEnemy { max_range } => max_range,
+            SkillTargeting::Cone { .. } => CONE_FRAGMENT_SPEED * CONE_FRAGMENT_LIFETIME_SECS,
+            SkillTargeting::Line => match self.effect {
+                SkillEffectType::Projectile { speed, lifetime_secs, .. } => speed * lifetime_secs,
+                SkillEffectType::Dash { distance, .. } => distance * instance.range_multiplier,
+                SkillEffectType::ChanneledBeam { length, .. } => length * instance.range_multiplier,
+                _ => 0.0,
+            },
+            SkillTargeting::SelfCentered => 0.0,
+        }
+    }
+
+    /// Width of the strip drawn by the line indicator.
+    pub fn line_width(&self) -> f32 {
+        match self.effect {
+            SkillEffectType::Projectile { size, .. } => size.x.max(8.0),
+            SkillEffectType::ChanneledBeam { width, .. } => width,
+            SkillEffectType::Dash { .. } => SURVIVOR_SIZE.x,
+            _ => 8.0,
+        }
+    }
+
+    /// Radius of the area the skill affects once cast, including the instance's radius upgrades.
+    pub fn effect_radius(&self, instance: &ActiveSkillInstance) -> f32 {
+        let radius_multiplier = instance.aoe_radius_multiplier.max(0.1);
+        match self.effect {
+            SkillEffectType::AreaOfEffect { base_radius, .. } => base_radius * radius_multiplier,
+            SkillEffectType::SummonSentry { sentry_radius, .. } => sentry_radius * radius_multiplier,
+            SkillEffectType::FreezingNova { radius, .. } => radius * radius_multiplier,
+            SkillEffectType::Projectile { size, .. } => instance.impact_aoe_radius.max(size.x / 2.0),
+            SkillEffectType::Vortex { radius, .. } => radius * instance.range_multiplier,
+            SkillEffectType::SummonCompanion { attack_range, .. } => attack_range * instance.range_multiplier,
+            SkillEffectType::ChanneledBeam { width, .. } => width / 2.0,
+            SkillEffectType::SurvivorBuff { .. } | SkillEffectType::TemporaryShield { .. } | SkillEffectType::Dash { .. } => SURVIVOR_SIZE.x * 0.75,
+        }
+    }
+}
+
+/// Where a skill lands once targeting has been resolved.
+#[derive(Debug, Clone, Copy)]
+pub struct SkillCastTarget { pub position: Vec2, pub direction: Vec2, }
+
+pub fn resolve_skill_target(
+    targeting: SkillTargeting,
+    survivor_pos: Vec2,
+    aim_direction: Vec2,
+    aim_world_position: Vec2,
+    horror_positions: impl Iterator<Item = Vec2>,
+) -> SkillCastTarget {
+    match targeting {
+        SkillTargeting::Line | SkillTargeting::Cone { .. } | SkillTargeting::SelfCentered => SkillCastTarget { position: survivor_pos, direction: aim_direction },
+        SkillTargeting::GroundTarget { max_range } => {
+            let offset = (aim_world_position - survivor_pos).clamp_length_max(max_range);
+            SkillCastTarget { position: survivor_pos + offset, direction: aim_direction }
+        }
+        SkillTargeting::NearestEnemy { max_range } => {
+            let nearest = horror_positions
+                .filter(|pos| pos.distance_squared(survivor_pos) <= max_range * max_range)
+                .min_by(|a, b| a.distance_squared(survivor_pos).total_cmp(&b.distance_squared(survivor_pos)));
+            match nearest {
+                Some(target_pos) => SkillCastTarget { position: target_pos, direction: (target_pos - survivor_pos).try_normalize().unwrap_or(aim_direction) },
+                None => SkillCastTarget { position: survivor_pos, direction: aim_direction },
+            }
+        }
+    }
+}
+
+/// Which skill slot is currently being aimed; the skill is cast when its key is released.
+#[derive(Resource, Default)]
+pub struct SkillAimState { pub held_slot: Option<usize>, }
+
+const SKILL_SLOT_KEYS: [KeyCode; MAX_ACTIVE_SKILLS] = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::KeyE, KeyCode::KeyR];
+
+fn skill_slot_just_pressed(slot: usize, mouse: &ButtonInput<MouseButton>, keyboard: &ButtonInput<KeyCode>) -> bool { keyboard.just_pressed(SKILL_SLOT_KEYS[slot]) || (slot == 0 && mouse.just_pressed(MouseButton::Right)) }
+fn skill_slot_held(slot: usize, mouse: &ButtonInput<MouseButton>, keyboard: &ButtonInput<KeyCode>) -> bool { keyboard.pressed(SKILL_SLOT_KEYS[slot]) || (slot == 0 && mouse.pressed(MouseButton::Right)) }
+
+/// The skill keys together with the slot being aimed.
+#[derive(SystemParam)]
+pub struct SkillAimInput<'w> {
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    state: ResMut<'w, SkillAimState>,
+}
+
+impl SkillAimInput<'_> {
+    /// Pressing a skill key starts aiming; releasing it returns the slot to cast. Escape cancels the aim.
+    /// Channeled skills skip the aim and cast on press instead, then last for as long as the key is held.
+    fn advance(&mut self, equipped_skills: &[ActiveSkillInstance], skill_library: &SkillLibrary) -> Option<usize> {
+        if let Some(held_slot) = self.state.held_slot {
+            if self.keyboard.just_pressed(KeyCode::Escape) { self.state.held_slot = None; }
+            else if !skill_slot_held(held_slot, &self.mouse, &self.keyboard) { self.state.held_slot = None; return Some(held_slot); }
+        } else if let Some(slot) = (0..MAX_ACTIVE_SKILLS).find(|&slot| skill_slot_just_pressed(slot, &self.mouse, &self.keyboard)) {
+            let instance = equipped_skills.get(slot)?;
+            let is_channeled = skill_library.get_skill_definition(instance.definition_id).is_some_and(|def| matches!(def.effect, SkillEffectType::ChanneledBeam { .. }));
+            if is_channeled { return Some(slot); }
+            self.state.held_slot = Some(slot);
+        }
+        None
+    }
+}
+
+const CONE_FRAGMENT_SPEED: f32 = 400.0;
+const CONE_FRAGMENT_LIFETIME_SECS: f32 = 0.4;
+const CONE_FRAGMENT_BASE_DAMAGE: i32 = 15;
+
+const AIM_INDICATOR_Z: f32 = 0.1;
+const AIM_RANGE_INDICATOR_COLOR: Color = Color::rgba(0.6, 0.3, 0.9, 0.15);
+const AIM_AREA_INDICATOR_COLOR: Color = Color::rgba(0.8, 0.4, 1.0, 0.35);
+const AIM_AREA_INDICATOR_COOLDOWN_COLOR: Color = Color::rgba(0.5, 0.5, 0.5, 0.25);
+
+#[derive(Component)] pub struct SkillRangeIndicator;
+#[derive(Component)] pub struct SkillAreaIndicator;
+
+type AimIndicatorFilter = Or<(With<SkillRangeIndicator>, With<SkillAreaIndicator>)>;
+type AimIndicatorQuery<'w, 's, Marker, Other> = Query<'w, 's, (Entity, &'static mut Transform, &'static mut Sprite), (With<Marker>, Without<Other>, Without<Survivor>, Without<Horror>)>;
+
+/// The range ring and the area marker drawn while a skill is being aimed.
+#[derive(SystemParam)]
+struct AimIndicators<'w, 's> {
+    range: AimIndicatorQuery<'w, 's, SkillRangeIndicator, SkillAreaIndicator>,
+    area: AimIndicatorQuery<'w, 's, SkillAreaIndicator, SkillRangeIndicator>,
 }
 
 #[derive(Component, Debug, Clone, Reflect)]
@@ -77,6 +257,9 @@ pub struct ActiveSkillInstance {
     pub aoe_radius_multiplier: f32, // For skills that are inherently AoE
     pub impact_aoe_radius: f32,      // New: Radius of explosion on projectile impact
     pub impact_aoe_damage_fraction: f32, // New: Fraction of main projectile damage for AoE
+    pub knockback_bonus: f32,
+    pub duration_bonus_secs: f32, // Extra lifetime for channeled/summoned skills, extra i-frames for dashes
+    pub range_multiplier: f32,    // Dash distance, beam length, vortex radius, companion attack range
 }
 
 impl ActiveSkillInstance {
@@ -90,6 +273,9 @@ impl ActiveSkillInstance {
             aoe_radius_multiplier: 1.0,
             impact_aoe_radius: 0.0, // Default to no impact AoE
             impact_aoe_damage_fraction: 0.0, // Default to no impact AoE damage
+            knockback_bonus: 0.0,
+            duration_bonus_secs: 0.0,
+            range_multiplier: 1.0,
         }
     }
     pub fn tick_cooldown(&mut self, delta: Duration) { if self.current_cooldown > Duration::ZERO { self.current_cooldown = self.current_cooldown.saturating_sub(delta); } }
@@ -109,13 +295,35 @@ pub struct SkillProjectile {
     // Properties for on-impact AoE, copied from ActiveSkillInstance at spawn time
     pub impact_aoe_radius: f32,
     pub impact_aoe_damage_fraction: f32,
+    pub knockback_force: f32,
+    pub crit: CritStats,
 }
 
-#[derive(Component)] pub struct ActiveSkillAoEEffect { pub skill_id: SkillId, pub actual_damage_per_tick: i32, pub actual_radius_sq: f32, pub tick_timer: Timer, pub lifetime_timer: Timer, pub already_hit_this_tick: Vec<Entity>, }
+const CONE_FRAGMENT_KNOCKBACK: f32 = 60.0;
+
+const COMPANION_BOLT_SIZE: Vec2 = Vec2::new(8.0, 8.0);
+const COMPANION_BOLT_KNOCKBACK: f32 = 30.0;
+const COMPANION_SIZE: Vec2 = Vec2::new(28.0, 28.0);
+const COMPANION_FOLLOW_OFFSET: Vec2 = Vec2::new(-45.0, 35.0);
+
+#[derive(Component, Debug)] pub struct SurvivorDash { pub velocity: Vec2, pub timer: Timer, }
+
+/// Beam that stays attached to the survivor along the aim direction while it lasts, or until `slot`'s key is released or the survivor dashes.
+#[derive(Component)]
+pub struct ChanneledBeamEffect { pub skill_id: SkillId, pub slot: usize, pub damage_per_tick: i32, pub crit: CritStats, pub length: f32, pub width: f32, pub tick_timer: Timer, pub lifetime_timer: Timer, }
+
+#[derive(Component)]
+pub struct VortexEffect { pub skill_id: SkillId, pub damage_per_tick: i32, pub crit: CritStats, pub radius: f32, pub pull_speed: f32, pub tick_timer: Timer, pub lifetime_timer: Timer, }
+
+/// Summoned ally that trails the survivor and shoots the nearest horror in range.
+#[derive(Component)]
+pub struct CompanionSummon { pub skill_id: SkillId, pub damage: i32, pub crit: CritStats, pub attack_range: f32, pub attack_timer: Timer, pub projectile_speed: f32, pub move_speed: f32, pub lifetime_timer: Timer, pub color: Color, }
+
+#[derive(Component)] pub struct ActiveSkillAoEEffect { pub skill_id: SkillId, pub actual_damage_per_tick: i32, pub crit: CritStats, pub actual_radius_sq: f32, pub tick_timer: Timer, pub lifetime_timer: Timer, pub already_hit_this_tick: Vec<Entity>, }
 #[derive(Component, Debug)] pub struct SurvivorBuffEffect { pub speed_multiplier_bonus: f32, pub fire_rate_multiplier_bonus: f32, pub duration_timer: Timer, }
 
 #[derive(Component, Debug, Reflect, Default)] #[reflect(Component)]
-pub struct FreezingNovaEffect { pub damage: i32, pub radius_sq: f32, pub lifetime_timer: Timer, pub slow_multiplier: f32, pub slow_duration_secs: f32, pub already_hit_entities: Vec<Entity>, }
+pub struct FreezingNovaEffect { pub skill_id: SkillId, pub damage: i32, pub crit: CritStats, pub radius_sq: f32, pub lifetime_timer: Timer, pub slow_multiplier: f32, pub slow_duration_secs: f32, pub already_hit_entities: Vec<Entity>, }
 
 #[derive(Component, Debug, Reflect, Default)]
 #[reflect(Component)]
@@ -134,29 +342,42 @@ impl Plugin for SkillsPlugin {
         app .register_type::<SkillId>() .register_type::<SkillEffectType>() .register_type::<SkillDefinition>() .register_type::<ActiveSkillInstance>() .register_type::<SkillLibrary>()
             .register_type::<FreezingNovaEffect>()
             .register_type::<ActiveShield>()
+            .register_type::<SkillTargeting>()
             .init_resource::<SkillLibrary>()
+            .init_resource::<SkillAimState>()
             .add_systems(Startup, populate_skill_library)
             .add_systems(Update, (
                 active_skill_cooldown_recharge_system,
                 survivor_skill_input_system,
+                skill_aim_indicator_system,
                 skill_projectile_lifetime_system,
                 skill_projectile_collision_system, // This will need to trigger the AoE
                 active_skill_aoe_system,
                 survivor_buff_management_system,
                 freezing_nova_effect_damage_system,
                 active_shield_timer_system,
-            ).chain().run_if(in_state(AppState::InGame)) );
+                survivor_dash_system,
+                break_channeled_beams_system,
+                channeled_beam_system,
+                vortex_effect_system,
+                companion_summon_system,
+            ).chain().run_if(in_state(AppState::InGame)) )
+            .add_systems(OnExit(AppState::InGame), clear_skill_aim_system);
     }
 }
 
 fn populate_skill_library(mut library: ResMut<SkillLibrary>) {
-    library.skills.push(SkillDefinition { id: SkillId(1), name: "Eldritch Bolt".to_string(), description: "Fires a bolt of arcane energy.".to_string(), base_cooldown: Duration::from_secs_f32(1.5), effect: SkillEffectType::Projectile { base_damage: 25, speed: 650.0, size: Vec2::new(12.0, 28.0), color: Color::rgb(0.6, 0.1, 0.9), lifetime_secs: 2.5, piercing: 0, }, });
-    library.skills.push(SkillDefinition { id: SkillId(2), name: "Mind Shatter".to_string(), description: "Unleashes a short-range psychic burst in a wide arc.".to_string(), base_cooldown: Duration::from_secs(4), effect: SkillEffectType::AreaOfEffect { base_damage_per_tick: 35, base_radius: 175.0, tick_interval_secs: 0.1, duration_secs: 0.2, color: Color::rgba(0.8, 0.2, 1.0, 0.7), }, });
-    library.skills.push(SkillDefinition { id: SkillId(3), name: "Void Lance".to_string(), description: "Projects a slow but potent lance of void energy that pierces foes.".to_string(), base_cooldown: Duration::from_secs_f32(2.5), effect: SkillEffectType::Projectile { base_damage: 40, speed: 400.0, size: Vec2::new(10.0, 40.0), color: Color::rgb(0.1, 0.0, 0.2), lifetime_secs: 3.0, piercing: 2, }, });
-    library.skills.push(SkillDefinition { id: SkillId(4), name: "Fleeting Agility".to_string(), description: "Briefly enhance your speed and reflexes.".to_string(), base_cooldown: Duration::from_secs(20), effect: SkillEffectType::SurvivorBuff { speed_multiplier_bonus: 0.30, fire_rate_multiplier_bonus: 0.25, duration_secs: 5.0, }, });
-    library.skills.push(SkillDefinition { id: SkillId(5), name: "Glacial Nova".to_string(), description: "Emits a chilling nova, damaging and slowing nearby foes.".to_string(), base_cooldown: Duration::from_secs(10), effect: SkillEffectType::FreezingNova { damage: 20, radius: 200.0, nova_duration_secs: 0.5, slow_multiplier: 0.5, slow_duration_secs: 3.0, color: Color::rgba(0.5, 0.8, 1.0, 0.6), }, });
-    library.skills.push(SkillDefinition { id: SkillId(6), name: "Psychic Sentry".to_string(), description: "Summons a stationary sentry that pulses with psychic energy.".to_string(), base_cooldown: Duration::from_secs(18), effect: SkillEffectType::SummonSentry { sentry_damage_per_tick: 15, sentry_radius: 100.0, sentry_tick_interval_secs: 0.75, sentry_duration_secs: 8.0, sentry_color: Color::rgba(0.2, 0.7, 0.9, 0.5), }, });
-    library.skills.push(SkillDefinition { id: SkillId(7), name: "Ethereal Ward".to_string(), description: "Briefly manifest an ethereal shield that absorbs incoming damage.".to_string(), base_cooldown: Duration::from_secs(25), effect: SkillEffectType::TemporaryShield { amount: 50, duration_secs: 5.0, }, });
+    library.skills.push(SkillDefinition { id: SkillId(1), name: "Eldritch Bolt".to_string(), description: "Fires a bolt of arcane energy.".to_string(), base_cooldown: Duration::from_secs_f32(1.5), effect: SkillEffectType::Projectile { base_damage: 25, speed: 650.0, size: Vec2::new(12.0, 28.0), color: Color::rgb(0.6, 0.1, 0.9), lifetime_secs: 2.5, piercing: 0, knockback: 120.0, }, targeting: SkillTargeting::NearestEnemy { max_range: 600.0 }, crit_chance_override: Some(0.1), crit_multiplier_override: None, });
+    library.skills.push(SkillDefinition { id: SkillId(2), name: "Mind Shatter".to_string(), description: "Detonates a psychic burst at the chosen spot.".to_string(), base_cooldown: Duration::from_secs(4), effect: SkillEffectType::AreaOfEffect { base_damage_per_tick: 35, base_radius: 175.0, tick_interval_secs: 0.1, duration_secs: 0.2, color: Color::rgba(0.8, 0.2, 1.0, 0.7), }, targeting: SkillTargeting::GroundTarget { max_range: 450.0 }, crit_chance_override: None, crit_multiplier_override: None, });
+    library.skills.push(SkillDefinition { id: SkillId(3), name: "Void Lance".to_string(), description: "Projects a slow but potent lance of void energy that pierces foes.".to_string(), base_cooldown: Duration::from_secs_f32(2.5), effect: SkillEffectType::Projectile { base_damage: 40, speed: 400.0, size: Vec2::new(10.0, 40.0), color: Color::rgb(0.1, 0.0, 0.2), lifetime_secs: 3.0, piercing: 2, knockback: 320.0, }, targeting: SkillTargeting::Line, crit_chance_override: None, crit_multiplier_override: Some(2.25), });
+    library.skills.push(SkillDefinition { id: SkillId(4), name: "Fleeting Agility".to_string(), description: "Briefly enhance your speed and reflexes.".to_string(), base_cooldown: Duration::from_secs(20), effect: SkillEffectType::SurvivorBuff { speed_multiplier_bonus: 0.30, fire_rate_multiplier_bonus: 0.25, duration_secs: 5.0, }, targeting: SkillTargeting::SelfCentered, crit_chance_override: None, crit_multiplier_override: None, });
+    library.skills.push(SkillDefinition { id: SkillId(5), name: "Glacial Nova".to_string(), description: "Emits a chilling nova, damaging and slowing nearby foes.".to_string(), base_cooldown: Duration::from_secs(10), effect: SkillEffectType::FreezingNova { damage: 20, radius: 200.0, nova_duration_secs: 0.5, slow_multiplier: 0.5, slow_duration_secs: 3.0, color: Color::rgba(0.5, 0.8, 1.0, 0.6), }, targeting: SkillTargeting::SelfCentered, crit_chance_override: None, crit_multiplier_override: None, });
+    library.skills.push(SkillDefinition { id: SkillId(6), name: "Psychic Sentry".to_string(), description: "Summons a stationary sentry that pulses with psychic energy.".to_string(), base_cooldown: Duration::from_secs(18), effect: SkillEffectType::SummonSentry { sentry_damage_per_tick: 15, sentry_radius: 100.0, sentry_tick_interval_secs: 0.75, sentry_duration_secs: 8.0, sentry_color: Color::rgba(0.2, 0.7, 0.9, 0.5), }, targeting: SkillTargeting::GroundTarget { max_range: 350.0 }, crit_chance_override: None, crit_multiplier_override: None, });
+    library.skills.push(SkillDefinition { id: SkillId(7), name: "Ethereal Ward".to_string(), description: "Briefly manifest an ethereal shield that absorbs incoming damage.".to_string(), base_cooldown: Duration::from_secs(25), effect: SkillEffectType::TemporaryShield { amount: 50, duration_secs: 5.0, }, targeting: SkillTargeting::SelfCentered, crit_chance_override: None, crit_multiplier_override: None, });
+    library.skills.push(SkillDefinition { id: SkillId(8), name: "Shadow Step".to_string(), description: "Blink through the dark in the aimed direction, untouchable while in motion.".to_string(), base_cooldown: Duration::from_secs(6), effect: SkillEffectType::Dash { distance: 220.0, duration_secs: 0.15, invulnerability_secs: 0.35, }, targeting: SkillTargeting::Line, crit_chance_override: None, crit_multiplier_override: None, });
+    library.skills.push(SkillDefinition { id: SkillId(9), name: "Abyssal Gaze".to_string(), description: "Channel a searing beam that burns everything along your gaze.".to_string(), base_cooldown: Duration::from_secs(12), effect: SkillEffectType::ChanneledBeam { damage_per_tick: 8, length: 420.0, width: 24.0, tick_interval_secs: 0.15, duration_secs: 2.5, color: Color::rgba(0.7, 0.2, 1.0, 0.8), }, targeting: SkillTargeting::Line, crit_chance_override: Some(0.02), crit_multiplier_override: None, });
+    library.skills.push(SkillDefinition { id: SkillId(10), name: "Maelstrom of Madness".to_string(), description: "Tear open a vortex that drags nearby horrors into its churning heart.".to_string(), base_cooldown: Duration::from_secs(15), effect: SkillEffectType::Vortex { damage_per_tick: 6, radius: 180.0, pull_speed: 220.0, tick_interval_secs: 0.4, duration_secs: 3.5, color: Color::rgba(0.3, 0.1, 0.5, 0.6), }, targeting: SkillTargeting::GroundTarget { max_range: 400.0 }, crit_chance_override: Some(0.02), crit_multiplier_override: None, });
+    library.skills.push(SkillDefinition { id: SkillId(11), name: "Bound Familiar".to_string(), description: "Summon a familiar that follows you and spits bolts at nearby horrors.".to_string(), base_cooldown: Duration::from_secs(30), effect: SkillEffectType::SummonCompanion { damage: 10, attack_range: 300.0, attack_interval_secs: 0.6, projectile_speed: 500.0, move_speed: 320.0, duration_secs: 15.0, color: Color::rgb(0.5, 0.9, 0.6), }, targeting: SkillTargeting::SelfCentered, crit_chance_override: None, crit_multiplier_override: None, });
 }
 
 fn active_skill_cooldown_recharge_system(time: Res<Time>, mut player_query: Query<&mut Survivor>,) { if let Ok(mut player) = player_query.get_single_mut() { for skill_instance in player.equipped_skills.iter_mut() { skill_instance.tick_cooldown(time.delta()); } } }
@@ -164,27 +385,24 @@ fn active_skill_cooldown_recharge_system(time: Res<Time>, mut player_query: Quer
 fn survivor_skill_input_system(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut aim_input: SkillAimInput,
     mut player_query: Query<(Entity, &mut Survivor, &Transform)>,
+    horror_query: Query<&Transform, (With<Horror>, Without<Survivor>)>,
     skill_library: Res<SkillLibrary>,
-    mut sound_event_writer: EventWriter<PlaySoundEvent>,
+    mut sound_event_writer: EventWriter<PlaySoundAtEvent>,
 ) {
     if let Ok((player_entity, mut player, player_transform)) = player_query.get_single_mut() {
-        let mut skill_to_trigger_idx: Option<usize> = None;
-        if mouse_button_input.just_pressed(MouseButton::Right) { skill_to_trigger_idx = Some(0); }
-        else if keyboard_input.just_pressed(KeyCode::Digit1) { skill_to_trigger_idx = Some(0); }
-        else if keyboard_input.just_pressed(KeyCode::Digit2) { skill_to_trigger_idx = Some(1); }
-        else if keyboard_input.just_pressed(KeyCode::Digit3) { skill_to_trigger_idx = Some(2); }
-        else if keyboard_input.just_pressed(KeyCode::KeyE) { skill_to_trigger_idx = Some(3); }
-        else if keyboard_input.just_pressed(KeyCode::KeyR) { skill_to_trigger_idx = Some(4); }
-
-        if let Some(idx) = skill_to_trigger_idx { if idx >= player.equipped_skills.len() { return; } let current_aim_direction = player.aim_direction; 
+        let skill_to_trigger_idx = aim_input.advance(&player.equipped_skills, &skill_library);
+
+        if let Some(idx) = skill_to_trigger_idx { if idx >= player.equipped_skills.len() { return; }
             // Clone the specific skill instance we are about to use
             let skill_instance_snapshot = player.equipped_skills[idx].clone();
             
             if skill_instance_snapshot.is_ready() { if let Some(skill_def) = skill_library.get_skill_definition(skill_instance_snapshot.definition_id) {
                 let mut effect_was_triggered = false;
+                let cast_target = resolve_skill_target(skill_def.targeting, player_transform.translation.truncate(), player.aim_direction, player.aim_world_position, horror_query.iter().map(|t| t.translation.truncate()));
+                let current_aim_direction = cast_target.direction;
+                let crit = CritStats::for_attack(&player, skill_def.crit_chance_override, skill_def.crit_multiplier_override);
 
                 let mut projectile_damage = 0;
                 let projectile_bounces: u32 = 0; 
@@ -229,7 +447,7 @@ fn survivor_skill_input_system(
                     SkillEffectType::TemporaryShield { amount, .. } => {
                         shield_amount = *amount + skill_instance_snapshot.flat_damage_bonus;
                     }
-                    SkillEffectType::SurvivorBuff { .. } => {}
+                    SkillEffectType::SurvivorBuff { .. } | SkillEffectType::Dash { .. } | SkillEffectType::ChanneledBeam { .. } | SkillEffectType::Vortex { .. } | SkillEffectType::SummonCompanion { .. } => {}
                 }
 
                 effective_cooldown_multiplier = effective_cooldown_multiplier.max(0.1);
@@ -240,7 +458,7 @@ fn survivor_skill_input_system(
                 if matches!(skill_def.effect, SkillEffectType::FreezingNova { .. }) { effective_nova_radius *= effective_aoe_radius_multiplier; }
 
                 match &skill_def.effect {
-                    SkillEffectType::Projectile { speed, size, color, piercing,.. } => { 
+                    SkillEffectType::Projectile { speed, size, color, piercing, knockback, .. } => { 
                         if current_aim_direction != Vec2::ZERO {
                             let projectile_spawn_position = player_transform.translation + current_aim_direction.extend(0.0) * (SURVIVOR_SIZE.y / 2.0 + size.y / 2.0);
                             commands.spawn((
@@ -252,6 +470,8 @@ fn survivor_skill_input_system(
                                     already_hit_by_this_projectile: Vec::new(),
                                     impact_aoe_radius: impact_aoe_rad, // Pass impact AoE properties
                                     impact_aoe_damage_fraction: impact_aoe_dmg_frac,
+                                    knockback_force: *knockback + skill_instance_snapshot.knockback_bonus,
+                                    crit,
                                 }, 
                                 Velocity(current_aim_direction * *speed),
                                 Damage(projectile_damage), // Main projectile damage
@@ -262,16 +482,16 @@ fn survivor_skill_input_system(
                         }
                     }
                     SkillEffectType::AreaOfEffect { tick_interval_secs, duration_secs, color, .. } => {
-                        if skill_def.id == SkillId(2) { 
-                            let num_projectiles = 5;
-                            let spread_angle_rad = 60.0f32.to_radians();
+                        if let SkillTargeting::Cone { angle_degrees, fragments } = skill_def.targeting { 
+                            let num_projectiles = fragments.max(2);
+                            let spread_angle_rad = angle_degrees.to_radians();
                             let angle_step = spread_angle_rad / (num_projectiles -1) as f32;
                             let base_angle = current_aim_direction.to_angle() - spread_angle_rad / 2.0;
                             for i in 0..num_projectiles {
                                 let angle = base_angle + angle_step * i as f32;
                                 let direction = Vec2::new(angle.cos(), angle.sin());
                                 let projectile_spawn_position = player_transform.translation + direction.extend(0.0) * (SURVIVOR_SIZE.y / 2.0 + 10.0 / 2.0);
-                                let mind_shatter_fragment_damage = 15 + skill_instance_snapshot.flat_damage_bonus;
+                                let cone_fragment_damage = CONE_FRAGMENT_BASE_DAMAGE + skill_instance_snapshot.flat_damage_bonus;
                                 commands.spawn((
                                     SpriteBundle { texture: asset_server.load("sprites/mind_shatter_fragment_placeholder.png"), sprite: Sprite { custom_size: Some(Vec2::new(10.0, 10.0)), color: *color, ..default()}, transform: Transform::from_translation(projectile_spawn_position).with_rotation(Quat::from_rotation_z(direction.y.atan2(direction.x))), ..default()},
                                     SkillProjectile { 
@@ -279,21 +499,23 @@ fn survivor_skill_input_system(
                                         piercing_left: 0, 
                                         bounces_left: 0, 
                                         already_hit_by_this_projectile: Vec::new(),
-                                        impact_aoe_radius: 0.0, // Mind shatter fragments don't have their own impact AoE by default
+                                        impact_aoe_radius: 0.0, // Cone fragments don't have their own impact AoE by default
                                         impact_aoe_damage_fraction: 0.0,
+                                        knockback_force: CONE_FRAGMENT_KNOCKBACK,
+                                        crit,
                                     }, 
-                                    Velocity(direction * 400.0), Damage(mind_shatter_fragment_damage), Lifetime { timer: Timer::from_seconds(0.4, TimerMode::Once) }, Name::new(format!("MindShatterFragment_{}", i)),
+                                    Velocity(direction * CONE_FRAGMENT_SPEED), Damage(cone_fragment_damage), Lifetime { timer: Timer::from_seconds(CONE_FRAGMENT_LIFETIME_SECS, TimerMode::Once) }, Name::new(format!("ConeFragment_{}_{}", skill_def.name, i)),
                                 ));
                             }
                             effect_was_triggered = true;
                         } else { 
-                            let aoe_spawn_position = player_transform.translation;
-                            commands.spawn(( SpriteBundle { texture: asset_server.load("sprites/generic_aoe_placeholder.png"), sprite: Sprite { custom_size: Some(Vec2::splat(effective_aoe_radius * 2.0)), color: *color, ..default()}, transform: Transform::from_translation(aoe_spawn_position.truncate().extend(0.2)), ..default() }, ActiveSkillAoEEffect { skill_id: skill_def.id, actual_damage_per_tick: aoe_damage_per_tick, actual_radius_sq: effective_aoe_radius.powi(2), tick_timer: Timer::from_seconds(*tick_interval_secs, TimerMode::Repeating), lifetime_timer: Timer::from_seconds(*duration_secs, TimerMode::Once), already_hit_this_tick: Vec::new(), }, Name::new(format!("SkillAoE_{}", skill_def.name)), )); effect_was_triggered = true;
+                            let aoe_spawn_position = cast_target.position.extend(player_transform.translation.z);
+                            commands.spawn(( SpriteBundle { texture: asset_server.load("sprites/generic_aoe_placeholder.png"), sprite: Sprite { custom_size: Some(Vec2::splat(effective_aoe_radius * 2.0)), color: *color, ..default()}, transform: Transform::from_translation(aoe_spawn_position.truncate().extend(0.2)), ..default() }, ActiveSkillAoEEffect { skill_id: skill_def.id, actual_damage_per_tick: aoe_damage_per_tick, crit, actual_radius_sq: effective_aoe_radius.powi(2), tick_timer: Timer::from_seconds(*tick_interval_secs, TimerMode::Repeating), lifetime_timer: Timer::from_seconds(*duration_secs, TimerMode::Once), already_hit_this_tick: Vec::new(), }, Name::new(format!("SkillAoE_{}", skill_def.name)), )); effect_was_triggered = true;
                         }
                     }
                     SkillEffectType::SurvivorBuff { speed_multiplier_bonus, fire_rate_multiplier_bonus, duration_secs } => { commands.entity(player_entity).insert(SurvivorBuffEffect { speed_multiplier_bonus: *speed_multiplier_bonus, fire_rate_multiplier_bonus: *fire_rate_multiplier_bonus, duration_timer: Timer::from_seconds(*duration_secs, TimerMode::Once), }); effect_was_triggered = true; }
-                    SkillEffectType::SummonSentry { sentry_tick_interval_secs, sentry_duration_secs, sentry_color, .. } => { let sentry_spawn_position = player_transform.translation.truncate().extend(0.15); commands.spawn(( SpriteBundle { texture: asset_server.load("sprites/psychic_sentry_placeholder.png"), sprite: Sprite { custom_size: Some(Vec2::splat(effective_sentry_radius * 0.5)), color: *sentry_color, ..default() }, transform: Transform::from_translation(sentry_spawn_position), ..default() }, ActiveSkillAoEEffect { skill_id: skill_def.id, actual_damage_per_tick: sentry_damage_val, actual_radius_sq: effective_sentry_radius.powi(2), tick_timer: Timer::from_seconds(*sentry_tick_interval_secs, TimerMode::Repeating), lifetime_timer: Timer::from_seconds(*sentry_duration_secs, TimerMode::Once), already_hit_this_tick: Vec::new(), }, Name::new("PsychicSentry"), )); effect_was_triggered = true; }
-                    SkillEffectType::FreezingNova { nova_duration_secs, slow_multiplier, slow_duration_secs, color, .. } => { let nova_spawn_position = player_transform.translation; commands.spawn(( SpriteBundle { texture: asset_server.load("sprites/frost_nova_placeholder.png"), sprite: Sprite { custom_size: Some(Vec2::splat(0.1)), color: *color, ..default() }, transform: Transform::from_translation(nova_spawn_position.truncate().extend(0.25)), ..default() }, FreezingNovaEffect { damage: nova_damage_val, radius_sq: effective_nova_radius.powi(2), lifetime_timer: Timer::from_seconds(*nova_duration_secs, TimerMode::Once), slow_multiplier: *slow_multiplier, slow_duration_secs: *slow_duration_secs, already_hit_entities: Vec::new(), }, Name::new("GlacialNovaEffect"), )); effect_was_triggered = true; sound_event_writer.send(PlaySoundEvent(SoundEffect::RitualCast)); }
+                    SkillEffectType::SummonSentry { sentry_tick_interval_secs, sentry_duration_secs, sentry_color, .. } => { let sentry_spawn_position = cast_target.position.extend(0.15); commands.spawn(( SpriteBundle { texture: asset_server.load("sprites/psychic_sentry_placeholder.png"), sprite: Sprite { custom_size: Some(Vec2::splat(effective_sentry_radius * 0.5)), color: *sentry_color, ..default() }, transform: Transform::from_translation(sentry_spawn_position), ..default() }, ActiveSkillAoEEffect { skill_id: skill_def.id, actual_damage_per_tick: sentry_damage_val, crit, actual_radius_sq: effective_sentry_radius.powi(2), tick_timer: Timer::from_seconds(*sentry_tick_interval_secs, TimerMode::Repeating), lifetime_timer: Timer::from_seconds(*sentry_duration_secs, TimerMode::Once), already_hit_this_tick: Vec::new(), }, Name::new("PsychicSentry"), )); effect_was_triggered = true; }
+                    SkillEffectType::FreezingNova { nova_duration_secs, slow_multiplier, slow_duration_secs, color, .. } => { let nova_spawn_position = cast_target.position.extend(player_transform.translation.z); commands.spawn(( SpriteBundle { texture: asset_server.load("sprites/frost_nova_placeholder.png"), sprite: Sprite { custom_size: Some(Vec2::splat(0.1)), color: *color, ..default() }, transform: Transform::from_translation(nova_spawn_position.truncate().extend(0.25)), ..default() }, FreezingNovaEffect { skill_id: skill_def.id, damage: nova_damage_val, crit, radius_sq: effective_nova_radius.powi(2), lifetime_timer: Timer::from_seconds(*nova_duration_secs, TimerMode::Once), slow_multiplier: *slow_multiplier, slow_duration_secs: *slow_duration_secs, already_hit_entities: Vec::new(), }, Name::new("GlacialNovaEffect"), )); effect_was_triggered = true; sound_event_writer.send(PlaySoundAtEvent(SoundEffect::RitualCast, nova_spawn_position)); }
                     SkillEffectType::TemporaryShield { duration_secs, .. } => {
                         commands.entity(player_entity).insert(ActiveShield {
                             amount: shield_amount,
@@ -301,6 +523,40 @@ fn survivor_skill_input_system(
                         });
                         effect_was_triggered = true;
                     }
+                    SkillEffectType::Dash { distance, duration_secs, invulnerability_secs } => {
+                        if current_aim_direction != Vec2::ZERO {
+                            let dash_distance = distance * skill_instance_snapshot.range_multiplier;
+                            commands.entity(player_entity).insert(SurvivorDash { velocity: current_aim_direction * dash_distance / duration_secs.max(0.01), timer: Timer::from_seconds(*duration_secs, TimerMode::Once) });
+                            player.grant_invulnerability(invulnerability_secs + skill_instance_snapshot.duration_bonus_secs);
+                            effect_was_triggered = true;
+                        }
+                    }
+                    SkillEffectType::ChanneledBeam { damage_per_tick, length, width, tick_interval_secs, duration_secs, color } => {
+                        let beam_length = length * skill_instance_snapshot.range_multiplier;
+                        commands.spawn((
+                            SpriteBundle { texture: asset_server.load("sprites/abyssal_beam_placeholder.png"), sprite: Sprite { custom_size: Some(Vec2::new(beam_length, *width)), color: *color, anchor: Anchor::CenterLeft, ..default() }, transform: Transform::from_translation(player_transform.translation.truncate().extend(0.6)).with_rotation(Quat::from_rotation_z(current_aim_direction.to_angle())), ..default() },
+                            ChanneledBeamEffect { skill_id: skill_def.id, slot: idx, damage_per_tick: damage_per_tick + skill_instance_snapshot.flat_damage_bonus, crit, length: beam_length, width: *width, tick_timer: Timer::from_seconds(*tick_interval_secs, TimerMode::Repeating), lifetime_timer: Timer::from_seconds(duration_secs + skill_instance_snapshot.duration_bonus_secs, TimerMode::Once) },
+                            Name::new(format!("SkillBeam_{}", skill_def.name)),
+                        ));
+                        effect_was_triggered = true;
+                    }
+                    SkillEffectType::Vortex { damage_per_tick, radius, pull_speed, tick_interval_secs, duration_secs, color } => {
+                        let vortex_radius = radius * skill_instance_snapshot.range_multiplier;
+                        commands.spawn((
+                            SpriteBundle { texture: asset_server.load("sprites/vortex_placeholder.png"), sprite: Sprite { custom_size: Some(Vec2::splat(vortex_radius * 2.0)), color: *color, ..default() }, transform: Transform::from_translation(cast_target.position.extend(0.2)), ..default() },
+                            VortexEffect { skill_id: skill_def.id, damage_per_tick: damage_per_tick + skill_instance_snapshot.flat_damage_bonus, crit, radius: vortex_radius, pull_speed: *pull_speed, tick_timer: Timer::from_seconds(*tick_interval_secs, TimerMode::Repeating), lifetime_timer: Timer::from_seconds(duration_secs + skill_instance_snapshot.duration_bonus_secs, TimerMode::Once) },
+                            Name::new(format!("SkillVortex_{}", skill_def.name)),
+                        ));
+                        effect_was_triggered = true;
+                    }
+                    SkillEffectType::SummonCompanion { damage, attack_range, attack_interval_secs, projectile_speed, move_speed, duration_secs, color } => {
+                        commands.spawn((
+                            SpriteBundle { texture: asset_server.load("sprites/bound_familiar_placeholder.png"), sprite: Sprite { custom_size: Some(COMPANION_SIZE), color: *color, ..default() }, transform: Transform::from_translation((cast_target.position + COMPANION_FOLLOW_OFFSET).extend(0.9)), ..default() },
+                            CompanionSummon { skill_id: skill_def.id, damage: damage + skill_instance_snapshot.flat_damage_bonus, crit, attack_range: attack_range * skill_instance_snapshot.range_multiplier, attack_timer: Timer::from_seconds(*attack_interval_secs, TimerMode::Repeating), projectile_speed: *projectile_speed, move_speed: *move_speed, lifetime_timer: Timer::from_seconds(duration_secs + skill_instance_snapshot.duration_bonus_secs, TimerMode::Once), color: *color },
+                            Name::new(format!("Companion_{}", skill_def.name)),
+                        ));
+                        effect_was_triggered = true;
+                    }
                 }
                 if effect_was_triggered {
                     if let Some(skill_instance_mut) = player.equipped_skills.get_mut(idx) {
@@ -331,11 +587,10 @@ fn skill_projectile_lifetime_system(mut commands: Commands, time: Res<Time>, mut
 
 fn skill_projectile_collision_system(
     mut commands: Commands,
-    mut skill_projectile_query: Query<(Entity, &GlobalTransform, &Damage, &mut SkillProjectile, &Sprite)>,
-    mut horror_query: Query<(Entity, &GlobalTransform, &mut Health, &Horror)>,
+    mut skill_projectile_query: Query<(Entity, &GlobalTransform, &Damage, &mut SkillProjectile, &Sprite, &Velocity)>,
+    mut horror_query: Query<HorrorHitTarget>,
     asset_server: Res<AssetServer>,
-    time: Res<Time>,
-    mut sound_event_writer: EventWriter<PlaySoundEvent>,
+    mut hit_writers: HorrorHitWriters,
     skill_library: Res<SkillLibrary>,
     player_query: Query<&Survivor>, 
     // Query for ExplosionEffect definition if we reuse it, or item library if it's an item effect
@@ -344,19 +599,37 @@ fn skill_projectile_collision_system(
     // If it's in items.rs, it's `crate::items::ExplosionEffect`
 ) {
     let Ok(_player) = player_query.get_single() else { return }; 
-    for (proj_entity, proj_g_transform, proj_damage, mut skill_projectile_data, proj_sprite) in skill_projectile_query.iter_mut() {
+    for (proj_entity, proj_g_transform, proj_damage, mut skill_projectile_data, proj_sprite, proj_velocity) in skill_projectile_query.iter_mut() {
         if skill_projectile_data.already_hit_by_this_projectile.len() > (skill_projectile_data.piercing_left + skill_projectile_data.bounces_left + 5) as usize { commands.entity(proj_entity).despawn_recursive(); continue; }
         let proj_pos = proj_g_transform.translation().truncate();
         let proj_radius = proj_sprite.custom_size.map_or(5.0, |s| (s.x.max(s.y)) / 2.0);
-        for (horror_entity, horror_gtransform, mut horror_health, horror_data) in horror_query.iter_mut() {
+        for HorrorHitTargetItem { entity: horror_entity, transform: horror_gtransform, health: mut horror_health, ward: mut ward_opt, horror: horror_data, warden: warden_opt, affixes: affixes_opt } in horror_query.iter_mut() {
             if skill_projectile_data.already_hit_by_this_projectile.contains(&horror_entity) { continue; }
             let horror_pos = horror_gtransform.translation().truncate();
             let horror_radius = horror_data.size.x / 2.0;
             if proj_pos.distance(horror_pos) < proj_radius + horror_radius {
-                sound_event_writer.send(PlaySoundEvent(SoundEffect::HorrorHit));
-                horror_health.0 -= proj_damage.0; // Main projectile damage
-                spawn_damage_text(&mut commands, &asset_server, horror_gtransform.translation(), proj_damage.0, &time);
+                if warden_opt.is_some_and(|warden| warden.blocks_projectile(horror_pos, proj_pos)) {
+                    hit_writers.sound.send(PlaySoundAtEvent(SoundEffect::ProjectileBlocked, proj_pos.extend(0.0)));
+                    commands.entity(proj_entity).despawn_recursive();
+                    break;
+                }
+                if try_mirror_reflect(&mut commands, &asset_server, affixes_opt, horror_data, horror_gtransform.translation(), proj_velocity.0) {
+                    hit_writers.sound.send(PlaySoundAtEvent(SoundEffect::ProjectileBlocked, proj_pos.extend(0.0)));
+                    commands.entity(proj_entity).despawn_recursive();
+                    break;
+                }
+                hit_writers.sound.send(PlaySoundAtEvent(SoundEffect::HorrorHit, horror_gtransform.translation()));
+                let (hit_damage, is_crit) = skill_projectile_data.crit.roll(proj_damage.0);
+                let dealt = apply_horror_damage(&mut horror_health, ward_opt.as_deref_mut(), hit_damage); // Main projectile damage
+                if dealt > 0 {
+                    hit_writers.damage.send(DamageDealtEvent { source: DamageSource::Skill(skill_projectile_data.skill_id), amount: dealt, target: horror_entity, position: horror_gtransform.translation(), is_crit });
+                }
                 skill_projectile_data.already_hit_by_this_projectile.push(horror_entity);
+                commands.entity(horror_entity).try_insert(HitFlash::default());
+                if skill_projectile_data.knockback_force > 0.0 {
+                    commands.entity(horror_entity).try_insert(Knockback::from_hit(proj_velocity.0.normalize_or_zero(), skill_projectile_data.knockback_force, horror_data.mass()));
+                    if skill_projectile_data.knockback_force >= HEAVY_HIT_KNOCKBACK_THRESHOLD { hit_writers.hitstop.send(HitstopEvent { duration_secs: HEAVY_HIT_HITSTOP_SECS }); }
+                }
 
                 // Handle on-impact AoE
                 if skill_projectile_data.impact_aoe_radius > 0.0 && skill_projectile_data.impact_aoe_damage_fraction > 0.0 {
@@ -374,6 +647,7 @@ fn skill_projectile_collision_system(
                                 radius_sq: skill_projectile_data.impact_aoe_radius.powi(2),
                                 timer: Timer::from_seconds(0.1, TimerMode::Once), // Very short timer, nearly instant
                                 already_hit_entities: vec![horror_entity], // Don't double-hit the main target with this AoE
+                                source: DamageSource::Skill(skill_projectile_data.skill_id),
                             },
                             Name::new("SkillImpactAoE"),
                         ));
@@ -388,14 +662,14 @@ fn skill_projectile_collision_system(
                     skill_projectile_data.bounces_left -= 1;
                     let mut closest_new_target: Option<(Entity, f32)> = None;
                     let chain_search_radius_sq = 250.0 * 250.0; 
-                    for (potential_target_entity, potential_target_gtransform, _health, _horror_data) in horror_query.iter_mut() {
-                        if potential_target_entity == horror_entity || skill_projectile_data.already_hit_by_this_projectile.contains(&potential_target_entity) { continue; }
-                        let distance_sq = potential_target_gtransform.translation().truncate().distance_squared(horror_pos);
-                        if distance_sq < chain_search_radius_sq { if closest_new_target.is_none() || distance_sq < closest_new_target.unwrap().1 { closest_new_target = Some((potential_target_entity, distance_sq)); } }
+                    for potential_target in horror_query.iter_mut() {
+                        if potential_target.entity == horror_entity || skill_projectile_data.already_hit_by_this_projectile.contains(&potential_target.entity) { continue; }
+                        let distance_sq = potential_target.transform.translation().truncate().distance_squared(horror_pos);
+                        if distance_sq < chain_search_radius_sq { if closest_new_target.is_none() || distance_sq < closest_new_target.unwrap().1 { closest_new_target = Some((potential_target.entity, distance_sq)); } }
                     }
                     if let Some((target_entity, _)) = closest_new_target {
-                        if let Ok((_t_ent, target_transform, _h, _horror_data_ref)) = horror_query.get(target_entity) {
-                            let direction_to_new_target = (target_transform.translation().truncate() - horror_pos).normalize_or_zero();
+                        if let Ok(target) = horror_query.get(target_entity) {
+                            let direction_to_new_target = (target.transform.translation().truncate() - horror_pos).normalize_or_zero();
                                 if let Some(skill_def) = skill_library.get_skill_definition(skill_projectile_data.skill_id) {
                                     if let SkillEffectType::Projectile { speed, size, color, lifetime_secs, piercing, .. } = skill_def.effect {
                                         let chained_damage = proj_damage.0; 
@@ -408,6 +682,8 @@ fn skill_projectile_collision_system(
                                                 already_hit_by_this_projectile: vec![target_entity],
                                                 impact_aoe_radius: skill_projectile_data.impact_aoe_radius, // Carry over AoE props
                                                 impact_aoe_damage_fraction: skill_projectile_data.impact_aoe_damage_fraction,
+                                                knockback_force: skill_projectile_data.knockback_force,
+                                                crit: skill_projectile_data.crit,
                                             },
                                             Velocity(direction_to_new_target * speed), Damage(chained_damage), Lifetime { timer: Timer::from_seconds(lifetime_secs, TimerMode::Once) }, Name::new(format!("ChainedProjectile_{}", skill_def.name)),
                                         ));
@@ -422,5 +698,320 @@ fn skill_projectile_collision_system(
     }
 }
 
-fn active_skill_aoe_system(mut commands: Commands, time: Res<Time>, mut aoe_query: Query<(Entity, &mut ActiveSkillAoEEffect, &GlobalTransform, Option<&mut Sprite>)>, mut horror_query: Query<(Entity, &GlobalTransform, &mut Health), With<Horror>>, asset_server: Res<AssetServer>, mut sound_event_writer: EventWriter<PlaySoundEvent>,) { for (aoe_entity, mut aoe_effect, aoe_g_transform, opt_sprite) in aoe_query.iter_mut() { aoe_effect.lifetime_timer.tick(time.delta()); if let Some(mut sprite) = opt_sprite { let lifetime_remaining_fraction = 1.0 - aoe_effect.lifetime_timer.fraction(); let initial_alpha = sprite.color.a(); sprite.color.set_a((initial_alpha * lifetime_remaining_fraction).clamp(0.0, initial_alpha)); } if aoe_effect.lifetime_timer.finished() { commands.entity(aoe_entity).despawn_recursive(); continue; } aoe_effect.tick_timer.tick(time.delta()); if aoe_effect.tick_timer.just_finished() { aoe_effect.already_hit_this_tick.clear(); let aoe_pos = aoe_g_transform.translation().truncate(); for (horror_entity, horror_gtransform, mut horror_health) in horror_query.iter_mut() { if aoe_effect.already_hit_this_tick.contains(&horror_entity) { continue; } let horror_pos = horror_gtransform.translation().truncate(); if horror_pos.distance_squared(aoe_pos) < aoe_effect.actual_radius_sq { sound_event_writer.send(PlaySoundEvent(SoundEffect::HorrorHit)); horror_health.0 -= aoe_effect.actual_damage_per_tick; spawn_damage_text(&mut commands, &asset_server, horror_gtransform.translation(), aoe_effect.actual_damage_per_tick, &time); aoe_effect.already_hit_this_tick.push(horror_entity); } } } } }
-fn freezing_nova_effect_damage_system( mut commands: Commands, time: Res<Time>, mut nova_query: Query<(Entity, &mut FreezingNovaEffect, &GlobalTransform, &mut Sprite, &mut Transform)>, mut horror_query: Query<(Entity, &GlobalTransform, &mut Health, &mut Velocity), (With<Horror>, Without<crate::horror::Frozen>)>, asset_server: Res<AssetServer>, mut sound_event_writer: EventWriter<PlaySoundEvent>,) { for (nova_entity, mut nova, nova_g_transform, mut sprite, mut vis_transform) in nova_query.iter_mut() { nova.lifetime_timer.tick(time.delta()); let progress = nova.lifetime_timer.fraction(); let current_visual_radius = nova.radius_sq.sqrt() * 2.0 * progress; vis_transform.scale = Vec3::splat(current_visual_radius); sprite.color.set_a((1.0 - progress * progress).max(0.0)); if nova.lifetime_timer.fraction() < 0.5 && !nova.already_hit_entities.contains(&nova_entity) { let nova_pos = nova_g_transform.translation().truncate(); for (horror_entity, horror_gtransform, mut horror_health, _horror_velocity) in horror_query.iter_mut() { if nova.already_hit_entities.contains(&horror_entity) { continue; } let horror_pos = horror_gtransform.translation().truncate(); if horror_pos.distance_squared(nova_pos) < nova.radius_sq { horror_health.0 -= nova.damage; spawn_damage_text(&mut commands, &asset_server, horror_gtransform.translation(), nova.damage, &time); sound_event_writer.send(PlaySoundEvent(SoundEffect::RitualCast)); commands.entity(horror_entity).insert(crate::horror::Frozen { timer: Timer::from_seconds(nova.slow_duration_secs, TimerMode::Once), speed_multiplier: nova.slow_multiplier, }); nova.already_hit_entities.push(horror_entity); } } if !nova.already_hit_entities.contains(&nova_entity) { nova.already_hit_entities.push(nova_entity); } } if nova.lifetime_timer.finished() { commands.entity(nova_entity).despawn_recursive(); } } }
\ No newline at end of file
+fn active_skill_aoe_system(mut commands: Commands, time: Res<Time>, mut aoe_query: Query<(Entity, &mut ActiveSkillAoEEffect, &GlobalTransform, Option<&mut Sprite>)>, mut horror_query: Query<(Entity, &GlobalTransform, &mut Health, Option<&mut AffixWard>), With<Horror>>, mut sound_event_writer: EventWriter<PlaySoundAtEvent>, mut damage_event_writer: EventWriter<DamageDealtEvent>,) { for (aoe_entity, mut aoe_effect, aoe_g_transform, opt_sprite) in aoe_query.iter_mut() { aoe_effect.lifetime_timer.tick(time.delta()); if let Some(mut sprite) = opt_sprite { let lifetime_remaining_fraction = 1.0 - aoe_effect.lifetime_timer.fraction(); let initial_alpha = sprite.color.a(); sprite.color.set_a((initial_alpha * lifetime_remaining_fraction).clamp(0.0, initial_alpha)); } if aoe_effect.lifetime_timer.finished() { commands.entity(aoe_entity).despawn_recursive(); continue; } aoe_effect.tick_timer.tick(time.delta()); if aoe_effect.tick_timer.just_finished() { aoe_effect.already_hit_this_tick.clear(); let aoe_pos = aoe_g_transform.translation().truncate(); for (horror_entity, horror_gtransform, mut horror_health, mut ward_opt) in horror_query.iter_mut() { if aoe_effect.already_hit_this_tick.contains(&horror_entity) { continue; } let horror_pos = horror_gtransform.translation().truncate(); if horror_pos.distance_squared(aoe_pos) < aoe_effect.actual_radius_sq { sound_event_writer.send(PlaySoundAtEvent(SoundEffect::HorrorHit, horror_gtransform.translation())); let (tick_damage, is_crit) = aoe_effect.crit.roll(aoe_effect.actual_damage_per_tick); let dealt = apply_horror_damage(&mut horror_health, ward_opt.as_deref_mut(), tick_damage); if dealt > 0 { damage_event_writer.send(DamageDealtEvent { source: DamageSource::Skill(aoe_effect.skill_id), amount: dealt, target: horror_entity, position: horror_gtransform.translation(), is_crit }); } commands.entity(horror_entity).try_insert(HitFlash::default()); aoe_effect.already_hit_this_tick.push(horror_entity); } } } } }
+fn freezing_nova_effect_damage_system( mut commands: Commands, time: Res<Time>, mut nova_query: Query<(Entity, &mut FreezingNovaEffect, &GlobalTransform, &mut Sprite, &mut Transform)>, mut horror_query: Query<(Entity, &GlobalTransform, &mut Health, Option<&mut AffixWard>), (With<Horror>, Without<crate::horror::Frozen>)>, mut sound_event_writer: EventWriter<PlaySoundAtEvent>, mut damage_event_writer: EventWriter<DamageDealtEvent>,) { for (nova_entity, mut nova, nova_g_transform, mut sprite, mut vis_transform) in nova_query.iter_mut() { nova.lifetime_timer.tick(time.delta()); let progress = nova.lifetime_timer.fraction(); let current_visual_radius = nova.radius_sq.sqrt() * 2.0 * progress; vis_transform.scale = Vec3::splat(current_visual_radius); sprite.color.set_a((1.0 - progress * progress).max(0.0)); if nova.lifetime_timer.fraction() < 0.5 && !nova.already_hit_entities.contains(&nova_entity) { let nova_pos = nova_g_transform.translation().truncate(); for (horror_entity, horror_gtransform, mut horror_health, mut ward_opt) in horror_query.iter_mut() { if nova.already_hit_entities.contains(&horror_entity) { continue; } let horror_pos = horror_gtransform.translation().truncate(); if horror_pos.distance_squared(nova_pos) < nova.radius_sq { let (nova_damage, is_crit) = nova.crit.roll(nova.damage); let dealt = apply_horror_damage(&mut horror_health, ward_opt.as_deref_mut(), nova_damage); if dealt > 0 { damage_event_writer.send(DamageDealtEvent { source: DamageSource::Skill(nova.skill_id), amount: dealt, target: horror_entity, position: horror_gtransform.translation(), is_crit }); } commands.entity(horror_entity).try_insert(HitFlash::default()); sound_event_writer.send(PlaySoundAtEvent(SoundEffect::RitualCast, horror_gtransform.translation())); commands.entity(horror_entity).insert(crate::horror::Frozen { timer: Timer::from_seconds(nova.slow_duration_secs, TimerMode::Once), speed_multiplier: nova.slow_multiplier, }); nova.already_hit_entities.push(horror_entity); } } if !nova.already_hit_entities.contains(&nova_entity) { nova.already_hit_entities.push(nova_entity); } } if nova.lifetime_timer.finished() { commands.entity(nova_entity).despawn_recursive(); } } }
+fn skill_aim_indicator_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    aim_state: Res<SkillAimState>,
+    skill_library: Res<SkillLibrary>,
+    player_query: Query<(&Survivor, &Transform)>,
+    horror_query: Query<&Transform, (With<Horror>, Without<Survivor>)>,
+    mut indicators: AimIndicators,
+) {
+    let aimed_skill = aim_state.held_slot.zip(player_query.get_single().ok()).and_then(|(slot, (player, player_transform))| {
+        let instance = player.equipped_skills.get(slot)?;
+        skill_library.get_skill_definition(instance.definition_id).map(|def| (def, instance, player, player_transform))
+    });
+    let Some((skill_def, instance, player, player_transform)) = aimed_skill else {
+        for (entity, ..) in indicators.range.iter() { commands.entity(entity).despawn_recursive(); }
+        for (entity, ..) in indicators.area.iter() { commands.entity(entity).despawn_recursive(); }
+        return;
+    };
+
+    let survivor_pos = player_transform.translation.truncate();
+    let cast_target = resolve_skill_target(skill_def.targeting, survivor_pos, player.aim_direction, player.aim_world_position, horror_query.iter().map(|t| t.translation.truncate()));
+    let range = skill_def.targeting_range(instance);
+    let area_color = if instance.is_ready() { AIM_AREA_INDICATOR_COLOR } else { AIM_AREA_INDICATOR_COOLDOWN_COLOR };
+
+    // Lines and cones are drawn from the survivor outwards; everything else is a circle on the landing point.
+    let (area_texture, area_anchor, area_size, area_position, area_rotation) = match skill_def.targeting {
+        SkillTargeting::Line => {
+            ("sprites/aim_line_indicator_placeholder.png", Anchor::CenterLeft, Vec2::new(range, skill_def.line_width()), survivor_pos, cast_target.direction.to_angle())
+        }
+        SkillTargeting::Cone { angle_degrees, .. } => {
+            let spread = 2.0 * range * (angle_degrees.to_radians() / 2.0).tan();
+            ("sprites/aim_cone_indicator_placeholder.png", Anchor::CenterLeft, Vec2::new(range, spread), survivor_pos, cast_target.direction.to_angle())
+        }
+        SkillTargeting::GroundTarget { .. } | SkillTargeting::NearestEnemy { .. } | SkillTargeting::SelfCentered => {
+            ("sprites/aim_circle_indicator_placeholder.png", Anchor::Center, Vec2::splat(skill_def.effect_radius(instance) * 2.0), cast_target.position, 0.0)
+        }
+    };
+
+    if let Ok((_, mut transform, mut sprite)) = indicators.area.get_single_mut() {
+        transform.translation = area_position.extend(AIM_INDICATOR_Z);
+        transform.rotation = Quat::from_rotation_z(area_rotation);
+        sprite.custom_size = Some(area_size);
+        sprite.color = area_color;
+    } else {
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load(area_texture),
+                sprite: Sprite { custom_size: Some(area_size), color: area_color, anchor: area_anchor, ..default() },
+                transform: Transform::from_translation(area_position.extend(AIM_INDICATOR_Z)).with_rotation(Quat::from_rotation_z(area_rotation)),
+                ..default()
+            },
+            SkillAreaIndicator,
+            Name::new("SkillAreaIndicator"),
+        ));
+    }
+
+    if range <= 0.0 {
+        for (entity, ..) in indicators.range.iter() { commands.entity(entity).despawn_recursive(); }
+    } else if let Ok((_, mut transform, mut sprite)) = indicators.range.get_single_mut() {
+        transform.translation = survivor_pos.extend(AIM_INDICATOR_Z - 0.01);
+        sprite.custom_size = Some(Vec2::splat(range * 2.0));
+    } else {
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load("sprites/aim_range_ring_placeholder.png"),
+                sprite: Sprite { custom_size: Some(Vec2::splat(range * 2.0)), color: AIM_RANGE_INDICATOR_COLOR, ..default() },
+                transform: Transform::from_translation(survivor_pos.extend(AIM_INDICATOR_Z - 0.01)),
+                ..default()
+            },
+            SkillRangeIndicator,
+            Name::new("SkillRangeIndicator"),
+        ));
+    }
+}
+
+fn clear_skill_aim_system(
+    mut commands: Commands,
+    mut aim_state: ResMut<SkillAimState>,
+    indicator_query: Query<Entity, AimIndicatorFilter>,
+) {
+    aim_state.held_slot = None;
+    for entity in indicator_query.iter() { commands.entity(entity).despawn_recursive(); }
+}
+
+fn survivor_dash_system(mut commands: Commands, time: Res<Time>, mut query: Query<(Entity, &mut SurvivorDash)>,) {
+    for (entity, mut dash) in query.iter_mut() {
+        dash.timer.tick(time.delta());
+        if dash.timer.finished() { commands.entity(entity).remove::<SurvivorDash>(); }
+    }
+}
+
+/// A channel is broken by letting go of its key or by dashing (dodges included).
+fn break_channeled_beams_system(
+    mut commands: Commands,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    player_query: Query<Has<SurvivorDash>, With<Survivor>>,
+    beam_query: Query<(Entity, &ChanneledBeamEffect)>,
+) {
+    let is_dashing = player_query.get_single().unwrap_or(false);
+    for (beam_entity, beam) in beam_query.iter() {
+        if is_dashing || !skill_slot_held(beam.slot, &mouse_button_input, &keyboard_input) { commands.entity(beam_entity).despawn_recursive(); }
+    }
+}
+
+fn channeled_beam_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    player_query: Query<(&Survivor, &Transform), Without<ChanneledBeamEffect>>,
+    mut beam_query: Query<(Entity, &mut ChanneledBeamEffect, &mut Transform), Without<Survivor>>,
+    mut horror_query: Query<(Entity, &GlobalTransform, &mut Health, Option<&mut AffixWard>, &Horror)>,
+    mut hit_writers: HorrorHitWriters,
+) {
+    let Ok((player, player_transform)) = player_query.get_single() else { return; };
+    let beam_origin = player_transform.translation.truncate();
+    for (beam_entity, mut beam, mut beam_transform) in beam_query.iter_mut() {
+        beam.lifetime_timer.tick(time.delta());
+        if beam.lifetime_timer.finished() { commands.entity(beam_entity).despawn_recursive(); continue; }
+        beam_transform.translation = beam_origin.extend(beam_transform.translation.z);
+        beam_transform.rotation = Quat::from_rotation_z(player.aim_direction.to_angle());
+
+        beam.tick_timer.tick(time.delta());
+        if !beam.tick_timer.just_finished() { continue; }
+        let beam_end = beam_origin + player.aim_direction * beam.length;
+        let mut last_hit_position = None;
+        for (horror_entity, horror_gtransform, mut horror_health, mut ward_opt, horror_data) in horror_query.iter_mut() {
+            let horror_pos = horror_gtransform.translation().truncate();
+            let closest_on_beam = closest_point_on_segment(horror_pos, beam_origin, beam_end);
+            if horror_pos.distance(closest_on_beam) < beam.width / 2.0 + horror_data.size.x / 2.0 {
+                let (tick_damage, is_crit) = beam.crit.roll(beam.damage_per_tick);
+                let dealt = apply_horror_damage(&mut horror_health, ward_opt.as_deref_mut(), tick_damage);
+                if dealt > 0 {
+                    hit_writers.damage.send(DamageDealtEvent { source: DamageSource::Skill(beam.skill_id), amount: dealt, target: horror_entity, position: horror_gtransform.translation(), is_crit });
+                }
+                commands.entity(horror_entity).try_insert(HitFlash::default());
+                last_hit_position = Some(horror_gtransform.translation());
+            }
+        }
+        if let Some(position) = last_hit_position { hit_writers.sound.send(PlaySoundAtEvent(SoundEffect::HorrorHit, position)); }
+    }
+}
+
+fn closest_point_on_segment(point: Vec2, start: Vec2, end: Vec2) -> Vec2 {
+    let segment = end - start;
+    let length_sq = segment.length_squared();
+    if length_sq <= f32::EPSILON { return start; }
+    start + segment * ((point - start).dot(segment) / length_sq).clamp(0.0, 1.0)
+}
+
+fn vortex_effect_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut vortex_query: Query<(Entity, &mut VortexEffect, &mut Transform, &mut Sprite), Without<Horror>>,
+    mut horror_query: Query<(Entity, &mut Transform, &mut Health, Option<&mut AffixWard>, &Horror)>,
+    mut damage_event_writer: EventWriter<DamageDealtEvent>,
+) {
+    for (vortex_entity, mut vortex, mut vortex_transform, mut sprite) in vortex_query.iter_mut() {
+        vortex.lifetime_timer.tick(time.delta());
+        if vortex.lifetime_timer.finished() { commands.entity(vortex_entity).despawn_recursive(); continue; }
+        vortex_transform.rotate_z(-4.0 * time.delta_seconds());
+        sprite.color.set_a((1.0 - vortex.lifetime_timer.fraction()).clamp(0.2, 0.6));
+
+        let vortex_pos = vortex_transform.translation.truncate();
+        vortex.tick_timer.tick(time.delta());
+        let deals_damage = vortex.tick_timer.just_finished();
+        for (horror_entity, mut horror_transform, mut horror_health, mut ward_opt, horror_data) in horror_query.iter_mut() {
+            let horror_pos = horror_transform.translation.truncate();
+            let offset = vortex_pos - horror_pos;
+            let distance = offset.length();
+            if distance > vortex.radius { continue; }
+            // Heavier horrors resist the pull; never drag anything past the centre.
+            let pull = (vortex.pull_speed / horror_data.mass() * time.delta_seconds()).min(distance);
+            let pulled = horror_pos + offset.normalize_or_zero() * pull;
+            horror_transform.translation.x = pulled.x;
+            horror_transform.translation.y = pulled.y;
+            if deals_damage {
+                let (tick_damage, is_crit) = vortex.crit.roll(vortex.damage_per_tick);
+                let dealt = apply_horror_damage(&mut horror_health, ward_opt.as_deref_mut(), tick_damage);
+                if dealt > 0 {
+                    damage_event_writer.send(DamageDealtEvent { source: DamageSource::Skill(vortex.skill_id), amount: dealt, target: horror_entity, position: horror_transform.translation, is_crit });
+                }
+                commands.entity(horror_entity).try_insert(HitFlash::default());
+            }
+        }
+    }
+}
+
+fn companion_summon_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    player_query: Query<&Transform, (With<Survivor>, Without<CompanionSummon>)>,
+    mut companion_query: Query<(Entity, &mut CompanionSummon, &mut Transform), Without<Survivor>>,
+    horror_query: Query<&GlobalTransform, With<Horror>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else { return; };
+    let follow_target = player_transform.translation.truncate() + COMPANION_FOLLOW_OFFSET;
+    for (companion_entity, mut companion, mut companion_transform) in companion_query.iter_mut() {
+        companion.lifetime_timer.tick(time.delta());
+        if companion.lifetime_timer.finished() { commands.entity(companion_entity).despawn_recursive(); continue; }
+
+        let companion_pos = companion_transform.translation.truncate();
+        let to_target = follow_target - companion_pos;
+        let step = (companion.move_speed * time.delta_seconds()).min(to_target.length());
+        let new_pos = companion_pos + to_target.normalize_or_zero() * step;
+        companion_transform.translation.x = new_pos.x;
+        companion_transform.translation.y = new_pos.y;
+
+        companion.attack_timer.tick(time.delta());
+        if !companion.attack_timer.just_finished() { continue; }
+        let range_sq = companion.attack_range * companion.attack_range;
+        let nearest_horror = horror_query.iter()
+            .map(|horror_gtransform| horror_gtransform.translation().truncate())
+            .filter(|horror_pos| horror_pos.distance_squared(new_pos) <= range_sq)
+            .min_by(|a, b| a.distance_squared(new_pos).total_cmp(&b.distance_squared(new_pos)));
+        let Some(target_pos) = nearest_horror else { continue; };
+        let direction = (target_pos - new_pos).normalize_or_zero();
+        if direction == Vec2::ZERO { continue; }
+        commands.spawn((
+            SpriteBundle { texture: asset_server.load("sprites/familiar_bolt_placeholder.png"), sprite: Sprite { custom_size: Some(COMPANION_BOLT_SIZE), color: companion.color, ..default() }, transform: Transform::from_translation(new_pos.extend(0.9)).with_rotation(Quat::from_rotation_z(direction.to_angle())), ..default() },
+            SkillProjectile { skill_id: companion.skill_id, piercing_left: 0, bounces_left: 0, already_hit_by_this_projectile: Vec::new(), impact_aoe_radius: 0.0, impact_aoe_damage_fraction: 0.0, knockback_force: COMPANION_BOLT_KNOCKBACK, crit: companion.crit },
+            Velocity(direction * companion.projectile_speed),
+            Damage(companion.damage),
+            Lifetime { timer: Timer::from_seconds(companion.attack_range / companion.projectile_speed.max(1.0) * 1.2, TimerMode::Once) },
+            Name::new("FamiliarBolt"),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use crate::items::AutomaticWeaponLibrary;
+
+    const SURVIVOR_POS: Vec2 = Vec2::new(100.0, 50.0);
+
+    fn resolve(targeting: SkillTargeting, cursor: Vec2, horrors: &[Vec2]) -> SkillCastTarget {
+        resolve_skill_target(targeting, SURVIVOR_POS, Vec2::X, cursor, horrors.iter().copied())
+    }
+
+    #[test]
+    fn ground_target_lands_on_the_cursor_within_range() {
+        let target = resolve(SkillTargeting::GroundTarget { max_range: 300.0 }, SURVIVOR_POS + Vec2::new(120.0, -90.0), &[]);
+        assert_eq!(target.position, SURVIVOR_POS + Vec2::new(120.0, -90.0));
+    }
+
+    #[test]
+    fn ground_target_is_clamped_to_max_range_towards_the_cursor() {
+        let target = resolve(SkillTargeting::GroundTarget { max_range: 300.0 }, SURVIVOR_POS + Vec2::new(0.0, 1000.0), &[]);
+        assert!(target.position.abs_diff_eq(SURVIVOR_POS + Vec2::new(0.0, 300.0), 1e-3));
+    }
+
+    #[test]
+    fn nearest_enemy_picks_the_closest_horror_in_range() {
+        let horrors = [SURVIVOR_POS + Vec2::new(0.0, 250.0), SURVIVOR_POS + Vec2::new(-120.0, 0.0), SURVIVOR_POS + Vec2::new(1000.0, 0.0)];
+        let target = resolve(SkillTargeting::NearestEnemy { max_range: 400.0 }, Vec2::ZERO, &horrors);
+        assert_eq!(target.position, horrors[1]);
+        assert!(target.direction.abs_diff_eq(Vec2::NEG_X, 1e-5));
+    }
+
+    #[test]
+    fn nearest_enemy_ignores_horrors_out_of_range() {
+        let target = resolve(SkillTargeting::NearestEnemy { max_range: 100.0 }, Vec2::ZERO, &[SURVIVOR_POS + Vec2::new(0.0, 150.0)]);
+        assert_eq!(target.position, SURVIVOR_POS);
+        assert_eq!(target.direction, Vec2::X);
+    }
+
+    #[test]
+    fn line_cone_and_self_stay_on_the_survivor_regardless_of_cursor() {
+        let cursor = SURVIVOR_POS + Vec2::new(900.0, 900.0);
+        for targeting in [SkillTargeting::Line, SkillTargeting::Cone { angle_degrees: 60.0, fragments: 5 }, SkillTargeting::SelfCentered] {
+            let target = resolve(targeting, cursor, &[SURVIVOR_POS + Vec2::Y * 10.0]);
+            assert_eq!(target.position, SURVIVOR_POS);
+            assert_eq!(target.direction, Vec2::X);
+        }
+    }
+
+    fn world_with_channeled_beam(slot: usize) -> (World, Entity, Entity) {
+        let mut world = World::new();
+        world.init_resource::<ButtonInput<KeyCode>>();
+        world.init_resource::<ButtonInput<MouseButton>>();
+        world.init_resource::<AutomaticWeaponLibrary>();
+        let survivor = world.run_system_once(|mut commands: Commands, library: Res<AutomaticWeaponLibrary>| commands.spawn(Survivor::new_with_skills_and_items(Vec::new(), Vec::new(), None, &library)).id());
+        let beam = world.spawn(ChanneledBeamEffect { skill_id: SkillId(9), slot, damage_per_tick: 5, crit: CritStats::default(), length: 300.0, width: 20.0, tick_timer: Timer::from_seconds(0.1, TimerMode::Repeating), lifetime_timer: Timer::from_seconds(3.0, TimerMode::Once) }).id();
+        (world, survivor, beam)
+    }
+
+    #[test]
+    fn channel_holds_while_its_key_is_held() {
+        let (mut world, _, beam) = world_with_channeled_beam(1);
+        world.resource_mut::<ButtonInput<KeyCode>>().press(SKILL_SLOT_KEYS[1]);
+        world.run_system_once(break_channeled_beams_system);
+        assert!(world.get_entity(beam).is_some());
+    }
+
+    #[test]
+    fn releasing_the_key_breaks_the_channel() {
+        let (mut world, _, beam) = world_with_channeled_beam(1);
+        world.resource_mut::<ButtonInput<KeyCode>>().press(SKILL_SLOT_KEYS[1]);
+        world.run_system_once(break_channeled_beams_system);
+        world.resource_mut::<ButtonInput<KeyCode>>().release(SKILL_SLOT_KEYS[1]);
+        world.run_system_once(break_channeled_beams_system);
+        assert!(world.get_entity(beam).is_none());
+    }
+
+    #[test]
+    fn dashing_breaks_the_channel_even_with_the_key_held() {
+        let (mut world, survivor, beam) = world_with_channeled_beam(0);
+        world.resource_mut::<ButtonInput<MouseButton>>().press(MouseButton::Right);
+        world.entity_mut(survivor).insert(SurvivorDash { velocity: Vec2::X * 900.0, timer: Timer::from_seconds(0.2, TimerMode::Once) });
+        world.run_system_once(break_channeled_beams_system);
+        assert!(world.get_entity(beam).is_none());
+    }
+}
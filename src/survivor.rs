@@ -1,26 +1,34 @@
 // src/survivor.rs
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::{prelude::*, window::PrimaryWindow, ecs::system::SystemParam};
 use std::time::Duration;
 use rand::Rng;
 use crate::{
     components::{Velocity, Health as ComponentHealth},
-    game::{AppState, ItemCollectedEvent},
-    automatic_projectiles::{spawn_automatic_projectile, LightningParticleEffects},
-    horror::Horror,
+    game::{AppState, OnSessionEnd, ItemCollectedEvent},
+    automatic_projectiles::{spawn_automatic_projectile, ProjectileSpawnParams, LightningParticleEffects},
+    horror::{Horror, FrenziedBehemothBehavior, FrenziedBehemothState},
+    visual_effects::{HitstopEvent, BEHEMOTH_SLAM_HITSTOP_SECS},
+    camera_systems::{CameraShakeEvent, SURVIVOR_HIT_TRAUMA, BEHEMOTH_SLAM_TRAUMA},
     weapons::{CircleOfWarding, SwarmOfNightmares},
-    audio::{PlaySoundEvent, SoundEffect},
-    skills::{ActiveSkillInstance, SkillLibrary, SkillId, SurvivorBuffEffect, ActiveShield},
+    omens::RunModifiers,
+    audio::{PlaySoundEvent, PlaySoundAtEvent, SoundEffect},
+    skills::{ActiveSkillInstance, SkillLibrary, SkillId, SurvivorBuffEffect, SurvivorDash, ActiveShield},
     items::{ItemId, ItemDrop, ItemLibrary, ItemEffect, RetaliationNovaEffect, AutomaticWeaponId, AutomaticWeaponLibrary},
+    run_statistics::RunStatistics,
+    archetypes::{ArchetypeLibrary, SelectedArchetype},
+    xp_curve::XpCurve,
+    dodge::SurvivorDodge,
+    critical_hits::{CritStats, BASE_CRIT_CHANCE, BASE_CRIT_MULTIPLIER},
 };
 
 pub const SURVIVOR_SIZE: Vec2 = Vec2::new(50.0, 50.0);
-const XP_FOR_LEVEL: [u32; 10] = [100, 150, 250, 400, 600, 850, 1100, 1400, 1800, 2500];
 pub const BASE_PICKUP_RADIUS: f32 = 100.0;
 const PROJECTILE_SPREAD_ANGLE_DEGREES: f32 = 10.0;
 pub const INITIAL_SURVIVOR_MAX_HEALTH: i32 = 100;
 const BASE_SURVIVOR_SPEED: f32 = 250.0;
 const ITEM_COLLECTION_RADIUS: f32 = SURVIVOR_SIZE.x / 2.0 + crate::items::ITEM_DROP_SIZE.x / 2.0;
 pub const MAX_ACTIVE_SKILLS: usize = 5;
+pub const SURVIVOR_HIT_INVINCIBILITY_SECS: f32 = 1.0;
 
 #[derive(Component)] pub struct SanityStrain { pub base_fire_rate_secs: f32, pub fire_timer: Timer, }
 
@@ -28,7 +36,11 @@ pub struct SurvivorPlugin;
 #[derive(Component)]
 pub struct Survivor {
     pub speed: f32, pub experience: u32, pub current_level_xp: u32, pub level: u32,
-    pub aim_direction: Vec2, pub invincibility_timer: Timer,
+    /// Level-ups earned but not yet shown; each one opens its own upgrade screen.
+    pub pending_level_ups: u32,
+    /// Times the XP bar filled at the curve's max level, waiting to be paid out as `XpOverflowReward`.
+    pub pending_overflow_rewards: u32,
+    pub aim_direction: Vec2, pub aim_world_position: Vec2, pub invincibility_timer: Timer,
 
     pub auto_weapon_damage_bonus: i32,
     pub auto_weapon_projectile_speed_multiplier: f32,
@@ -36,6 +48,11 @@ pub struct Survivor {
     pub auto_weapon_additional_projectiles_bonus: u32,
     pub auto_weapon_chain_bonus: u32,
     pub auto_weapon_chain_range_multiplier: f32,
+    pub auto_weapon_knockback_bonus: f32,
+
+    /// Shared by automatic weapons and skills; see `CritStats::for_attack` for per-weapon and per-skill overrides.
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
 
     pub xp_gain_multiplier: f32, pub pickup_radius_multiplier: f32,
     pub max_health: i32, pub health_regen_rate: f32,
@@ -45,9 +62,28 @@ pub struct Survivor {
 }
 
 impl Survivor {
-    pub fn experience_to_next_level(&self) -> u32 { if self.level == 0 { return 0; } if (self.level as usize -1) < XP_FOR_LEVEL.len() { XP_FOR_LEVEL[self.level as usize - 1] } else { XP_FOR_LEVEL.last().unwrap_or(&2500) + (self.level - XP_FOR_LEVEL.len() as u32) * 500 } }
-    pub fn add_experience( &mut self, amount: u32, next_state_value: &mut NextState<AppState>, sound_event_writer: &mut EventWriter<PlaySoundEvent>,) { let actual_xp_gained = (amount as f32 * self.xp_gain_multiplier).round() as u32; self.current_level_xp += actual_xp_gained; self.experience += actual_xp_gained; while self.current_level_xp >= self.experience_to_next_level() && self.level > 0 { let needed = self.experience_to_next_level(); self.current_level_xp -= needed; self.level += 1; sound_event_writer.send(PlaySoundEvent(SoundEffect::Revelation)); next_state_value.set(AppState::LevelUp); if next_state_value.0 == Some(AppState::LevelUp) { break; } } }
+    pub fn experience_to_next_level(&self, xp_curve: &XpCurve) -> u32 { xp_curve.experience_for_level(self.level) }
+    /// Banks every level the XP pays for; `process_pending_level_ups_system` then opens one upgrade screen per level.
+    pub fn add_experience(&mut self, amount: u32, xp_curve: &XpCurve, sound_event_writer: &mut EventWriter<PlaySoundEvent>,) {
+        let actual_xp_gained = (amount as f32 * self.xp_gain_multiplier).round() as u32;
+        self.current_level_xp += actual_xp_gained;
+        self.experience += actual_xp_gained;
+        while self.level > 0 {
+            let cost = self.experience_to_next_level(xp_curve);
+            if cost == 0 || self.current_level_xp < cost { break; }
+            self.current_level_xp -= cost;
+            if xp_curve.is_max_level(self.level) {
+                self.pending_overflow_rewards += 1;
+                continue;
+            }
+            self.level += 1;
+            self.pending_level_ups += 1;
+            sound_event_writer.send(PlaySoundEvent(SoundEffect::Revelation));
+        }
+    }
     pub fn get_effective_pickup_radius(&self) -> f32 { BASE_PICKUP_RADIUS * self.pickup_radius_multiplier }
+    /// Makes the survivor untouchable for at least `secs`, without shortening any invulnerability already running.
+    pub fn grant_invulnerability(&mut self, secs: f32) { if secs > self.invincibility_timer.remaining_secs() { self.invincibility_timer = Timer::from_seconds(secs, TimerMode::Once); } }
 
     pub fn new_with_skills_and_items(
         initial_skills: Vec<ActiveSkillInstance>,
@@ -58,14 +94,19 @@ impl Survivor {
         Self {
             speed: BASE_SURVIVOR_SPEED,
             experience: 0, current_level_xp: 0, level: 1,
+            pending_level_ups: 0, pending_overflow_rewards: 0,
             aim_direction: Vec2::X,
-            invincibility_timer: Timer::from_seconds(1.0, TimerMode::Once),
+            aim_world_position: Vec2::ZERO,
+            invincibility_timer: Timer::from_seconds(SURVIVOR_HIT_INVINCIBILITY_SECS, TimerMode::Once),
             auto_weapon_damage_bonus: 0,
             auto_weapon_projectile_speed_multiplier: 1.0,
             auto_weapon_piercing_bonus: 0,
             auto_weapon_additional_projectiles_bonus: 0,
             auto_weapon_chain_bonus: 0,
             auto_weapon_chain_range_multiplier: 1.0,
+            auto_weapon_knockback_bonus: 0.0,
+            crit_chance: BASE_CRIT_CHANCE,
+            crit_multiplier: BASE_CRIT_MULTIPLIER,
             xp_gain_multiplier: 1.0,
             pickup_radius_multiplier: 1.0,
             max_health: INITIAL_SURVIVOR_MAX_HEALTH,
@@ -77,7 +118,6 @@ impl Survivor {
     }
 }
 
-fn should_despawn_survivor(next_state: Res<NextState<AppState>>) -> bool { match next_state.0 { Some(AppState::GameOver) | Some(AppState::MainMenu) => true, _ => false, } }
 fn no_survivor_exists(survivor_query: Query<(), With<Survivor>>) -> bool { survivor_query.is_empty() }
 
 impl Plugin for SurvivorPlugin {
@@ -93,7 +133,7 @@ impl Plugin for SurvivorPlugin {
                 check_survivor_death_system,
                 survivor_item_drop_collection_system,
             ).chain().run_if(in_state(AppState::InGame)))
-            .add_systems(OnExit(AppState::InGame), despawn_survivor.run_if(should_despawn_survivor));
+            .add_systems(OnSessionEnd, despawn_survivor);
     }
 }
 
@@ -102,55 +142,68 @@ fn spawn_survivor(
     asset_server: Res<AssetServer>,
     skill_library: Res<SkillLibrary>,
     weapon_library: Res<AutomaticWeaponLibrary>,
+    archetype_library: Res<ArchetypeLibrary>,
+    selected_archetype: Res<SelectedArchetype>,
 ) {
+    let archetype = archetype_library.get_archetype(selected_archetype.0).or_else(|| archetype_library.archetypes.first());
+    if archetype.is_none() { warn!("No survivor archetypes defined, spawning with default loadout."); }
+
+    let starting_skill_id = archetype.map_or(SkillId(1), |def| def.starting_skill);
     let mut initial_skills = Vec::new();
-    if let Some(_skill_def_bolt) = skill_library.get_skill_definition(SkillId(1)) {
-        let bolt_instance = ActiveSkillInstance::new(SkillId(1));
-        initial_skills.push(bolt_instance);
+    if let Some(_skill_def) = skill_library.get_skill_definition(starting_skill_id) {
+        initial_skills.push(ActiveSkillInstance::new(starting_skill_id));
     }
 
-    let default_weapon_id = AutomaticWeaponId(3);
+    let default_weapon_id = archetype.map_or(AutomaticWeaponId(3), |def| def.starting_weapon);
     let mut initial_fire_rate = 0.8;
 
     if let Some(weapon_def) = weapon_library.get_weapon_definition(default_weapon_id) {
         initial_fire_rate = weapon_def.base_fire_rate_secs;
     } else if let Some(fallback_weapon_def) = weapon_library.get_weapon_definition(AutomaticWeaponId(0)) {
-        warn!("Default weapon ID 3 (Chain Lightning) not found, falling back to ID 0 (Primordial Ichor Blast). Ensure it's defined in items.rs.");
+        warn!("Starting weapon {:?} not found, falling back to ID 0 (Primordial Ichor Blast). Ensure it's defined in items.rs.", default_weapon_id);
         initial_fire_rate = fallback_weapon_def.base_fire_rate_secs;
     } else {
-        warn!("Neither default weapon ID 3 nor fallback ID 0 found. Using hardcoded fire rate (0.8s).");
+        warn!("Neither starting weapon {:?} nor fallback ID 0 found. Using hardcoded fire rate (0.8s).", default_weapon_id);
     }
 
+    let mut survivor = Survivor::new_with_skills_and_items(initial_skills, Vec::new(), Some(default_weapon_id), &weapon_library);
+    let mut circle_of_warding = CircleOfWarding::default();
+    if let Some(archetype) = archetype { archetype.apply_to(&mut survivor, &mut circle_of_warding); }
+    let sprite_path = archetype.map_or_else(|| "sprites/survivor_placeholder.png".to_string(), |def| def.sprite_path.clone());
+    let starting_health = survivor.max_health;
+
     commands.spawn((
         SpriteBundle {
-            texture: asset_server.load("sprites/survivor_placeholder.png"),
+            texture: asset_server.load(sprite_path),
             sprite: Sprite { custom_size: Some(SURVIVOR_SIZE), ..default() },
             transform: Transform::from_xyz(0.0, 0.0, 1.0),
             ..default()
         },
-        Survivor::new_with_skills_and_items(initial_skills, Vec::new(), Some(default_weapon_id), &weapon_library),
-        ComponentHealth(INITIAL_SURVIVOR_MAX_HEALTH),
+        survivor,
+        ComponentHealth(starting_health),
         Velocity(Vec2::ZERO),
         SanityStrain {
             base_fire_rate_secs: initial_fire_rate,
             fire_timer: Timer::from_seconds(initial_fire_rate, TimerMode::Repeating),
         },
-        CircleOfWarding::default(),
+        circle_of_warding,
         SwarmOfNightmares::default(),
+        SurvivorDodge::default(),
         Name::new("Survivor"),
     ));
 }
 fn despawn_survivor(mut commands: Commands, survivor_query: Query<Entity, With<Survivor>>) { if let Ok(survivor_entity) = survivor_query.get_single() { commands.entity(survivor_entity).despawn_recursive(); } }
-fn survivor_health_regeneration_system(time: Res<Time>, mut query: Query<(&Survivor, &mut ComponentHealth)>,) { for (survivor_stats, mut current_health) in query.iter_mut() { if survivor_stats.health_regen_rate > 0.0 && current_health.0 > 0 && current_health.0 < survivor_stats.max_health { let regen_amount = survivor_stats.health_regen_rate * time.delta_seconds(); current_health.0 = (current_health.0 as f32 + regen_amount).round() as i32; current_health.0 = current_health.0.min(survivor_stats.max_health); } } }
-fn survivor_movement( keyboard_input: Res<ButtonInput<KeyCode>>, mut query: Query<(&Survivor, &mut Transform, &mut Velocity, Option<&SurvivorBuffEffect>)>, time: Res<Time>,) { for (survivor, mut transform, mut velocity, buff_effect_opt) in query.iter_mut() { let mut direction = Vec2::ZERO; if keyboard_input.pressed(KeyCode::KeyA) { direction.x -= 1.0; } if keyboard_input.pressed(KeyCode::KeyD) { direction.x += 1.0; } if keyboard_input.pressed(KeyCode::KeyW) { direction.y += 1.0; } if keyboard_input.pressed(KeyCode::KeyS) { direction.y -= 1.0; } let mut current_speed = survivor.speed; if let Some(buff) = buff_effect_opt { current_speed *= 1.0 + buff.speed_multiplier_bonus; } velocity.0 = if direction != Vec2::ZERO { direction.normalize() * current_speed } else { Vec2::ZERO }; transform.translation.x += velocity.0.x * time.delta_seconds(); transform.translation.y += velocity.0.y * time.delta_seconds(); } }
-fn survivor_aiming(mut survivor_query: Query<(&mut Survivor, &Transform)>, window_query: Query<&Window, With<PrimaryWindow>>, camera_query: Query<(&Camera, &GlobalTransform)>,) { if let Ok((mut survivor, survivor_transform)) = survivor_query.get_single_mut() { if let Ok(primary_window) = window_query.get_single() { if let Ok((camera, camera_transform)) = camera_query.get_single() { if let Some(cursor_position) = primary_window.cursor_position() { if let Some(world_position) = camera.viewport_to_world_2d(camera_transform, cursor_position) { let direction_to_mouse = (world_position - survivor_transform.translation.truncate()).normalize_or_zero(); if direction_to_mouse != Vec2::ZERO { survivor.aim_direction = direction_to_mouse; } } } } } } }
+fn survivor_health_regeneration_system(time: Res<Time>, run_modifiers: Res<RunModifiers>, mut query: Query<(&Survivor, &mut ComponentHealth)>,) { for (survivor_stats, mut current_health) in query.iter_mut() { let regen_rate = survivor_stats.health_regen_rate * run_modifiers.health_regen_multiplier; if regen_rate > 0.0 && current_health.0 > 0 && current_health.0 < survivor_stats.max_health { let regen_amount = regen_rate * time.delta_seconds(); current_health.0 = (current_health.0 as f32 + regen_amount).round() as i32; current_health.0 = current_health.0.min(survivor_stats.max_health); } } }
+type SurvivorMovementQuery<'w, 's> = Query<'w, 's, (&'static Survivor, &'static mut Transform, &'static mut Velocity, Option<&'static SurvivorBuffEffect>, Option<&'static SurvivorDash>)>;
+fn survivor_movement( keyboard_input: Res<ButtonInput<KeyCode>>, mut query: SurvivorMovementQuery, time: Res<Time>,) { for (survivor, mut transform, mut velocity, buff_effect_opt, dash_opt) in query.iter_mut() { let mut direction = Vec2::ZERO; if keyboard_input.pressed(KeyCode::KeyA) { direction.x -= 1.0; } if keyboard_input.pressed(KeyCode::KeyD) { direction.x += 1.0; } if keyboard_input.pressed(KeyCode::KeyW) { direction.y += 1.0; } if keyboard_input.pressed(KeyCode::KeyS) { direction.y -= 1.0; } let mut current_speed = survivor.speed; if let Some(buff) = buff_effect_opt { current_speed *= 1.0 + buff.speed_multiplier_bonus; } velocity.0 = if let Some(dash) = dash_opt { dash.velocity } else if direction != Vec2::ZERO { direction.normalize() * current_speed } else { Vec2::ZERO }; transform.translation.x += velocity.0.x * time.delta_seconds(); transform.translation.y += velocity.0.y * time.delta_seconds(); } }
+fn survivor_aiming(mut survivor_query: Query<(&mut Survivor, &Transform)>, window_query: Query<&Window, With<PrimaryWindow>>, camera_query: Query<(&Camera, &GlobalTransform)>,) { if let Ok((mut survivor, survivor_transform)) = survivor_query.get_single_mut() { if let Ok(primary_window) = window_query.get_single() { if let Ok((camera, camera_transform)) = camera_query.get_single() { if let Some(cursor_position) = primary_window.cursor_position() { if let Some(world_position) = camera.viewport_to_world_2d(camera_transform, cursor_position) { survivor.aim_world_position = world_position; let direction_to_mouse = (world_position - survivor_transform.translation.truncate()).normalize_or_zero(); if direction_to_mouse != Vec2::ZERO { survivor.aim_direction = direction_to_mouse; } } } } } } }
 
 fn survivor_casting_system(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     time: Res<Time>,
     mut query: Query<(&Transform, &Survivor, &mut SanityStrain, Option<&SurvivorBuffEffect>)>,
-    mut sound_event_writer: EventWriter<PlaySoundEvent>,
+    mut sound_event_writer: EventWriter<PlaySoundAtEvent>,
     weapon_library: Res<AutomaticWeaponLibrary>,
     particle_effects_res: Option<Res<LightningParticleEffects>>,
 ) {
@@ -177,7 +230,7 @@ fn survivor_casting_system(
 
         if sanity_strain.fire_timer.just_finished() {
             if survivor_stats.aim_direction != Vec2::ZERO {
-                sound_event_writer.send(PlaySoundEvent(SoundEffect::RitualCast));
+                sound_event_writer.send(PlaySoundAtEvent(SoundEffect::RitualCast, survivor_transform.translation));
 
                 let current_damage = weapon_def.base_damage + survivor_stats.auto_weapon_damage_bonus;
                 let effective_projectile_lifetime_secs = weapon_def.projectile_lifetime_secs;
@@ -198,17 +251,21 @@ fn survivor_casting_system(
                     spawn_automatic_projectile(
                         &mut commands,
                         &asset_server,
-                        survivor_transform.translation,
-                        fragment_direction,
-                        current_damage,
-                        current_speed,
-                        current_piercing,
-                        current_chains,
-                        weapon_def.id,
-                        weapon_def.projectile_sprite_path,
-                        weapon_def.projectile_size,
-                        weapon_def.projectile_color,
-                        effective_projectile_lifetime_secs,
+                        ProjectileSpawnParams {
+                            position: survivor_transform.translation,
+                            direction: fragment_direction,
+                            damage: current_damage,
+                            speed: current_speed,
+                            piercing: current_piercing,
+                            chains: current_chains,
+                            knockback_force: weapon_def.base_knockback + survivor_stats.auto_weapon_knockback_bonus,
+                            weapon_id: weapon_def.id,
+                            sprite_path: weapon_def.projectile_sprite_path,
+                            size: weapon_def.projectile_size,
+                            color: weapon_def.projectile_color,
+                            lifetime_secs: effective_projectile_lifetime_secs,
+                            crit: CritStats::for_attack(survivor_stats, weapon_def.crit_chance_override, weapon_def.crit_multiplier_override),
+                        },
                         particle_effects_res.as_ref(),
                     );
                 }
@@ -216,25 +273,40 @@ fn survivor_casting_system(
         }
     }
 }
+/// Feedback fired when a horror lands a hit on the survivor.
+#[derive(SystemParam)]
+struct SurvivorHitFeedback<'w> {
+    sound: EventWriter<'w, PlaySoundAtEvent>,
+    hitstop: EventWriter<'w, HitstopEvent>,
+    camera_shake: EventWriter<'w, CameraShakeEvent>,
+}
+
 fn survivor_horror_collision_system(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut survivor_query: Query<(Entity, &Transform, &mut ComponentHealth, &mut Survivor, Option<&mut ActiveShield>)>,
-    horror_query: Query<(&Transform, &Horror)>,
+    horror_query: Query<(&Transform, &Horror, Option<&FrenziedBehemothBehavior>)>,
     item_library: Res<ItemLibrary>,
-    mut sound_event_writer: EventWriter<PlaySoundEvent>,
+    mut hit_feedback: SurvivorHitFeedback,
+    mut run_stats: ResMut<RunStatistics>,
 ) {
     if let Ok((survivor_entity, survivor_transform, mut survivor_health, mut survivor_component, mut opt_active_shield)) = survivor_query.get_single_mut() {
         if !survivor_component.invincibility_timer.finished() { return; }
 
-        for (horror_transform, horror_stats) in horror_query.iter() {
+        for (horror_transform, horror_stats, behemoth_opt) in horror_query.iter() {
             let distance = survivor_transform.translation.truncate().distance(horror_transform.translation.truncate());
             let survivor_radius = SURVIVOR_SIZE.x / 2.0;
             let horror_radius = horror_stats.size.x / 2.0;
 
             if distance < survivor_radius + horror_radius {
                 if survivor_component.invincibility_timer.finished() {
-                    sound_event_writer.send(PlaySoundEvent(SoundEffect::SurvivorHit));
+                    hit_feedback.sound.send(PlaySoundAtEvent(SoundEffect::SurvivorHit, survivor_transform.translation));
+                    if behemoth_opt.is_some_and(|behemoth| behemoth.state == FrenziedBehemothState::Charging) {
+                        hit_feedback.hitstop.send(HitstopEvent { duration_secs: BEHEMOTH_SLAM_HITSTOP_SECS });
+                        hit_feedback.camera_shake.send(CameraShakeEvent { trauma: BEHEMOTH_SLAM_TRAUMA });
+                    } else {
+                        hit_feedback.camera_shake.send(CameraShakeEvent { trauma: SURVIVOR_HIT_TRAUMA });
+                    }
                     let mut damage_to_take = horror_stats.damage_on_collision;
 
                     if let Some(ref mut shield) = opt_active_shield {
@@ -251,9 +323,10 @@ fn survivor_horror_collision_system(
 
                     if damage_to_take > 0 {
                         survivor_health.0 -= damage_to_take;
+                        run_stats.record_damage_taken(horror_stats.horror_type, damage_to_take);
                     }
 
-                    survivor_component.invincibility_timer.reset();
+                    survivor_component.grant_invulnerability(SURVIVOR_HIT_INVINCIBILITY_SECS);
 
                     let mut rng = rand::thread_rng();
                     for item_id in survivor_component.collected_item_ids.iter() {
@@ -280,4 +353,4 @@ fn survivor_horror_collision_system(
 }
 fn survivor_invincibility_system(time: Res<Time>, mut query: Query<(&mut Survivor, &mut Sprite, &ComponentHealth)>,) { for (mut survivor, mut sprite, health) in query.iter_mut() { if health.0 <= 0 { if sprite.color.a() != 1.0 { sprite.color.set_a(1.0); } continue; } if !survivor.invincibility_timer.finished() { survivor.invincibility_timer.tick(time.delta()); let alpha = (time.elapsed_seconds() * 20.0).sin() / 2.0 + 0.7; sprite.color.set_a(alpha.clamp(0.3, 1.0) as f32); } else { if sprite.color.a() != 1.0 { sprite.color.set_a(1.0); } } } }
 fn check_survivor_death_system(survivor_query: Query<&ComponentHealth, With<Survivor>>, mut app_state_next: ResMut<NextState<AppState>>, mut sound_event_writer: EventWriter<PlaySoundEvent>, current_app_state: Res<State<AppState>>,) { if let Ok(survivor_health) = survivor_query.get_single() { if survivor_health.0 <= 0 && *current_app_state.get() == AppState::InGame { sound_event_writer.send(PlaySoundEvent(SoundEffect::MadnessConsumes)); app_state_next.set(AppState::GameOver); } } }
-fn survivor_item_drop_collection_system(mut commands: Commands, survivor_query: Query<&Transform, With<Survivor>>, item_drop_query: Query<(Entity, &Transform, &ItemDrop)>, mut item_collected_event_writer: EventWriter<ItemCollectedEvent>, mut sound_event_writer: EventWriter<PlaySoundEvent>,) { if let Ok(survivor_transform) = survivor_query.get_single() { let survivor_pos = survivor_transform.translation.truncate(); for (item_drop_entity, item_drop_transform, item_drop_data) in item_drop_query.iter() { let item_drop_pos = item_drop_transform.translation.truncate(); if survivor_pos.distance(item_drop_pos) < ITEM_COLLECTION_RADIUS { item_collected_event_writer.send(ItemCollectedEvent(item_drop_data.item_id)); sound_event_writer.send(PlaySoundEvent(SoundEffect::SoulCollect)); commands.entity(item_drop_entity).despawn_recursive(); } } } }
\ No newline at end of file
+fn survivor_item_drop_collection_system(mut commands: Commands, survivor_query: Query<&Transform, With<Survivor>>, item_drop_query: Query<(Entity, &Transform, &ItemDrop)>, mut item_collected_event_writer: EventWriter<ItemCollectedEvent>, mut sound_event_writer: EventWriter<PlaySoundAtEvent>,) { if let Ok(survivor_transform) = survivor_query.get_single() { let survivor_pos = survivor_transform.translation.truncate(); for (item_drop_entity, item_drop_transform, item_drop_data) in item_drop_query.iter() { let item_drop_pos = item_drop_transform.translation.truncate(); if survivor_pos.distance(item_drop_pos) < ITEM_COLLECTION_RADIUS { item_collected_event_writer.send(ItemCollectedEvent(item_drop_data.item_id)); sound_event_writer.send(PlaySoundAtEvent(SoundEffect::SoulCollect, item_drop_transform.translation)); commands.entity(item_drop_entity).despawn_recursive(); } } } }
\ No newline at end of file
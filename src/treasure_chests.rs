@@ -0,0 +1,263 @@
+use bevy::{prelude::*, ecs::system::SystemParam};
+use rand::{Rng, seq::SliceRandom};
+use crate::{
+    game::{AppState, OnSessionEnd, GameState, UpgradeChosenEvent, ItemCollectedEvent},
+    survivor::{Survivor, SanityStrain},
+    horror::HorrorKilledEvent,
+    items::{ItemId, ItemLibrary, AutomaticWeaponLibrary, WeaponEvolution},
+    upgrades::{UpgradePool, UpgradeCard, SurvivorUpgradeContext},
+    weapons::{CircleOfWarding, SwarmOfNightmares},
+    achievements::ContentLocks,
+    audio::{PlaySoundEvent, SoundEffect},
+};
+
+const CHEST_SIZE: Vec2 = Vec2::new(36.0, 30.0);
+const CHEST_PICKUP_RADIUS: f32 = 40.0;
+/// (reward count, weight) — most chests hold a single reward, a lucky few hold five.
+const CHEST_REWARD_COUNT_WEIGHTS: [(usize, u32); 3] = [(1, 70), (3, 25), (5, 5)];
+const RELIC_REWARD_CHANCE: f64 = 0.35;
+const REWARD_REVEAL_INTERVAL_SECS: f32 = 0.45;
+/// Paid out instead when every weapon is maxed and every relic is owned.
+const FALLBACK_GOLD_BASE: u32 = 30;
+const FALLBACK_GOLD_PER_CYCLE: u32 = 10;
+
+#[derive(Component)]
+pub struct TreasureChest;
+
+#[derive(Debug, Clone)]
+pub enum ChestReward {
+    WeaponLevel(UpgradeCard),
+    Relic(ItemId),
+    Evolution(WeaponEvolution),
+    Gold(u32),
+}
+
+/// Rewards rolled from the chest currently being opened; applied once the reveal is dismissed.
+#[derive(Resource, Default)]
+pub struct PendingChestRewards { pub rewards: Vec<ChestReward>, revealed_count: usize, reveal_timer: Timer, }
+
+/// Everything a chest draws its rewards from.
+#[derive(SystemParam)]
+struct ChestRewardSources<'w> {
+    upgrade_pool: Res<'w, UpgradePool>,
+    item_library: Res<'w, ItemLibrary>,
+    weapon_library: Res<'w, AutomaticWeaponLibrary>,
+    content_locks: Res<'w, ContentLocks>,
+    game_state: Res<'w, GameState>,
+}
+
+#[derive(Component)] struct ChestRevealUI;
+#[derive(Component)] struct ChestRewardSlot(usize);
+#[derive(Component)] struct ChestRevealPromptText;
+
+pub struct TreasureChestsPlugin;
+
+impl Plugin for TreasureChestsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<PendingChestRewards>()
+            .add_systems(Update, (
+                spawn_chest_on_elite_or_boss_kill_system,
+                chest_collection_system,
+            ).chain().run_if(in_state(AppState::InGame)))
+            .add_systems(OnEnter(AppState::ChestReveal), setup_chest_reveal_ui)
+            .add_systems(Update, (
+                chest_reveal_sequence_system,
+                chest_reveal_input_system,
+            ).chain().run_if(in_state(AppState::ChestReveal)))
+            .add_systems(OnExit(AppState::ChestReveal), (claim_chest_rewards, despawn_chest_reveal_ui))
+            .add_systems(OnSessionEnd, despawn_all_chests);
+    }
+}
+
+fn spawn_chest_on_elite_or_boss_kill_system(mut commands: Commands, asset_server: Res<AssetServer>, mut kill_events: EventReader<HorrorKilledEvent>) {
+    for event in kill_events.read() {
+        if !event.is_elite && !event.horror_type.is_boss() { continue; }
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load("sprites/eldritch_relic_placeholder.png"),
+                sprite: Sprite { custom_size: Some(CHEST_SIZE), color: Color::rgb(1.0, 0.8, 0.3), ..default() },
+                transform: Transform::from_translation(event.position.truncate().extend(0.4)),
+                ..default()
+            },
+            TreasureChest,
+            Name::new("TreasureChest"),
+        ));
+    }
+}
+
+fn chest_collection_system(
+    mut commands: Commands,
+    survivor_query: Query<(&Transform, &Survivor, Option<&CircleOfWarding>, Option<&SwarmOfNightmares>)>,
+    chest_query: Query<(Entity, &Transform), With<TreasureChest>>,
+    sources: ChestRewardSources,
+    mut pending_rewards: ResMut<PendingChestRewards>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut sound_event_writer: EventWriter<PlaySoundEvent>,
+) {
+    // Don't clobber a level-up queued this frame; the chest will still be here afterwards.
+    if next_app_state.0.is_some() { return; }
+    let Ok((survivor_transform, survivor, opt_circle, opt_swarm)) = survivor_query.get_single() else { return; };
+    let survivor_pos = survivor_transform.translation.truncate();
+    let Some((chest_entity, _)) = chest_query.iter().find(|(_, transform)| transform.translation.truncate().distance(survivor_pos) < CHEST_PICKUP_RADIUS) else { return; };
+    commands.entity(chest_entity).despawn_recursive();
+
+    let context = SurvivorUpgradeContext {
+        is_circle_of_warding_active: opt_circle.is_some_and(|circle| circle.is_active),
+        is_swarm_of_nightmares_active: opt_swarm.is_some_and(|swarm| swarm.is_active),
+        equipped_skills: &survivor.equipped_skills,
+        current_weapon_id: survivor.equipped_weapon_id,
+        choice_limit: None,
+    };
+    let mut weapon_levels: Vec<&UpgradeCard> = sources.upgrade_pool.eligible_upgrades(&context).into_iter().filter(|card| card.upgrade_type.is_weapon_level_up()).collect();
+    let mut relics: Vec<ItemId> = sources.item_library.items.iter()
+        .map(|def| def.id)
+        .filter(|id| !sources.content_locks.items.contains(id) && !survivor.collected_item_ids.contains(id))
+        .collect();
+    let evolution = sources.weapon_library.available_evolution(survivor.equipped_weapon_id, &survivor.collected_item_ids);
+
+    let mut rng = rand::thread_rng();
+    let reward_count = CHEST_REWARD_COUNT_WEIGHTS.choose_weighted(&mut rng, |(_, weight)| *weight).map_or(1, |(count, _)| *count);
+    let mut rewards = Vec::with_capacity(reward_count);
+    // An evolution that is ready always takes the first slot.
+    if let Some(evolution) = evolution { rewards.push(ChestReward::Evolution(evolution.clone())); }
+    while rewards.len() < reward_count {
+        let wants_relic = rng.gen_bool(RELIC_REWARD_CHANCE) || weapon_levels.is_empty();
+        if wants_relic && !relics.is_empty() {
+            let relic = relics.swap_remove(rng.gen_range(0..relics.len()));
+            rewards.push(ChestReward::Relic(relic));
+        } else if !weapon_levels.is_empty() {
+            let card = weapon_levels.swap_remove(rng.gen_range(0..weapon_levels.len()));
+            rewards.push(ChestReward::WeaponLevel(card.clone()));
+        } else {
+            break;
+        }
+    }
+    // The chest is already gone, so an empty roll still pays out something.
+    if rewards.is_empty() { rewards.push(ChestReward::Gold(FALLBACK_GOLD_BASE + FALLBACK_GOLD_PER_CYCLE * sources.game_state.cycle_number)); }
+
+    *pending_rewards = PendingChestRewards { rewards, revealed_count: 0, reveal_timer: Timer::from_seconds(REWARD_REVEAL_INTERVAL_SECS, TimerMode::Repeating) };
+    sound_event_writer.send(PlaySoundEvent(SoundEffect::OmenAccepted));
+    next_app_state.set(AppState::ChestReveal);
+}
+
+fn reward_title_and_color(reward: &ChestReward, item_library: &ItemLibrary, weapon_library: &AutomaticWeaponLibrary) -> (String, String, Color) {
+    match reward {
+        ChestReward::WeaponLevel(card) => (card.name.clone(), card.description.clone(), Color::rgb(0.6, 0.8, 1.0)),
+        ChestReward::Relic(item_id) => item_library.get_item_definition(*item_id)
+            .map_or(("Unknown Relic".to_string(), String::new(), Color::GOLD), |def| (def.name.clone(), def.description.clone(), Color::GOLD)),
+        ChestReward::Evolution(evolution) => {
+            let evolved_name = weapon_library.get_weapon_definition(evolution.evolved_weapon).map_or("Unknown".to_string(), |def| def.name.clone());
+            (format!("Evolution: {}", evolved_name), "Your weapon is reborn in a greater form.".to_string(), Color::rgb(1.0, 0.4, 1.0))
+        }
+        ChestReward::Gold(amount) => (format!("{} Gold", amount), "Nothing left to find but coin.".to_string(), Color::rgb(1.0, 0.85, 0.3)),
+    }
+}
+
+fn setup_chest_reveal_ui(mut commands: Commands, asset_server: Res<AssetServer>, pending_rewards: Res<PendingChestRewards>) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands.spawn((
+        NodeBundle { style: Style { width: Val::Percent(100.0), height: Val::Percent(100.0), position_type: PositionType::Absolute, justify_content: JustifyContent::Center, align_items: AlignItems::Center, flex_direction: FlexDirection::Column, row_gap: Val::Px(24.0), ..default() }, background_color: Color::rgba(0.08, 0.05, 0.1, 0.92).into(), z_index: ZIndex::Global(10), ..default() },
+        ChestRevealUI,
+    )).with_children(|parent| {
+        parent.spawn(TextBundle::from_section("A Reliquary Opens", TextStyle { font: font.clone(), font_size: 56.0, color: Color::GOLD }).with_text_justify(JustifyText::Center));
+        parent.spawn(NodeBundle { style: Style { flex_direction: FlexDirection::Row, column_gap: Val::Px(16.0), ..default() }, ..default() }).with_children(|row| {
+            for index in 0..pending_rewards.rewards.len() {
+                row.spawn(NodeBundle { style: Style { width: Val::Px(210.0), min_height: Val::Px(150.0), padding: UiRect::all(Val::Px(10.0)), flex_direction: FlexDirection::Column, align_items: AlignItems::Center, justify_content: JustifyContent::Center, border: UiRect::all(Val::Px(2.0)), ..default() }, border_color: Color::rgb(0.4, 0.35, 0.2).into(), background_color: Color::rgb(0.15, 0.12, 0.18).into(), ..default() })
+                    .with_children(|card| {
+                        card.spawn((
+                            TextBundle::from_sections([
+                                TextSection::new("?", TextStyle { font: font.clone(), font_size: 22.0, color: Color::rgb(0.5, 0.5, 0.5) }),
+                                TextSection::new("", TextStyle { font: font.clone(), font_size: 16.0, color: Color::rgb(0.85, 0.85, 0.85) }),
+                            ]).with_text_justify(JustifyText::Center),
+                            ChestRewardSlot(index),
+                        ));
+                    });
+            }
+        });
+        parent.spawn((TextBundle::from_section("SPACE to reveal all", TextStyle { font, font_size: 24.0, color: Color::rgba(0.8, 0.8, 0.8, 1.0) }).with_text_justify(JustifyText::Center), ChestRevealPromptText));
+    });
+}
+
+fn reveal_slot(slot_text: &mut Text, reward: &ChestReward, item_library: &ItemLibrary, weapon_library: &AutomaticWeaponLibrary) {
+    let (title, description, color) = reward_title_and_color(reward, item_library, weapon_library);
+    slot_text.sections[0].value = format!("{}\n", title);
+    slot_text.sections[0].style.color = color;
+    slot_text.sections[1].value = description;
+}
+
+fn chest_reveal_sequence_system(
+    time: Res<Time>,
+    mut pending_rewards: ResMut<PendingChestRewards>,
+    item_library: Res<ItemLibrary>,
+    weapon_library: Res<AutomaticWeaponLibrary>,
+    mut slot_query: Query<(&ChestRewardSlot, &mut Text), Without<ChestRevealPromptText>>,
+    mut prompt_query: Query<&mut Text, With<ChestRevealPromptText>>,
+    mut sound_event_writer: EventWriter<PlaySoundEvent>,
+) {
+    let total = pending_rewards.rewards.len();
+    if pending_rewards.revealed_count >= total { return; }
+    pending_rewards.reveal_timer.tick(time.delta());
+    if !pending_rewards.reveal_timer.just_finished() { return; }
+
+    let index = pending_rewards.revealed_count;
+    if let Some((_, mut text)) = slot_query.iter_mut().find(|(slot, _)| slot.0 == index) {
+        reveal_slot(&mut text, &pending_rewards.rewards[index], &item_library, &weapon_library);
+    }
+    pending_rewards.revealed_count += 1;
+    sound_event_writer.send(PlaySoundEvent(SoundEffect::SoulCollect));
+    if pending_rewards.revealed_count >= total {
+        for mut text in prompt_query.iter_mut() { text.sections[0].value = "SPACE to claim".to_string(); }
+    }
+}
+
+fn chest_reveal_input_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut pending_rewards: ResMut<PendingChestRewards>,
+    item_library: Res<ItemLibrary>,
+    weapon_library: Res<AutomaticWeaponLibrary>,
+    mut slot_query: Query<(&ChestRewardSlot, &mut Text), Without<ChestRevealPromptText>>,
+    mut prompt_query: Query<&mut Text, With<ChestRevealPromptText>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    if !keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Enter]) { return; }
+
+    if pending_rewards.revealed_count < pending_rewards.rewards.len() {
+        for (slot, mut text) in slot_query.iter_mut() {
+            if let Some(reward) = pending_rewards.rewards.get(slot.0) { reveal_slot(&mut text, reward, &item_library, &weapon_library); }
+        }
+        pending_rewards.revealed_count = pending_rewards.rewards.len();
+        for mut text in prompt_query.iter_mut() { text.sections[0].value = "SPACE to claim".to_string(); }
+        return;
+    }
+    next_app_state.set(AppState::InGame);
+}
+
+/// Rewards are applied on leaving the reveal, once the survivor has seen all of them.
+fn claim_chest_rewards(
+    mut pending_rewards: ResMut<PendingChestRewards>,
+    weapon_library: Res<AutomaticWeaponLibrary>,
+    mut survivor_query: Query<(&mut Survivor, &mut SanityStrain)>,
+    mut game_state: ResMut<GameState>,
+    mut upgrade_chosen_writer: EventWriter<UpgradeChosenEvent>,
+    mut item_collected_writer: EventWriter<ItemCollectedEvent>,
+) {
+    for reward in pending_rewards.rewards.drain(..) {
+        match reward {
+            ChestReward::WeaponLevel(card) => { upgrade_chosen_writer.send(UpgradeChosenEvent(card)); }
+            ChestReward::Relic(item_id) => { item_collected_writer.send(ItemCollectedEvent(item_id)); }
+            ChestReward::Evolution(evolution) => {
+                let Ok((mut survivor, mut sanity_strain)) = survivor_query.get_single_mut() else { continue; };
+                if let Some(evolved_def) = weapon_library.get_weapon_definition(evolution.evolved_weapon) {
+                    survivor.equipped_weapon_id = Some(evolved_def.id);
+                    sanity_strain.base_fire_rate_secs = evolved_def.base_fire_rate_secs;
+                }
+            }
+            ChestReward::Gold(amount) => { game_state.gold += amount; }
+        }
+    }
+}
+
+fn despawn_chest_reveal_ui(mut commands: Commands, query: Query<Entity, With<ChestRevealUI>>) { for entity in query.iter() { commands.entity(entity).despawn_recursive(); } }
+
+fn despawn_all_chests(mut commands: Commands, query: Query<Entity, With<TreasureChest>>) { for entity in query.iter() { commands.entity(entity).despawn_recursive(); } }
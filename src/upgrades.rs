@@ -24,6 +24,7 @@ pub enum UpgradeType {
     IncreaseAutoWeaponProjectiles(u32),   
     IncreaseAutoWeaponChains(u32),
     IncreaseAutoWeaponChainRangePercent(u32),
+    IncreaseAutoWeaponKnockback(f32),
 
     // Circle of Warding
     InscribeCircleOfWarding,
@@ -37,6 +38,15 @@ pub enum UpgradeType {
     IncreaseNightmareDamage(i32), 
     IncreaseNightmareRadius(f32), 
     IncreaseNightmareRotationSpeed(f32),
+
+    // Dodge
+    IncreaseDodgeCharges(u32),
+    ReduceDodgeCooldown(u32),
+    IncreaseDodgeDamage(i32),
+
+    // Critical Hits
+    IncreaseCritChance(u32), // Percentage points
+    IncreaseCritMultiplier(u32), // Percent of base damage added to each crit
     
     // Active Skill Upgrades
     IncreaseSkillDamage { slot_index: usize, amount: i32 }, 
@@ -44,12 +54,27 @@ pub enum UpgradeType {
     IncreaseSkillAoERadius { slot_index: usize, percent_increase: f32 }, // For skills with inherent AoE
     AddSkillImpactAoE { slot_index: usize, radius: f32, damage_fraction: f32 }, // New for projectile impact AoE
     IncreaseSkillPiercing { slot_index: usize, amount: u32 }, // New for projectile piercing
+    IncreaseSkillKnockback { slot_index: usize, amount: f32 },
+    IncreaseSkillDuration { skill_id: SkillId, secs: f32 }, // Keyed by skill rather than slot; dash uses it for i-frames
+    IncreaseSkillRange { skill_id: SkillId, percent_increase: f32 },
     
     // Utility/Granting
     GrantRandomRelic, 
     GrantSkill(SkillId),
 }
 
+impl UpgradeType {
+    /// Raises the level of something the survivor already wields, rather than granting something new.
+    pub fn is_weapon_level_up(&self) -> bool {
+        matches!(self,
+            UpgradeType::IncreaseAutoWeaponDamage(_) | UpgradeType::IncreaseAutoWeaponFireRate(_) | UpgradeType::IncreaseAutoWeaponProjectileSpeed(_) |
+            UpgradeType::IncreaseAutoWeaponPiercing(_) | UpgradeType::IncreaseAutoWeaponProjectiles(_) | UpgradeType::IncreaseAutoWeaponChains(_) |
+            UpgradeType::IncreaseAutoWeaponChainRangePercent(_) | UpgradeType::IncreaseAutoWeaponKnockback(_) |
+            UpgradeType::IncreaseCircleRadius(_) | UpgradeType::IncreaseCircleDamage(_) | UpgradeType::DecreaseCircleTickRate(_) |
+            UpgradeType::IncreaseNightmareCount(_) | UpgradeType::IncreaseNightmareDamage(_) | UpgradeType::IncreaseNightmareRadius(_) | UpgradeType::IncreaseNightmareRotationSpeed(_))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct UpgradeCard { pub id: UpgradeId, pub name: String, pub description: String, pub upgrade_type: UpgradeType, }
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -63,6 +88,7 @@ pub struct SurvivorUpgradeContext<'a> {
     pub is_swarm_of_nightmares_active: bool,
     pub equipped_skills: &'a Vec<ActiveSkillInstance>,
     pub current_weapon_id: Option<AutomaticWeaponId>, 
+    pub choice_limit: Option<usize>,
 }
 
 impl UpgradePool {
@@ -91,6 +117,8 @@ impl UpgradePool {
             UpgradeCard {id: UpgradeId(203), name: "Storm Conduit".to_string(), description: "Your automatic attacks chain to 2 additional nearby enemies. +2 Chains.".to_string(), upgrade_type: UpgradeType::IncreaseAutoWeaponChains(2),}, 
             UpgradeCard {id: UpgradeId(204), name: "Reaching Tendrils".to_string(), description: "Your chain lightning arcs to more distant foes. +25% Chain Range.".to_string(), upgrade_type: UpgradeType::IncreaseAutoWeaponChainRangePercent(25),}, 
             UpgradeCard {id: UpgradeId(205), name: "Voltaic Grasp".to_string(), description: "Your chain lightning arcs significantly further. +50% Chain Range.".to_string(), upgrade_type: UpgradeType::IncreaseAutoWeaponChainRangePercent(50),}, 
+            UpgradeCard {id: UpgradeId(206), name: "Crushing Presence".to_string(), description: "Your automatic attacks hurl horrors backward. +40 Knockback.".to_string(), upgrade_type: UpgradeType::IncreaseAutoWeaponKnockback(40.0),},
+            UpgradeCard {id: UpgradeId(207), name: "Repelling Sigils".to_string(), description: "Your automatic attacks strike like a battering ram. +80 Knockback.".to_string(), upgrade_type: UpgradeType::IncreaseAutoWeaponKnockback(80.0),},
 
 
             // Echoes (XP) & Pickups
@@ -126,8 +154,23 @@ impl UpgradePool {
             UpgradeCard {id: UpgradeId(520), name: "Unstable Bolt".to_string(), description: "Skill in Slot 1 (Eldritch Bolt) explodes on impact for 30% damage in a small area (50 radius).".to_string(), upgrade_type: UpgradeType::AddSkillImpactAoE { slot_index: 0, radius: 50.0, damage_fraction: 0.30 },},
             UpgradeCard {id: UpgradeId(521), name: "Volatile Bolt".to_string(), description: "Skill in Slot 1 (Eldritch Bolt) explodes with greater force (75 radius, 50% damage).".to_string(), upgrade_type: UpgradeType::AddSkillImpactAoE { slot_index: 0, radius: 75.0, damage_fraction: 0.50 },},
             // (Could add for Void Lance - SkillId(3) - if it's in slot_index 2)
+            UpgradeCard {id: UpgradeId(530), name: "Concussive Bolt".to_string(), description: "Skill in Slot 1 (Eldritch Bolt) knocks horrors back further. +60 Knockback.".to_string(), upgrade_type: UpgradeType::IncreaseSkillKnockback { slot_index: 0, amount: 60.0 },},
+            UpgradeCard {id: UpgradeId(531), name: "Lance of Banishment".to_string(), description: "Skill in Slot 3 (Void Lance) drives horrors away with overwhelming force. +100 Knockback.".to_string(), upgrade_type: UpgradeType::IncreaseSkillKnockback { slot_index: 2, amount: 100.0 },},
             UpgradeCard {id: UpgradeId(522), name: "Void Detonation".to_string(), description: "Skill in Slot 3 (Void Lance) erupts on final impact (60 radius, 40% damage).".to_string(), upgrade_type: UpgradeType::AddSkillImpactAoE{slot_index: 2, radius: 60.0, damage_fraction: 0.40},},
             
+            // Dodge
+            UpgradeCard {id: UpgradeId(900), name: "Doubled Shadow".to_string(), description: "Your shadow remembers how to flee. +1 Dodge charge.".to_string(), upgrade_type: UpgradeType::IncreaseDodgeCharges(1),},
+            UpgradeCard {id: UpgradeId(901), name: "Restless Feet".to_string(), description: "Dodge charges return 20% faster.".to_string(), upgrade_type: UpgradeType::ReduceDodgeCooldown(20),},
+            UpgradeCard {id: UpgradeId(902), name: "Slipstream of the Void".to_string(), description: "Dodge charges return 30% faster.".to_string(), upgrade_type: UpgradeType::ReduceDodgeCooldown(30),},
+            UpgradeCard {id: UpgradeId(903), name: "Rending Passage".to_string(), description: "Horrors you dodge through take 15 damage.".to_string(), upgrade_type: UpgradeType::IncreaseDodgeDamage(15),},
+            UpgradeCard {id: UpgradeId(904), name: "Flaying Passage".to_string(), description: "Horrors you dodge through take 25 damage.".to_string(), upgrade_type: UpgradeType::IncreaseDodgeDamage(25),},
+
+            // Critical Hits
+            UpgradeCard {id: UpgradeId(910), name: "Glimpse of the Weak Point".to_string(), description: "+5% critical hit chance for weapons and skills.".to_string(), upgrade_type: UpgradeType::IncreaseCritChance(5),},
+            UpgradeCard {id: UpgradeId(911), name: "Eyes Behind the Veil".to_string(), description: "+10% critical hit chance for weapons and skills.".to_string(), upgrade_type: UpgradeType::IncreaseCritChance(10),},
+            UpgradeCard {id: UpgradeId(912), name: "Cruel Precision".to_string(), description: "Critical hits deal +25% more damage.".to_string(), upgrade_type: UpgradeType::IncreaseCritMultiplier(25),},
+            UpgradeCard {id: UpgradeId(913), name: "Rend the Unseen".to_string(), description: "Critical hits deal +50% more damage.".to_string(), upgrade_type: UpgradeType::IncreaseCritMultiplier(50),},
+
             // General/Utility
             UpgradeCard {id: UpgradeId(600), name: "Mysterious Relic".to_string(), description: "The abyss grants you a random relic.".to_string(), upgrade_type: UpgradeType::GrantRandomRelic,},
 
@@ -139,6 +182,20 @@ impl UpgradePool {
             UpgradeCard {id: UpgradeId(703), name: "Learn: Glacial Nova".to_string(), description: "Unlock the Glacial Nova chilling skill. (Requires free skill slot)".to_string(), upgrade_type: UpgradeType::GrantSkill(SkillId(5)),},
             UpgradeCard {id: UpgradeId(704), name: "Learn: Psychic Sentry".to_string(), description: "Unlock the Psychic Sentry summon skill. (Requires free skill slot)".to_string(), upgrade_type: UpgradeType::GrantSkill(SkillId(6)),},
             UpgradeCard {id: UpgradeId(705), name: "Learn: Ethereal Ward".to_string(), description: "Unlock the Ethereal Ward defensive skill. (Requires free skill slot)".to_string(), upgrade_type: UpgradeType::GrantSkill(SkillId(7)),},
+            UpgradeCard {id: UpgradeId(706), name: "Learn: Shadow Step".to_string(), description: "Unlock the Shadow Step dash skill. (Requires free skill slot)".to_string(), upgrade_type: UpgradeType::GrantSkill(SkillId(8)),},
+            UpgradeCard {id: UpgradeId(707), name: "Learn: Abyssal Gaze".to_string(), description: "Unlock the Abyssal Gaze channeled beam skill. (Requires free skill slot)".to_string(), upgrade_type: UpgradeType::GrantSkill(SkillId(9)),},
+            UpgradeCard {id: UpgradeId(708), name: "Learn: Maelstrom of Madness".to_string(), description: "Unlock the Maelstrom of Madness pulling vortex skill. (Requires free skill slot)".to_string(), upgrade_type: UpgradeType::GrantSkill(SkillId(10)),},
+            UpgradeCard {id: UpgradeId(709), name: "Learn: Bound Familiar".to_string(), description: "Unlock the Bound Familiar companion skill. (Requires free skill slot)".to_string(), upgrade_type: UpgradeType::GrantSkill(SkillId(11)),},
+
+            // Skill Archetype Upgrades (keyed by skill)
+            UpgradeCard {id: UpgradeId(712), name: "Lengthened Stride".to_string(), description: "Shadow Step carries you 25% further.".to_string(), upgrade_type: UpgradeType::IncreaseSkillRange { skill_id: SkillId(8), percent_increase: 0.25 },},
+            UpgradeCard {id: UpgradeId(713), name: "Lingering Phase".to_string(), description: "Shadow Step leaves you untouchable for 0.25s longer.".to_string(), upgrade_type: UpgradeType::IncreaseSkillDuration { skill_id: SkillId(8), secs: 0.25 },},
+            UpgradeCard {id: UpgradeId(714), name: "Unblinking Gaze".to_string(), description: "Abyssal Gaze is channeled for 1s longer.".to_string(), upgrade_type: UpgradeType::IncreaseSkillDuration { skill_id: SkillId(9), secs: 1.0 },},
+            UpgradeCard {id: UpgradeId(715), name: "Far-Seeing Gaze".to_string(), description: "Abyssal Gaze reaches 20% further.".to_string(), upgrade_type: UpgradeType::IncreaseSkillRange { skill_id: SkillId(9), percent_increase: 0.20 },},
+            UpgradeCard {id: UpgradeId(716), name: "Devouring Maelstrom".to_string(), description: "Maelstrom of Madness pulls from 20% further away.".to_string(), upgrade_type: UpgradeType::IncreaseSkillRange { skill_id: SkillId(10), percent_increase: 0.20 },},
+            UpgradeCard {id: UpgradeId(717), name: "Endless Maelstrom".to_string(), description: "Maelstrom of Madness churns for 1.5s longer.".to_string(), upgrade_type: UpgradeType::IncreaseSkillDuration { skill_id: SkillId(10), secs: 1.5 },},
+            UpgradeCard {id: UpgradeId(718), name: "Loyal Familiar".to_string(), description: "Bound Familiar stays by your side for 5s longer.".to_string(), upgrade_type: UpgradeType::IncreaseSkillDuration { skill_id: SkillId(11), secs: 5.0 },},
+            UpgradeCard {id: UpgradeId(719), name: "Keen-Eyed Familiar".to_string(), description: "Bound Familiar attacks horrors from 25% further away.".to_string(), upgrade_type: UpgradeType::IncreaseSkillRange { skill_id: SkillId(11), percent_increase: 0.25 },},
 
 
             // Skill Meta Upgrades (Cooldown & AoE)
@@ -157,10 +214,11 @@ impl UpgradePool {
         ];
     }
 
-    pub fn get_random_upgrades(&self, count: usize, context: &SurvivorUpgradeContext) -> Vec<UpgradeCard> {
+    /// Every card that can currently be applied to the survivor, regardless of how many will be offered.
+    pub fn eligible_upgrades(&self, context: &SurvivorUpgradeContext) -> Vec<&UpgradeCard> {
         let is_chain_weapon_equipped = context.current_weapon_id == Some(crate::items::AutomaticWeaponId(3));
 
-        let filtered_upgrades: Vec<UpgradeCard> = self.available_upgrades.iter().filter(|card| {
+        self.available_upgrades.iter().filter(|card| {
             match &card.upgrade_type {
                 UpgradeType::InscribeCircleOfWarding => !context.is_circle_of_warding_active,
                 UpgradeType::IncreaseCircleRadius(_) | UpgradeType::IncreaseCircleDamage(_) | UpgradeType::DecreaseCircleTickRate(_) => {
@@ -174,9 +232,13 @@ impl UpgradePool {
                 UpgradeType::ReduceSkillCooldown { slot_index, .. } | 
                 UpgradeType::IncreaseSkillAoERadius { slot_index, .. } |
                 UpgradeType::AddSkillImpactAoE { slot_index, ..} | // Check if skill exists for these new types
-                UpgradeType::IncreaseSkillPiercing { slot_index, ..} => { // Check if skill exists
+                UpgradeType::IncreaseSkillPiercing { slot_index, ..} | // Check if skill exists
+                UpgradeType::IncreaseSkillKnockback { slot_index, .. } => {
                     context.equipped_skills.get(*slot_index).is_some()
                 }
+                UpgradeType::IncreaseSkillDuration { skill_id, .. } | UpgradeType::IncreaseSkillRange { skill_id, .. } => {
+                    context.equipped_skills.iter().any(|s| s.definition_id == *skill_id)
+                }
                 UpgradeType::GrantSkill(skill_id_to_grant) => {
                     context.equipped_skills.len() < MAX_ACTIVE_SKILLS &&
                     !context.equipped_skills.iter().any(|s| s.definition_id == *skill_id_to_grant)
@@ -185,10 +247,14 @@ impl UpgradePool {
                 UpgradeType::IncreaseAutoWeaponChainRangePercent(_) => is_chain_weapon_equipped, 
                 _ => true, 
             }
-        }).cloned().collect();
+        }).collect()
+    }
 
+    pub fn get_random_upgrades(&self, count: usize, context: &SurvivorUpgradeContext) -> Vec<UpgradeCard> {
+        let filtered_upgrades = self.eligible_upgrades(context);
         let mut rng = rand::thread_rng();
-        filtered_upgrades.choose_multiple(&mut rng, count).cloned().collect()
+        let count = context.choice_limit.map_or(count, |limit| count.min(limit));
+        filtered_upgrades.choose_multiple(&mut rng, count).map(|card| (*card).clone()).collect()
     }
 }
 
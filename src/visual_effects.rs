@@ -1,78 +1,104 @@
 // mescgit/bulletheavengame/bulletheavengame-72055389645106003b8bc2106f4eca70046cf9ad/src/visual_effects.rs
-use bevy::prelude::*;
-use rand::random; // Changed for direct use of rand::random()
-use crate::game::AppState;
+use bevy::{prelude::*, ecs::system::SystemParam};
+use crate::{game::AppState, audio::PlaySoundAtEvent, run_statistics::DamageDealtEvent};
 
-const DAMAGE_TEXT_LIFETIME_SECONDS: f32 = 0.75;
-const DAMAGE_TEXT_SPEED: f32 = 60.0;
-// Removed unused DAMAGE_TEXT_FADE_SPEED
+const HIT_FLASH_DURATION_SECS: f32 = 0.08;
+// Overbright tint: sprite color multiplies the texture, so values above 1.0 wash it out towards white.
+const HIT_FLASH_COLOR: Color = Color::rgb(4.0, 4.0, 4.0);
+
+pub const HEAVY_HIT_KNOCKBACK_THRESHOLD: f32 = 300.0;
+pub const HEAVY_HIT_HITSTOP_SECS: f32 = 0.06;
+/// A charging behemoth connecting with the survivor; twice a heavy hit.
+pub const BEHEMOTH_SLAM_HITSTOP_SECS: f32 = 0.12;
+const HITSTOP_TIME_SCALE: f32 = 0.05;
+const HITSTOP_COOLDOWN_SECS: f32 = 0.3;
 
 pub struct VisualEffectsPlugin;
 
 impl Plugin for VisualEffectsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update,
-            animate_damage_text_system.run_if(in_state(AppState::InGame))
-        );
+        app.add_event::<HitstopEvent>()
+            .init_resource::<Hitstop>()
+            .add_systems(Update, hit_flash_system.run_if(in_state(AppState::InGame)))
+            // PostUpdate, so it sees this frame's rotation from every horror AI system before transforms propagate.
+            .add_systems(PostUpdate, upright_anchor_system.before(bevy::transform::TransformSystem::TransformPropagate).run_if(in_state(AppState::InGame)))
+            // Not gated on InGame so a hitstop that is running when the state changes still restores time.
+            .add_systems(Update, hitstop_system);
     }
 }
 
-#[derive(Component)]
-pub struct DamageTextEffect {
-    pub spawn_time: f32,
-    pub velocity: Vec2,
+/// Briefly freezes the game on heavy impacts.
+#[derive(Event)]
+pub struct HitstopEvent { pub duration_secs: f32, }
+
+/// What a projectile hit on a horror reports: the impact sound, a hitstop for heavy hits and the damage for run statistics.
+#[derive(SystemParam)]
+pub struct HorrorHitWriters<'w> {
+    pub sound: EventWriter<'w, PlaySoundAtEvent>,
+    pub hitstop: EventWriter<'w, HitstopEvent>,
+    pub damage: EventWriter<'w, DamageDealtEvent>,
 }
 
-pub fn spawn_damage_text(
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    position: Vec3,
-    damage_amount: i32,
-    time: &Res<Time>,
-) {
-    let random_offset_x = (random::<f32>() - 0.5) * 20.0;
+#[derive(Resource, Default)]
+pub struct Hitstop { remaining_secs: f32, cooldown_secs: f32, }
 
-    commands.spawn((
-        Text2dBundle {
-            text: Text::from_section(
-                damage_amount.to_string(),
-                TextStyle {
-                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                    font_size: 20.0,
-                    color: Color::rgb(1.0, 0.8, 0.8),
-                },
-            ),
-            transform: Transform::from_translation(position + Vec3::new(random_offset_x, 10.0, 5.0)),
-            ..default()
-        },
-        DamageTextEffect {
-            spawn_time: time.elapsed_seconds(),
-            velocity: Vec2::new(random_offset_x * 0.5, DAMAGE_TEXT_SPEED),
-        },
-        Name::new("DamageText"),
-    ));
-}
+/// Flashes a sprite white for a moment after it is hit. Re-inserting it while flashing just restarts the flash.
+#[derive(Component)]
+pub struct HitFlash { pub timer: Timer, }
+impl Default for HitFlash { fn default() -> Self { Self { timer: Timer::from_seconds(HIT_FLASH_DURATION_SECS, TimerMode::Once) } } }
+
+#[derive(Component)]
+struct HitFlashBaseColor(Color);
+
+/// Keeps a child level and at a fixed offset from its parent while the parent rotates, e.g. labels and bars over horrors.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct UprightAnchor { pub offset: Vec3, }
 
-fn animate_damage_text_system(
+fn hit_flash_system(
     mut commands: Commands,
     time: Res<Time>,
-    mut query: Query<(Entity, &DamageTextEffect, &mut Transform, &mut Text)>,
+    mut query: Query<(Entity, &mut HitFlash, &mut Sprite, Option<&HitFlashBaseColor>)>,
 ) {
-    let current_time = time.elapsed_seconds();
-    for (entity, effect_data, mut transform, mut text_component) in query.iter_mut() {
-        let time_alive = current_time - effect_data.spawn_time;
-
-        if time_alive > DAMAGE_TEXT_LIFETIME_SECONDS {
-            commands.entity(entity).despawn_recursive(); // Use despawn_recursive for safety
+    for (entity, mut flash, mut sprite, base_color) in query.iter_mut() {
+        let Some(HitFlashBaseColor(base_color)) = base_color else {
+            commands.entity(entity).insert(HitFlashBaseColor(sprite.color));
+            sprite.color = HIT_FLASH_COLOR;
             continue;
+        };
+        flash.timer.tick(time.delta());
+        if flash.timer.finished() {
+            // Leave the sprite alone if something else recoloured it mid-flash (e.g. a behemoth telegraph).
+            if sprite.color == HIT_FLASH_COLOR { sprite.color = *base_color; }
+            commands.entity(entity).remove::<(HitFlash, HitFlashBaseColor)>();
         }
+    }
+}
 
-        transform.translation.y += effect_data.velocity.y * time.delta_seconds();
-        transform.translation.x += effect_data.velocity.x * time.delta_seconds();
+fn hitstop_system(
+    mut events: EventReader<HitstopEvent>,
+    mut hitstop: ResMut<Hitstop>,
+    real_time: Res<Time<Real>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    let delta = real_time.delta_seconds();
+    hitstop.cooldown_secs = (hitstop.cooldown_secs - delta).max(0.0);
+    for event in events.read() {
+        if hitstop.cooldown_secs > 0.0 { continue; }
+        hitstop.remaining_secs = hitstop.remaining_secs.max(event.duration_secs);
+        hitstop.cooldown_secs = HITSTOP_COOLDOWN_SECS;
+        virtual_time.set_relative_speed(HITSTOP_TIME_SCALE);
+    }
+    if hitstop.remaining_secs > 0.0 {
+        hitstop.remaining_secs -= delta;
+        if hitstop.remaining_secs <= 0.0 { hitstop.remaining_secs = 0.0; virtual_time.set_relative_speed(1.0); }
+    }
+}
 
-        if let Some(section) = text_component.sections.get_mut(0) {
-            let alpha_progress = (time_alive / DAMAGE_TEXT_LIFETIME_SECONDS).powf(2.0);
-            section.style.color.set_a((1.0 - alpha_progress).max(0.0));
-        }
+fn upright_anchor_system(mut anchor_query: Query<(&Parent, &UprightAnchor, &mut Transform)>, parent_query: Query<&Transform, Without<UprightAnchor>>) {
+    for (parent, anchor, mut transform) in anchor_query.iter_mut() {
+        let Ok(parent_transform) = parent_query.get(parent.get()) else { continue; };
+        let counter_rotation = parent_transform.rotation.inverse();
+        transform.rotation = counter_rotation;
+        transform.translation = counter_rotation * anchor.offset;
     }
-}
\ No newline at end of file
+}
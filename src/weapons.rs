@@ -5,8 +5,11 @@ use crate::{
     horror::Horror,   // Changed
     components::{Health, Damage},
     game::AppState, // GameState import removed as it was unused
-    audio::{PlaySoundEvent, SoundEffect},
-    visual_effects::{spawn_damage_text}, // Removed ImpactEffectRequest, spawn_impact_effect
+    audio::{PlaySoundAtEvent, SoundEffect},
+    visual_effects::HorrorHitWriters, // Removed ImpactEffectRequest, spawn_impact_effect
+    run_statistics::{DamageDealtEvent, DamageSource},
+    elite_affixes::{AffixWard, apply_horror_damage},
+    critical_hits::CritStats,
 };
 
 // --- Circle of Warding Aura Weapon ---
@@ -89,22 +92,27 @@ impl Plugin for WeaponsPlugin {
     }
 }
 
+/// The aura never despawns, so rather than snapshotting crit stats it rolls against the survivor's current ones each tick.
 fn circle_of_warding_aura_system(
     _commands: Commands,
     time: Res<Time>,
-    mut player_query: Query<(&Transform, &mut CircleOfWarding), With<Survivor>>,
-    mut horror_query: Query<(&Transform, &mut Health, &Horror), With<Horror>>,
+    mut player_query: Query<(&Transform, &Survivor, &mut CircleOfWarding)>,
+    mut horror_query: Query<(Entity, &Transform, &mut Health, Option<&mut AffixWard>), With<Horror>>,
+    mut damage_event_writer: EventWriter<DamageDealtEvent>,
 ) {
-    for (player_transform, mut aura_weapon) in player_query.iter_mut() {
+    for (player_transform, survivor, mut aura_weapon) in player_query.iter_mut() {
         if !aura_weapon.is_active { continue; }
+        let crit = CritStats::for_attack(survivor, None, None);
         aura_weapon.damage_tick_timer.tick(time.delta());
         if aura_weapon.damage_tick_timer.just_finished() {
             let player_position = player_transform.translation.truncate();
             let aura_radius_sq = aura_weapon.current_radius.powi(2);
-            for (horror_transform, mut horror_health, _horror_data) in horror_query.iter_mut() {
+            for (horror_entity, horror_transform, mut horror_health, mut ward_opt) in horror_query.iter_mut() {
                 let horror_position = horror_transform.translation.truncate();
                 if player_position.distance_squared(horror_position) < aura_radius_sq {
-                    horror_health.0 -= aura_weapon.base_damage_per_tick;
+                    let (tick_damage, is_crit) = crit.roll(aura_weapon.base_damage_per_tick);
+                    let dealt = apply_horror_damage(&mut horror_health, ward_opt.as_deref_mut(), tick_damage);
+                    if dealt > 0 { damage_event_writer.send(DamageDealtEvent { source: DamageSource::CircleOfWarding, amount: dealt, target: horror_entity, position: horror_transform.translation, is_crit }); }
                 }
             }
         }
@@ -208,17 +216,17 @@ fn nightmare_larva_movement_system(
     }
 }
 
+/// Like the aura, larvae live as long as the weapon, so each hit rolls against the survivor's current crit stats.
 fn nightmare_larva_collision_system(
-    mut commands: Commands,
     time: Res<Time>,
     mut larva_query: Query<(Entity, &GlobalTransform, &Damage, &mut NightmareLarva)>,
-    mut horror_query: Query<(Entity, &GlobalTransform, &mut Health, &Horror)>, // Added &Horror
-    asset_server: Res<AssetServer>,
-    mut sound_event_writer: EventWriter<PlaySoundEvent>,
-    player_weapon_query: Query<&SwarmOfNightmares, With<Survivor>>,
+    mut horror_query: Query<(Entity, &GlobalTransform, &mut Health, Option<&mut AffixWard>, &Horror)>,
+    mut hit_writers: HorrorHitWriters,
+    player_weapon_query: Query<(&SwarmOfNightmares, &Survivor)>,
 ) {
-    let Ok(weapon_stats) = player_weapon_query.get_single() else { return; };
+    let Ok((weapon_stats, survivor)) = player_weapon_query.get_single() else { return; };
     if !weapon_stats.is_active { return; }
+    let crit = CritStats::for_attack(survivor, None, None);
 
     for (_larva_entity, larva_g_transform, larva_damage, mut larva_data) in larva_query.iter_mut() {
         larva_data.enemies_on_cooldown.retain_mut(|(_enemy_id, timer)| {
@@ -227,14 +235,17 @@ fn nightmare_larva_collision_system(
         let larva_pos = larva_g_transform.translation().truncate();
         let larva_radius = NIGHTMARE_LARVA_SPRITE_SIZE.x / 2.0;
 
-        for (horror_entity, horror_gtransform, mut horror_health, horror_data) in horror_query.iter_mut() { // Added horror_data
+        for (horror_entity, horror_gtransform, mut horror_health, mut ward_opt, horror_data) in horror_query.iter_mut() {
             if larva_data.enemies_on_cooldown.iter().any(|(e_id, _)| *e_id == horror_entity) { continue; }
             let horror_pos = horror_gtransform.translation().truncate();
             let horror_radius = horror_data.size.x / 2.0; // Use horror_data
             if larva_pos.distance(horror_pos) < larva_radius + horror_radius {
-                sound_event_writer.send(PlaySoundEvent(SoundEffect::HorrorHit));
-                horror_health.0 -= larva_damage.0;
-                spawn_damage_text(&mut commands, &asset_server, horror_gtransform.translation(), larva_damage.0, &time);
+                hit_writers.sound.send(PlaySoundAtEvent(SoundEffect::HorrorHit, horror_gtransform.translation()));
+                let (hit_damage, is_crit) = crit.roll(larva_damage.0);
+                let dealt = apply_horror_damage(&mut horror_health, ward_opt.as_deref_mut(), hit_damage);
+                if dealt > 0 {
+                    hit_writers.damage.send(DamageDealtEvent { source: DamageSource::SwarmOfNightmares, amount: dealt, target: horror_entity, position: horror_gtransform.translation(), is_crit });
+                }
                 larva_data.enemies_on_cooldown.push((horror_entity, Timer::from_seconds(weapon_stats.hit_cooldown_duration, TimerMode::Once)));
             }
         }
@@ -0,0 +1,168 @@
+use bevy::{prelude::*, asset::io::file::FileAssetReader};
+use serde::Deserialize;
+use crate::{
+    game::{AppState, GameState},
+    survivor::Survivor,
+    components::Health,
+    audio::{PlaySoundEvent, SoundEffect},
+    omens::RunModifiers,
+};
+
+/// Resolved against the same base directory the `AssetServer` loads `assets/` from, not the working directory.
+const XP_CURVE_PATH: &str = "assets/data/xp_curve.json";
+
+/// What the survivor gets each time the XP bar fills at the max level.
+#[derive(Debug, Clone, Deserialize)]
+pub enum XpOverflowReward {
+    Heal { amount: i32 },
+    Score { points: u32 },
+    Gold { amount: u32 },
+}
+
+/// Experience needed per level. Levels past the end of `levels` grow linearly by `growth_per_level`.
+#[derive(Resource, Debug, Clone, Deserialize)]
+pub struct XpCurve {
+    pub levels: Vec<u32>,
+    pub growth_per_level: u32,
+    #[serde(default)]
+    pub max_level: Option<u32>,
+    pub overflow_reward: XpOverflowReward,
+}
+
+impl Default for XpCurve {
+    fn default() -> Self {
+        Self {
+            levels: vec![100, 150, 250, 400, 600, 850, 1100, 1400, 1800, 2500],
+            growth_per_level: 500,
+            max_level: Some(60),
+            overflow_reward: XpOverflowReward::Heal { amount: 25 },
+        }
+    }
+}
+
+impl XpCurve {
+    /// Read synchronously while the plugin is built rather than through a custom `AssetLoader`: the curve is
+    /// plain tuning data that must be in place before the first soul is collected, and an asset handle would
+    /// leave every XP system to cope with a curve that hasn't loaded yet.
+    fn load() -> Self {
+        let path = FileAssetReader::get_base_path().join(XP_CURVE_PATH);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents)
+                .unwrap_or_else(|| { warn!("XP curve at {} is invalid, using the built-in curve.", path.display()); Self::default() }),
+            Err(err) => { warn!("Could not read XP curve at {} ({}), using the built-in curve.", path.display(), err); Self::default() }
+        }
+    }
+
+    /// Rejects curves where some level would cost no experience, since levelling would never stop.
+    fn parse(contents: &str) -> Option<Self> {
+        serde_json::from_str::<Self>(contents).ok().filter(|curve| !curve.levels.is_empty() && curve.levels.iter().all(|xp| *xp > 0))
+    }
+
+    pub fn experience_for_level(&self, level: u32) -> u32 {
+        if level == 0 { return 0; }
+        let index = level as usize - 1;
+        match self.levels.get(index) {
+            Some(xp) => *xp,
+            None => {
+                let levels_past_table = u32::try_from(index + 1 - self.levels.len()).unwrap_or(u32::MAX);
+                self.levels.last().copied().unwrap_or(0).saturating_add(levels_past_table.saturating_mul(self.growth_per_level))
+            }
+        }
+    }
+
+    pub fn is_max_level(&self, level: u32) -> bool { self.max_level.is_some_and(|max| level >= max) }
+}
+
+pub struct XpCurvePlugin;
+
+impl Plugin for XpCurvePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(XpCurve::load())
+            .add_systems(Update, (
+                process_pending_level_ups_system,
+                apply_xp_overflow_rewards_system,
+            ).run_if(in_state(AppState::InGame)));
+    }
+}
+
+/// Opens the next queued upgrade screen. Runs again after each choice returns to `InGame`, so a big soul
+/// that grants several levels shows consecutive screens.
+fn process_pending_level_ups_system(mut survivor_query: Query<&mut Survivor>, mut next_app_state: ResMut<NextState<AppState>>) {
+    if next_app_state.0.is_some() { return; }
+    let Ok(mut survivor) = survivor_query.get_single_mut() else { return; };
+    if survivor.pending_level_ups == 0 { return; }
+    survivor.pending_level_ups -= 1;
+    next_app_state.set(AppState::LevelUp);
+}
+
+fn apply_xp_overflow_rewards_system(
+    xp_curve: Res<XpCurve>,
+    mut survivor_query: Query<(&mut Survivor, &mut Health)>,
+    mut game_state: ResMut<GameState>,
+    run_modifiers: Res<RunModifiers>,
+    mut sound_event_writer: EventWriter<PlaySoundEvent>,
+) {
+    let Ok((mut survivor, mut health)) = survivor_query.get_single_mut() else { return; };
+    if survivor.pending_overflow_rewards == 0 { return; }
+    for _ in 0..survivor.pending_overflow_rewards {
+        match xp_curve.overflow_reward {
+            XpOverflowReward::Heal { amount } => { health.0 = (health.0 + amount).min(survivor.max_health); }
+            XpOverflowReward::Score { points } => { game_state.score += (points as f32 * run_modifiers.reward_multiplier).round() as u32; }
+            XpOverflowReward::Gold { amount } => { game_state.gold += amount; }
+        }
+    }
+    survivor.pending_overflow_rewards = 0;
+    sound_event_writer.send(PlaySoundEvent(SoundEffect::SoulCollect));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve(levels: Vec<u32>, growth_per_level: u32) -> XpCurve {
+        XpCurve { levels, growth_per_level, max_level: None, overflow_reward: XpOverflowReward::Heal { amount: 1 } }
+    }
+
+    #[test]
+    fn experience_for_level_reads_the_table_then_grows_linearly() {
+        let curve = curve(vec![100, 150, 250], 50);
+        assert_eq!(curve.experience_for_level(0), 0);
+        assert_eq!(curve.experience_for_level(1), 100);
+        assert_eq!(curve.experience_for_level(3), 250);
+        assert_eq!(curve.experience_for_level(4), 300);
+        assert_eq!(curve.experience_for_level(6), 400);
+    }
+
+    #[test]
+    fn experience_for_level_saturates_instead_of_overflowing() {
+        let curve = curve(vec![u32::MAX - 10], 50);
+        assert_eq!(curve.experience_for_level(2), u32::MAX);
+        assert_eq!(curve.experience_for_level(u32::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn is_max_level_respects_the_cap() {
+        let mut curve = curve(vec![100], 0);
+        assert!(!curve.is_max_level(1000));
+        curve.max_level = Some(10);
+        assert!(!curve.is_max_level(9));
+        assert!(curve.is_max_level(10));
+    }
+
+    #[test]
+    fn parse_accepts_a_valid_curve() {
+        let parsed = XpCurve::parse(r#"{ "levels": [10, 20], "growth_per_level": 5, "overflow_reward": { "Score": { "points": 3 } } }"#).unwrap();
+        assert_eq!(parsed.levels, vec![10, 20]);
+        assert_eq!(parsed.max_level, None);
+        assert_eq!(parsed.experience_for_level(4), 30);
+    }
+
+    #[test]
+    fn parse_rejects_empty_and_zero_cost_curves() {
+        assert!(XpCurve::parse(r#"{ "levels": [], "growth_per_level": 5, "overflow_reward": { "Score": { "points": 3 } } }"#).is_none());
+        assert!(XpCurve::parse(r#"{ "levels": [10, 0, 20], "growth_per_level": 5, "overflow_reward": { "Score": { "points": 3 } } }"#).is_none());
+        assert!(XpCurve::parse(r#"{ "levels": [0], "growth_per_level": 0, "overflow_reward": { "Score": { "points": 3 } } }"#).is_none());
+        assert!(XpCurve::parse("not json").is_none());
+    }
+}